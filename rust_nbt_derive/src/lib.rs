@@ -0,0 +1,313 @@
+//! Derive macros for `rust_nbt`'s `NbtSerialize` and `NbtDeserialize` traits.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Fields, GenericArgument, Ident, LitStr,
+    PathArguments, Result, Type,
+};
+
+/// Derives `NbtSerialize` for a struct with named fields, writing it as a compound.
+///
+/// Field attributes:
+/// - `#[nbt(rename = "Name")]` stores the field under a different key.
+/// - `#[nbt(byte)]`, `short`, `int`, `long`, `float` or `double` casts a numeric field to that
+///   tag type.
+/// - `#[nbt(byte_array)]`, `int_array` or `long_array` stores a `Vec` of integers as that
+///   array type instead of a list.
+/// - `#[nbt(extra)]` on a `CompoundTag` field collects the keys no other field names, and
+///   writes them back out, so unknown data survives a round trip. Keys that a named field
+///   claims are skipped, so the field's own value always wins.
+///
+/// The numeric attributes convert through `rust_nbt::NbtCast`, in both directions. An unsigned
+/// field of the same width is reinterpreted as two's complement, so a `u8` of 200 is stored as
+/// the byte -56 and reads back as 200; this is how `Vec<u8>` light arrays and `Vec<u64>` block
+/// states round trip. Any other integer must fit in the tag's width, or serializing fails with
+/// `InvalidInput` and deserializing with `InvalidData`. A `bool` can be stored with
+/// `#[nbt(byte)]`, and reads back as `true` for any nonzero byte. A `SystemTime` can be stored
+/// with `#[nbt(long)]`, as milliseconds since the Unix epoch.
+///
+/// `Option` fields are left out of the compound when `None`.
+#[proc_macro_derive(NbtSerialize, attributes(nbt))]
+pub fn derive_nbt_serialize(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(input as DeriveInput);
+    expand_serialize(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derives `NbtDeserialize` for a struct with named fields, reading it from a compound.
+///
+/// Accepts the same attributes as `NbtSerialize`. Missing keys are an error unless the field
+/// is an `Option`, and keys the struct doesn't name are ignored.
+#[proc_macro_derive(NbtDeserialize, attributes(nbt))]
+pub fn derive_nbt_deserialize(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(input as DeriveInput);
+    expand_deserialize(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// How a field maps to a tag.
+enum Encoding {
+    /// Through the field type's own trait implementation.
+    Default,
+    /// Cast to a numeric tag, such as `Float`.
+    Scalar(Ident, TokenStream2),
+    /// Cast element-wise to an array tag, such as `IntArray`.
+    Array(Ident, TokenStream2),
+    /// A compound of the keys not claimed by other fields.
+    Extra,
+}
+
+struct Field {
+    ident: Ident,
+    key: String,
+    optional: bool,
+    encoding: Encoding,
+}
+
+fn parse_fields(input: &DeriveInput) -> Result<Vec<Field>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            input,
+            "Only structs with named fields are supported",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            input,
+            "Only structs with named fields are supported",
+        ));
+    };
+    let fields: Vec<Field> = fields
+        .named
+        .iter()
+        .map(|field| {
+            let ident: Ident = field.ident.clone().expect("Named fields have identifiers");
+            let mut key: String = ident.to_string();
+            let mut encoding: Encoding = Encoding::Default;
+            for attr in field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("nbt"))
+            {
+                attr.parse_nested_meta(|meta| {
+                    let name: String = meta
+                        .path
+                        .get_ident()
+                        .map(Ident::to_string)
+                        .unwrap_or_default();
+                    let (variant, element): (&str, TokenStream2) = match name.as_str() {
+                        "rename" => {
+                            key = meta.value()?.parse::<LitStr>()?.value();
+                            return Ok(());
+                        }
+                        "extra" => {
+                            encoding = Encoding::Extra;
+                            return Ok(());
+                        }
+                        "byte" | "byte_array" => ("Byte", quote!(i8)),
+                        "short" => ("Short", quote!(i16)),
+                        "int" | "int_array" => ("Int", quote!(i32)),
+                        "long" | "long_array" => ("Long", quote!(i64)),
+                        "float" => ("Float", quote!(f32)),
+                        "double" => ("Double", quote!(f64)),
+                        _ => return Err(meta.error("Unknown nbt attribute")),
+                    };
+                    encoding = if name.ends_with("_array") {
+                        Encoding::Array(
+                            Ident::new(&format!("{}Array", variant), ident.span()),
+                            element,
+                        )
+                    } else {
+                        Encoding::Scalar(Ident::new(variant, ident.span()), element)
+                    };
+                    Ok(())
+                })?;
+            }
+            Ok(Field {
+                ident,
+                key,
+                optional: is_option(&field.ty),
+                encoding,
+            })
+        })
+        .collect::<Result<_>>()?;
+    let extras: usize = fields
+        .iter()
+        .filter(|field| matches!(field.encoding, Encoding::Extra))
+        .count();
+    if extras > 1 {
+        return Err(Error::new_spanned(
+            input,
+            "Only one field can be marked #[nbt(extra)]",
+        ));
+    }
+    Ok(fields)
+}
+
+/// The keys named fields read and write, which an `extra` field leaves alone.
+fn claimed_keys(fields: &[Field]) -> Vec<String> {
+    fields
+        .iter()
+        .filter(|field| !matches!(field.encoding, Encoding::Extra))
+        .map(|field| field.key.clone())
+        .collect()
+}
+
+fn is_option(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    path.path.segments.last().is_some_and(|segment| {
+        segment.ident == "Option"
+            && matches!(
+                &segment.arguments,
+                PathArguments::AngleBracketed(args)
+                    if matches!(args.args.first(), Some(GenericArgument::Type(_)))
+            )
+    })
+}
+
+fn expand_serialize(input: &DeriveInput) -> Result<TokenStream2> {
+    let name: &Ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let fields: Vec<Field> = parse_fields(input)?;
+    let keys: Vec<String> = claimed_keys(&fields);
+    let inserts = fields.into_iter().map(|field| {
+        let Field {
+            ident,
+            key,
+            optional,
+            encoding,
+        } = field;
+        let encode: TokenStream2 = match encoding {
+            Encoding::Default => quote!(::rust_nbt::NbtSerialize::to_nbt(value)?),
+            Encoding::Scalar(variant, element) => quote!(::rust_nbt::Tag::#variant(
+                ::rust_nbt::NbtCast::<#element>::to_payload(value)?
+            )),
+            Encoding::Array(variant, element) => quote!(::rust_nbt::Tag::#variant(
+                value
+                    .iter()
+                    .map(::rust_nbt::NbtCast::<#element>::to_payload)
+                    .collect::<::std::io::Result<_>>()?
+            )),
+            Encoding::Extra => {
+                return quote! {
+                    for (key, value) in &self.#ident {
+                        if ![#(#keys),*].contains(&key.as_str()) {
+                            compound.insert(key.clone(), value.clone());
+                        }
+                    }
+                };
+            }
+        };
+        if optional {
+            quote! {
+                if let ::std::option::Option::Some(value) = &self.#ident {
+                    compound.insert(#key.to_string(), #encode);
+                }
+            }
+        } else {
+            quote! {
+                let value = &self.#ident;
+                compound.insert(#key.to_string(), #encode);
+            }
+        }
+    });
+    Ok(quote! {
+        impl #impl_generics ::rust_nbt::NbtSerialize for #name #type_generics #where_clause {
+            fn to_nbt(&self) -> ::std::io::Result<::rust_nbt::Tag> {
+                let mut compound: ::rust_nbt::CompoundTag = ::rust_nbt::CompoundTag::new();
+                #(#inserts)*
+                ::std::result::Result::Ok(::rust_nbt::Tag::Compound(compound))
+            }
+        }
+    })
+}
+
+fn expand_deserialize(input: &DeriveInput) -> Result<TokenStream2> {
+    let name: &Ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let fields: Vec<Field> = parse_fields(input)?;
+    let keys: Vec<String> = claimed_keys(&fields);
+    let fields = fields.into_iter().map(|field| {
+        let Field {
+            ident,
+            key,
+            optional,
+            encoding,
+        } = field;
+        let decode: TokenStream2 = match encoding {
+            Encoding::Default => quote!(::rust_nbt::NbtDeserialize::from_nbt(value)?),
+            Encoding::Scalar(variant, element) => quote! {
+                match value {
+                    ::rust_nbt::Tag::#variant(value) => {
+                        ::rust_nbt::NbtCast::<#element>::from_payload(*value)?
+                    }
+                    other => return ::std::result::Result::Err(
+                        ::rust_nbt::unexpected_tag(::rust_nbt::TagID::#variant, other),
+                    ),
+                }
+            },
+            Encoding::Array(variant, element) => quote! {
+                match value {
+                    ::rust_nbt::Tag::#variant(value) => value
+                        .iter()
+                        .map(|entry| ::rust_nbt::NbtCast::<#element>::from_payload(*entry))
+                        .collect::<::std::io::Result<_>>()?,
+                    other => return ::std::result::Result::Err(
+                        ::rust_nbt::unexpected_tag(::rust_nbt::TagID::#variant, other),
+                    ),
+                }
+            },
+            Encoding::Extra => {
+                return quote! {
+                    #ident: compound
+                        .iter()
+                        .filter(|(key, _)| ![#(#keys),*].contains(&key.as_str()))
+                        .map(|(key, value)| (key.clone(), value.clone()))
+                        .collect()
+                };
+            }
+        };
+        let missing: TokenStream2 = if optional {
+            quote!(::std::option::Option::None)
+        } else {
+            quote!(return ::std::result::Result::Err(::rust_nbt::missing_key(#key)))
+        };
+        let found: TokenStream2 = if optional {
+            quote!(::std::option::Option::Some(#decode))
+        } else {
+            decode
+        };
+        quote! {
+            #ident: match compound.get(#key) {
+                ::std::option::Option::Some(value) => {
+                    let value: ::std::borrow::Cow<::rust_nbt::Tag> =
+                        ::rust_nbt::decode_raw(value)?;
+                    let value: &::rust_nbt::Tag = &value;
+                    #found
+                }
+                ::std::option::Option::None => #missing,
+            }
+        }
+    });
+    Ok(quote! {
+        impl #impl_generics ::rust_nbt::NbtDeserialize for #name #type_generics #where_clause {
+            fn from_nbt(tag: &::rust_nbt::Tag) -> ::std::io::Result<Self> {
+                let tag: ::std::borrow::Cow<::rust_nbt::Tag> = ::rust_nbt::decode_raw(tag)?;
+                let ::rust_nbt::Tag::Compound(compound) = &*tag else {
+                    return ::std::result::Result::Err(
+                        ::rust_nbt::unexpected_tag(::rust_nbt::TagID::Compound, &tag),
+                    );
+                };
+                ::std::result::Result::Ok(#name {
+                    #(#fields,)*
+                })
+            }
+        }
+    })
+}
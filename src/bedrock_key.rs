@@ -0,0 +1,314 @@
+/// A key in a Bedrock world's LevelDB database.
+///
+/// Keys are decoded by shape: the well-known names first, then actor and digest prefixes,
+/// then the fixed-length chunk layouts. Anything else is kept as a name when it's UTF-8, or
+/// as raw bytes otherwise, so every key round trips through [`BedrockKey::encode`].
+///
+/// ```
+/// use rust_nbt::{BedrockKey, ChunkRecord};
+///
+/// let key: BedrockKey = BedrockKey::Chunk {
+///     x: -1,
+///     z: 2,
+///     dimension: 1,
+///     record: ChunkRecord::SubChunkPrefix,
+///     subchunk: Some(-4),
+/// };
+/// let bytes: Vec<u8> = key.encode();
+/// assert_eq!(bytes.len(), 14);
+/// assert_eq!(BedrockKey::decode(&bytes), key);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BedrockKey {
+    /// A record of a chunk. The dimension is 0 for the overworld, 1 for the nether and 2 for
+    /// the end; the overworld's is left out of the key. Only sub-chunk records have an index.
+    Chunk {
+        x: i32,
+        z: i32,
+        dimension: i32,
+        record: ChunkRecord,
+        subchunk: Option<i8>,
+    },
+    /// The list of actor IDs stored in a chunk, under `digp`.
+    ActorDigest { x: i32, z: i32, dimension: i32 },
+    /// An actor's data, under `actorprefix` and its unique ID.
+    Actor([u8; 8]),
+    /// `~local_player`, the player in a singleplayer world.
+    LocalPlayer,
+    /// `portals`, the nether portal records.
+    Portals,
+    /// Any other text key, such as `BiomeData`, `scoreboard` or `player_<uuid>`.
+    Named(String),
+    /// A key that isn't text and doesn't match a known layout.
+    Unknown(Vec<u8>),
+}
+
+/// The record type byte of a chunk key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChunkRecord {
+    Data3D,
+    Version,
+    Data2D,
+    Data2DLegacy,
+    SubChunkPrefix,
+    LegacyTerrain,
+    BlockEntity,
+    Entity,
+    PendingTicks,
+    LegacyBlockExtraData,
+    BiomeState,
+    FinalizedState,
+    ConversionData,
+    BorderBlocks,
+    HardcodedSpawners,
+    RandomTicks,
+    Checksums,
+    GenerationSeed,
+    GeneratedPreCavesAndCliffsBlending,
+    BlendingBiomeHeight,
+    MetaDataHash,
+    BlendingData,
+    ActorDigestVersion,
+    LegacyVersion,
+    Other(u8),
+}
+
+impl ChunkRecord {
+    pub fn id(&self) -> u8 {
+        match self {
+            ChunkRecord::Data3D => 0x2b,
+            ChunkRecord::Version => 0x2c,
+            ChunkRecord::Data2D => 0x2d,
+            ChunkRecord::Data2DLegacy => 0x2e,
+            ChunkRecord::SubChunkPrefix => 0x2f,
+            ChunkRecord::LegacyTerrain => 0x30,
+            ChunkRecord::BlockEntity => 0x31,
+            ChunkRecord::Entity => 0x32,
+            ChunkRecord::PendingTicks => 0x33,
+            ChunkRecord::LegacyBlockExtraData => 0x34,
+            ChunkRecord::BiomeState => 0x35,
+            ChunkRecord::FinalizedState => 0x36,
+            ChunkRecord::ConversionData => 0x37,
+            ChunkRecord::BorderBlocks => 0x38,
+            ChunkRecord::HardcodedSpawners => 0x39,
+            ChunkRecord::RandomTicks => 0x3a,
+            ChunkRecord::Checksums => 0x3b,
+            ChunkRecord::GenerationSeed => 0x3c,
+            ChunkRecord::GeneratedPreCavesAndCliffsBlending => 0x3d,
+            ChunkRecord::BlendingBiomeHeight => 0x3e,
+            ChunkRecord::MetaDataHash => 0x3f,
+            ChunkRecord::BlendingData => 0x40,
+            ChunkRecord::ActorDigestVersion => 0x41,
+            ChunkRecord::LegacyVersion => 0x76,
+            ChunkRecord::Other(id) => *id,
+        }
+    }
+
+    /// Maps a record type byte to its variant, or [`ChunkRecord::Other`] if it isn't known.
+    pub fn from_id(id: u8) -> Self {
+        match id {
+            0x2b => ChunkRecord::Data3D,
+            0x2c => ChunkRecord::Version,
+            0x2d => ChunkRecord::Data2D,
+            0x2e => ChunkRecord::Data2DLegacy,
+            0x2f => ChunkRecord::SubChunkPrefix,
+            0x30 => ChunkRecord::LegacyTerrain,
+            0x31 => ChunkRecord::BlockEntity,
+            0x32 => ChunkRecord::Entity,
+            0x33 => ChunkRecord::PendingTicks,
+            0x34 => ChunkRecord::LegacyBlockExtraData,
+            0x35 => ChunkRecord::BiomeState,
+            0x36 => ChunkRecord::FinalizedState,
+            0x37 => ChunkRecord::ConversionData,
+            0x38 => ChunkRecord::BorderBlocks,
+            0x39 => ChunkRecord::HardcodedSpawners,
+            0x3a => ChunkRecord::RandomTicks,
+            0x3b => ChunkRecord::Checksums,
+            0x3c => ChunkRecord::GenerationSeed,
+            0x3d => ChunkRecord::GeneratedPreCavesAndCliffsBlending,
+            0x3e => ChunkRecord::BlendingBiomeHeight,
+            0x3f => ChunkRecord::MetaDataHash,
+            0x40 => ChunkRecord::BlendingData,
+            0x41 => ChunkRecord::ActorDigestVersion,
+            0x76 => ChunkRecord::LegacyVersion,
+            id => ChunkRecord::Other(id),
+        }
+    }
+}
+
+const LOCAL_PLAYER_KEY: &[u8] = b"~local_player";
+const PORTALS_KEY: &[u8] = b"portals";
+const ACTOR_DIGEST_PREFIX: &[u8] = b"digp";
+const ACTOR_PREFIX: &[u8] = b"actorprefix";
+
+impl BedrockKey {
+    /// Interprets a database key.
+    pub fn decode(key: &[u8]) -> Self {
+        if key == LOCAL_PLAYER_KEY {
+            return BedrockKey::LocalPlayer;
+        }
+        if key == PORTALS_KEY {
+            return BedrockKey::Portals;
+        }
+        if let Some(rest) = key.strip_prefix(ACTOR_DIGEST_PREFIX) {
+            if let Some((x, z, dimension)) = decode_position(rest) {
+                return BedrockKey::ActorDigest { x, z, dimension };
+            }
+        }
+        if let Some(Ok(id)) = key.strip_prefix(ACTOR_PREFIX).map(<[u8; 8]>::try_from) {
+            return BedrockKey::Actor(id);
+        }
+        if let Some(chunk) = decode_chunk(key) {
+            return chunk;
+        }
+        match std::str::from_utf8(key) {
+            Ok(name) => BedrockKey::Named(name.to_string()),
+            Err(_) => BedrockKey::Unknown(key.to_vec()),
+        }
+    }
+
+    /// Encodes the key as stored in the database.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut key: Vec<u8> = Vec::new();
+        match self {
+            BedrockKey::Chunk {
+                x,
+                z,
+                dimension,
+                record,
+                subchunk,
+            } => {
+                encode_position(&mut key, *x, *z, *dimension);
+                key.push(record.id());
+                key.extend(subchunk.map(|index| index as u8));
+            }
+            BedrockKey::ActorDigest { x, z, dimension } => {
+                key.extend_from_slice(ACTOR_DIGEST_PREFIX);
+                encode_position(&mut key, *x, *z, *dimension);
+            }
+            BedrockKey::Actor(id) => {
+                key.extend_from_slice(ACTOR_PREFIX);
+                key.extend_from_slice(id);
+            }
+            BedrockKey::LocalPlayer => key.extend_from_slice(LOCAL_PLAYER_KEY),
+            BedrockKey::Portals => key.extend_from_slice(PORTALS_KEY),
+            BedrockKey::Named(name) => key.extend_from_slice(name.as_bytes()),
+            BedrockKey::Unknown(bytes) => key.extend_from_slice(bytes),
+        }
+        key
+    }
+}
+
+/// Decodes a chunk key: 8 or 12 bytes of position, a record type, and a sub-chunk index
+/// for sub-chunk records only.
+fn decode_chunk(key: &[u8]) -> Option<BedrockKey> {
+    let (position, record, subchunk): (&[u8], u8, Option<i8>) = match key.len() {
+        9 | 13 => (&key[..key.len() - 1], key[key.len() - 1], None),
+        10 | 14 => (
+            &key[..key.len() - 2],
+            key[key.len() - 2],
+            Some(key[key.len() - 1] as i8),
+        ),
+        _ => return None,
+    };
+    let record: ChunkRecord = ChunkRecord::from_id(record);
+    if matches!(record, ChunkRecord::Other(_))
+        || (record == ChunkRecord::SubChunkPrefix) != subchunk.is_some()
+    {
+        return None;
+    }
+    let (x, z, dimension) = decode_position(position)?;
+    Some(BedrockKey::Chunk {
+        x,
+        z,
+        dimension,
+        record,
+        subchunk,
+    })
+}
+
+/// Decodes little-endian chunk coordinates, followed by a dimension unless it's the
+/// overworld.
+fn decode_position(bytes: &[u8]) -> Option<(i32, i32, i32)> {
+    let int = |index: usize| -> i32 {
+        i32::from_le_bytes(bytes[index * 4..index * 4 + 4].try_into().unwrap())
+    };
+    match bytes.len() {
+        8 => Some((int(0), int(1), 0)),
+        12 if int(2) != 0 => Some((int(0), int(1), int(2))),
+        _ => None,
+    }
+}
+
+fn encode_position(key: &mut Vec<u8>, x: i32, z: i32, dimension: i32) {
+    key.extend_from_slice(&x.to_le_bytes());
+    key.extend_from_slice(&z.to_le_bytes());
+    if dimension != 0 {
+        key.extend_from_slice(&dimension.to_le_bytes());
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bedrock_keys() {
+        let keys: Vec<BedrockKey> = vec![
+            BedrockKey::Chunk {
+                x: 3,
+                z: -7,
+                dimension: 0,
+                record: ChunkRecord::Version,
+                subchunk: None,
+            },
+            BedrockKey::Chunk {
+                x: 3,
+                z: -7,
+                dimension: 2,
+                record: ChunkRecord::Data3D,
+                subchunk: None,
+            },
+            BedrockKey::Chunk {
+                x: 0,
+                z: 0,
+                dimension: 0,
+                record: ChunkRecord::SubChunkPrefix,
+                subchunk: Some(5),
+            },
+            BedrockKey::ActorDigest {
+                x: -1,
+                z: 1,
+                dimension: 1,
+            },
+            BedrockKey::Actor([0, 0, 0, 1, 0, 0, 0, 2]),
+            BedrockKey::LocalPlayer,
+            BedrockKey::Portals,
+            BedrockKey::Named("BiomeData".to_string()),
+            BedrockKey::Unknown(vec![0xff, 0xfe]),
+        ];
+        for key in keys {
+            assert_eq!(BedrockKey::decode(&key.encode()), key);
+        }
+
+        let mut version: Vec<u8> = Vec::new();
+        version.extend_from_slice(&3i32.to_le_bytes());
+        version.extend_from_slice(&(-7i32).to_le_bytes());
+        version.push(0x2c);
+        assert_eq!(
+            BedrockKey::decode(&version),
+            BedrockKey::Chunk {
+                x: 3,
+                z: -7,
+                dimension: 0,
+                record: ChunkRecord::Version,
+                subchunk: None,
+            }
+        );
+        assert_eq!(
+            BedrockKey::decode(b"mVillages"),
+            BedrockKey::Named("mVillages".to_string())
+        );
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{CompoundTag, LongArrayTag, NbtDeserialize, NbtSerialize};
+use indexmap::IndexMap;
+use std::io::{Error, ErrorKind, Result};
+
+/// A chunk in the 1.18+ Anvil format, as stored in a region file.
+///
+/// Read one with [`NbtDeserialize::from_nbt`] on the output of [`crate::RegionFile::chunk`].
+/// Keys not modelled here are kept in the `extra` compounds, so writing a chunk back doesn't
+/// lose data from newer or modded versions.
+///
+/// ```
+/// use rust_nbt::{nbt, Chunk, NbtDeserialize};
+///
+/// let tag = nbt!({
+///     DataVersion: 3465, xPos: 1, yPos: -4, zPos: 2, Status: "minecraft:full",
+///     sections: [{ Y: -4i8, biomes: { palette: ["minecraft:plains"] } }],
+///     block_entities: [],
+///     Heightmaps: { WORLD_SURFACE: [L; 0] },
+/// });
+/// let chunk: Chunk = Chunk::from_nbt(&tag).unwrap();
+/// assert_eq!(chunk.sections[0].biomes.as_ref().unwrap().palette, ["minecraft:plains"]);
+/// ```
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct Chunk {
+    #[nbt(rename = "DataVersion")]
+    pub data_version: i32,
+    #[nbt(rename = "xPos")]
+    pub x_pos: i32,
+    #[nbt(rename = "yPos")]
+    pub y_pos: i32,
+    #[nbt(rename = "zPos")]
+    pub z_pos: i32,
+    #[nbt(rename = "Status")]
+    pub status: String,
+    #[nbt(rename = "LastUpdate")]
+    pub last_update: Option<i64>,
+    #[nbt(rename = "InhabitedTime")]
+    pub inhabited_time: Option<i64>,
+    #[nbt(rename = "isLightOn")]
+    pub is_light_on: Option<bool>,
+    pub sections: Vec<Section>,
+    pub block_entities: Vec<CompoundTag>,
+    #[nbt(rename = "Heightmaps")]
+    pub heightmaps: Heightmaps,
+    pub fluid_ticks: Option<Vec<CompoundTag>>,
+    pub block_ticks: Option<Vec<CompoundTag>>,
+    pub structures: Option<CompoundTag>,
+    #[nbt(extra)]
+    pub extra: CompoundTag,
+}
+
+/// A 16 block tall slice of a chunk.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct Section {
+    #[nbt(rename = "Y")]
+    pub y: i8,
+    pub block_states: Option<BlockStates>,
+    pub biomes: Option<Biomes>,
+    #[nbt(rename = "BlockLight", byte_array)]
+    pub block_light: Option<Vec<u8>>,
+    #[nbt(rename = "SkyLight", byte_array)]
+    pub sky_light: Option<Vec<u8>>,
+    #[nbt(extra)]
+    pub extra: CompoundTag,
+}
+
+/// The palette of a section's blocks and their packed indices. `data` is absent when the
+/// palette has a single entry.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct BlockStates {
+    pub palette: Vec<BlockState>,
+    #[nbt(long_array)]
+    pub data: Option<Vec<i64>>,
+}
+
+/// A block and its properties, such as `minecraft:oak_log` with `axis: "y"`.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct BlockState {
+    #[nbt(rename = "Name")]
+    pub name: String,
+    #[nbt(rename = "Properties")]
+    pub properties: Option<IndexMap<String, String>>,
+}
+
+/// The palette of a section's biomes and their packed indices. `data` is absent when the
+/// palette has a single entry.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct Biomes {
+    pub palette: Vec<String>,
+    #[nbt(long_array)]
+    pub data: Option<Vec<i64>>,
+}
+
+/// The packed heightmaps a chunk stores, each present only once generation has computed it.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq, Default)]
+pub struct Heightmaps {
+    #[nbt(rename = "MOTION_BLOCKING", long_array)]
+    pub motion_blocking: Option<Vec<i64>>,
+    #[nbt(rename = "MOTION_BLOCKING_NO_LEAVES", long_array)]
+    pub motion_blocking_no_leaves: Option<Vec<i64>>,
+    #[nbt(rename = "OCEAN_FLOOR", long_array)]
+    pub ocean_floor: Option<Vec<i64>>,
+    #[nbt(rename = "OCEAN_FLOOR_WG", long_array)]
+    pub ocean_floor_wg: Option<Vec<i64>>,
+    #[nbt(rename = "WORLD_SURFACE", long_array)]
+    pub world_surface: Option<Vec<i64>>,
+    #[nbt(rename = "WORLD_SURFACE_WG", long_array)]
+    pub world_surface_wg: Option<Vec<i64>>,
+}
+
+/// Unpacks `length` palette indices of `bits` bits each from a packed long array, such as
+/// `block_states.data`.
+///
+/// Indices don't span longs, as in 1.16 and later: each long holds `64 / bits` of them,
+/// starting from the least significant bits, and any leftover high bits are padding.
+pub fn unpack_palette_indices(data: &LongArrayTag, bits: u32, length: usize) -> Result<Vec<u32>> {
+    let per_long: usize = values_per_long(bits)?;
+    if data.len() < length.div_ceil(per_long) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "{} longs can't hold {} indices of {} bits",
+                data.len(),
+                length,
+                bits
+            ),
+        ));
+    }
+    let mask: u64 = (1 << bits) - 1;
+    Ok((0..length)
+        .map(|index| {
+            let long: u64 = data[index / per_long] as u64;
+            (long >> ((index % per_long) as u32 * bits) & mask) as u32
+        })
+        .collect())
+}
+
+/// Packs palette indices into a long array of `bits` bits each, the inverse of
+/// [`unpack_palette_indices`]. Fails if an index doesn't fit in `bits`.
+pub fn pack_palette_indices(indices: &[u32], bits: u32) -> Result<LongArrayTag> {
+    let per_long: usize = values_per_long(bits)?;
+    let mut data: LongArrayTag = vec![0; indices.len().div_ceil(per_long)];
+    for (index, value) in indices.iter().enumerate() {
+        if u64::from(*value) >> bits != 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Index {} doesn't fit in {} bits", value, bits),
+            ));
+        }
+        data[index / per_long] |= (u64::from(*value) << ((index % per_long) as u32 * bits)) as i64;
+    }
+    Ok(data)
+}
+
+/// Bits per entry of a heightmap, enough for the 384 block tall overworld.
+pub const HEIGHTMAP_BITS: u32 = 9;
+
+/// Unpacks a heightmap such as `MOTION_BLOCKING` into a grid indexed as `[z][x]`.
+pub fn unpack_heightmap(data: &LongArrayTag) -> Result<[[u16; 16]; 16]> {
+    let heights: Vec<u32> = unpack_palette_indices(data, HEIGHTMAP_BITS, 256)?;
+    let mut grid: [[u16; 16]; 16] = [[0; 16]; 16];
+    for (index, height) in heights.into_iter().enumerate() {
+        grid[index / 16][index % 16] = height as u16;
+    }
+    Ok(grid)
+}
+
+/// Packs a `[z][x]` grid of heights into the 37 longs a heightmap is stored as. Fails if a
+/// height doesn't fit in [`HEIGHTMAP_BITS`].
+pub fn pack_heightmap(grid: &[[u16; 16]; 16]) -> Result<LongArrayTag> {
+    let heights: Vec<u32> = grid
+        .iter()
+        .flatten()
+        .map(|height| u32::from(*height))
+        .collect();
+    pack_palette_indices(&heights, HEIGHTMAP_BITS)
+}
+
+fn values_per_long(bits: u32) -> Result<usize> {
+    if !(1..=32).contains(&bits) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Bits per entry must be between 1 and 32, not {}", bits),
+        ));
+    }
+    Ok((64 / bits) as usize)
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nbt, ChunkCompression, RegionFile, Tag};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_chunk_model() {
+        let tag: Tag = nbt!({
+            DataVersion: 3465,
+            xPos: 3,
+            yPos: -4,
+            zPos: -1,
+            Status: "minecraft:full",
+            LastUpdate: 120i64,
+            sections: [{
+                Y: 0i8,
+                block_states: {
+                    palette: [
+                        { Name: "minecraft:air" },
+                        { Name: "minecraft:oak_log", Properties: { axis: "y" } },
+                    ],
+                    data: [L; 1, 2],
+                },
+                SkyLight: [B; -1],
+                modded: 1i8,
+            }],
+            block_entities: [{ id: "minecraft:chest", x: 48, y: 64, z: -16 }],
+            Heightmaps: { MOTION_BLOCKING: [L; 5] },
+            PostProcessing: [],
+        });
+        let mut region: RegionFile<Cursor<Vec<u8>>> =
+            RegionFile::open(Cursor::new(Vec::new())).unwrap();
+        region
+            .set_chunk_with(3, 31, &tag, ChunkCompression::Lz4)
+            .unwrap();
+        let chunk: Chunk = Chunk::from_nbt(&region.chunk(3, 31).unwrap().unwrap()).unwrap();
+
+        assert_eq!(chunk.x_pos, 3);
+        assert_eq!(chunk.inhabited_time, None);
+        let states: &BlockStates = chunk.sections[0].block_states.as_ref().unwrap();
+        assert_eq!(states.palette[1].name, "minecraft:oak_log");
+        assert_eq!(states.palette[1].properties.as_ref().unwrap()["axis"], "y");
+        assert_eq!(chunk.sections[0].sky_light, Some(vec![255]));
+        assert_eq!(chunk.heightmaps.motion_blocking, Some(vec![5]));
+        assert_eq!(chunk.extra.keys().collect::<Vec<_>>(), ["PostProcessing"]);
+        assert_eq!(chunk.sections[0].extra["modded"], nbt!(1i8));
+        assert_eq!(chunk.to_nbt().unwrap(), tag);
+
+        assert!(Chunk::from_nbt(&nbt!({ xPos: 0 })).is_err());
+    }
+
+    #[test]
+    fn test_palette_indices() {
+        // Five bits per entry leaves twelve entries per long and four bits of padding.
+        let indices: Vec<u32> = (0..4096).map(|index| index % 31).collect();
+        let data: LongArrayTag = pack_palette_indices(&indices, 5).unwrap();
+        assert_eq!(data.len(), 342);
+        assert_eq!(data[0] as u64 >> 60, 0);
+        assert_eq!(data[0] & 0b11111_11111, 1 << 5);
+        assert_eq!(unpack_palette_indices(&data, 5, 4096).unwrap(), indices);
+
+        let full: Vec<u32> = vec![u32::MAX, 0, 1];
+        let data: LongArrayTag = pack_palette_indices(&full, 32).unwrap();
+        assert_eq!(data, [u32::MAX as i64, 1]);
+        assert_eq!(unpack_palette_indices(&data, 32, 3).unwrap(), full);
+
+        assert!(pack_palette_indices(&[32], 5).is_err());
+        assert!(pack_palette_indices(&[0], 0).is_err());
+        assert!(unpack_palette_indices(&data, 32, 5).is_err());
+    }
+
+    #[test]
+    fn test_heightmap() {
+        let mut grid: [[u16; 16]; 16] = [[64; 16]; 16];
+        grid[0][1] = 511;
+        grid[15][15] = 0;
+        let data: LongArrayTag = pack_heightmap(&grid).unwrap();
+        assert_eq!(data.len(), 37);
+        assert_eq!(data[0] & 0x3ffff, 511 << 9 | 64);
+        assert_eq!(unpack_heightmap(&data).unwrap(), grid);
+
+        grid[3][3] = 512;
+        assert!(pack_heightmap(&grid).is_err());
+        assert!(unpack_heightmap(&data[..36].to_vec()).is_err());
+    }
+}
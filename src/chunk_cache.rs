@@ -0,0 +1,247 @@
+use crate::{ChunkCompression, ChunkPos, RegionFile, Tag};
+use indexmap::IndexMap;
+use std::io::{Read, Result, Seek, Write};
+
+/// A bounded cache of decoded chunks in front of a [`RegionFile`], for tools that revisit the
+/// same chunks repeatedly.
+///
+/// Chunks are kept in least-recently-used order. Changes made through
+/// [`ChunkCache::chunk_mut`] or [`ChunkCache::set_chunk`] mark the chunk dirty, and dirty
+/// chunks are written back when they're evicted, on [`ChunkCache::flush`], and by
+/// [`ChunkCache::into_inner`]. Dropping the cache without flushing discards pending changes.
+///
+/// ```
+/// use rust_nbt::{nbt, ChunkCache, ChunkCompression, RegionFile};
+/// use std::io::Cursor;
+///
+/// let region: RegionFile<Cursor<Vec<u8>>> = RegionFile::open(Cursor::new(Vec::new()))?;
+/// let mut cache: ChunkCache<Cursor<Vec<u8>>> =
+///     ChunkCache::new(region, 16).compression(ChunkCompression::Lz4);
+/// cache.set_chunk(0, 0, nbt!({ xPos: 0, zPos: 0 }))?;
+/// let mut region: RegionFile<Cursor<Vec<u8>>> = cache.into_inner()?;
+/// assert_eq!(region.chunk(0, 0)?, Some(nbt!({ xPos: 0, zPos: 0 })));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct ChunkCache<S> {
+    region: RegionFile<S>,
+    capacity: usize,
+    compression: ChunkCompression,
+    entries: IndexMap<usize, CachedChunk>,
+}
+
+#[derive(Debug)]
+struct CachedChunk {
+    x: i32,
+    z: i32,
+    tag: Tag,
+    dirty: bool,
+}
+
+impl<S: Read + Write + Seek> ChunkCache<S> {
+    /// Wraps a region, keeping at most `capacity` chunks in memory. A capacity of zero is
+    /// treated as one.
+    pub fn new(region: RegionFile<S>, capacity: usize) -> Self {
+        ChunkCache {
+            region,
+            capacity: capacity.max(1),
+            compression: ChunkCompression::Zlib,
+            entries: IndexMap::new(),
+        }
+    }
+
+    /// Sets the compression used when writing dirty chunks back. Defaults to zlib.
+    pub fn compression(mut self, compression: ChunkCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// The number of chunks currently held in memory.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns a chunk, reading it from the region on a miss, or `None` if it isn't present.
+    pub fn chunk(&mut self, x: i32, z: i32) -> Result<Option<&Tag>> {
+        Ok(self.load(x, z)?.map(|entry| &entry.tag))
+    }
+
+    /// Returns a chunk for modification and marks it dirty, or `None` if it isn't present.
+    pub fn chunk_mut(&mut self, x: i32, z: i32) -> Result<Option<&mut Tag>> {
+        Ok(self.load(x, z)?.map(|entry| {
+            entry.dirty = true;
+            &mut entry.tag
+        }))
+    }
+
+    /// Replaces a chunk in the cache. It's written to the region on eviction or flush.
+    pub fn set_chunk(&mut self, x: i32, z: i32, tag: Tag) -> Result<()> {
+        self.insert(
+            index(x, z),
+            CachedChunk {
+                x,
+                z,
+                tag,
+                dirty: true,
+            },
+        )
+    }
+
+    /// Writes every dirty chunk back to the region, keeping them cached.
+    pub fn flush(&mut self) -> Result<()> {
+        for entry in self.entries.values_mut().filter(|entry| entry.dirty) {
+            self.region
+                .set_chunk_with(entry.x, entry.z, &entry.tag, self.compression)?;
+            entry.dirty = false;
+        }
+        Ok(())
+    }
+
+    /// Flushes dirty chunks and returns the region.
+    pub fn into_inner(mut self) -> Result<RegionFile<S>> {
+        self.flush()?;
+        Ok(self.region)
+    }
+
+    /// Moves a chunk to the most recently used end, reading it first if it isn't cached.
+    fn load(&mut self, x: i32, z: i32) -> Result<Option<&mut CachedChunk>> {
+        let index: usize = index(x, z);
+        match self.entries.shift_remove(&index) {
+            Some(entry) => self.insert(index, entry)?,
+            None => match self.region.chunk(x, z)? {
+                Some(tag) => self.insert(
+                    index,
+                    CachedChunk {
+                        x,
+                        z,
+                        tag,
+                        dirty: false,
+                    },
+                )?,
+                None => return Ok(None),
+            },
+        }
+        Ok(self.entries.get_mut(&index))
+    }
+
+    /// Inserts a chunk as the most recently used, writing back whatever falls off the end.
+    ///
+    /// A chunk is only evicted once its write-back succeeds, so a failed write leaves it
+    /// cached and dirty, and the cache over capacity until the next insert or flush.
+    fn insert(&mut self, index: usize, entry: CachedChunk) -> Result<()> {
+        self.entries.shift_remove(&index);
+        self.entries.insert(index, entry);
+        while self.entries.len() > self.capacity {
+            let Some((_, evicted)) = self.entries.get_index_mut(0) else {
+                break;
+            };
+            if evicted.dirty {
+                self.region
+                    .set_chunk_with(evicted.x, evicted.z, &evicted.tag, self.compression)?;
+                evicted.dirty = false;
+            }
+            self.entries.shift_remove_index(0);
+        }
+        Ok(())
+    }
+}
+
+fn index(x: i32, z: i32) -> usize {
+    ChunkPos::new(x, z).region_index()
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt;
+    use std::cell::Cell;
+    use std::io::{Cursor, Error, SeekFrom};
+    use std::rc::Rc;
+
+    /// A region stream whose writes fail while the shared flag is set.
+    struct FailingStream {
+        inner: Cursor<Vec<u8>>,
+        fail: Rc<Cell<bool>>,
+    }
+
+    impl Read for FailingStream {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            self.inner.read(buf)
+        }
+    }
+
+    impl Write for FailingStream {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            if self.fail.get() {
+                return Err(Error::other("Write failed"));
+            }
+            self.inner.write(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    impl Seek for FailingStream {
+        fn seek(&mut self, position: SeekFrom) -> Result<u64> {
+            self.inner.seek(position)
+        }
+    }
+
+    #[test]
+    fn test_chunk_cache() {
+        let mut region: RegionFile<Cursor<Vec<u8>>> =
+            RegionFile::open(Cursor::new(Vec::new())).unwrap();
+        for x in 0..3 {
+            region
+                .set_chunk_with(x, 0, &nbt!({ xPos: x }), ChunkCompression::Lz4)
+                .unwrap();
+        }
+        let mut cache: ChunkCache<Cursor<Vec<u8>>> =
+            ChunkCache::new(region, 2).compression(ChunkCompression::Lz4);
+        assert_eq!(cache.chunk(0, 0).unwrap(), Some(&nbt!({ xPos: 0 })));
+        assert_eq!(cache.chunk(5, 5).unwrap(), None);
+        *cache.chunk_mut(1, 0).unwrap().unwrap() = nbt!({ xPos: 10 });
+        cache.chunk(0, 0).unwrap();
+
+        // Chunk 1 is now the least recently used, so loading chunk 2 writes it back.
+        cache.chunk(2, 0).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.region.chunk(1, 0).unwrap(), Some(nbt!({ xPos: 10 })));
+
+        cache.set_chunk(3, 0, nbt!({ xPos: 3 })).unwrap();
+        assert!(!cache.region.has_chunk(3, 0));
+        let mut region: RegionFile<Cursor<Vec<u8>>> = cache.into_inner().unwrap();
+        assert_eq!(region.chunk(3, 0).unwrap(), Some(nbt!({ xPos: 3 })));
+        assert_eq!(region.chunk(0, 0).unwrap(), Some(nbt!({ xPos: 0 })));
+    }
+
+    #[test]
+    fn test_failed_eviction() {
+        let fail: Rc<Cell<bool>> = Rc::new(Cell::new(false));
+        let stream: FailingStream = FailingStream {
+            inner: Cursor::new(Vec::new()),
+            fail: fail.clone(),
+        };
+        let region: RegionFile<FailingStream> = RegionFile::open(stream).unwrap();
+        let mut cache: ChunkCache<FailingStream> =
+            ChunkCache::new(region, 1).compression(ChunkCompression::Lz4);
+        cache.set_chunk(0, 0, nbt!({ xPos: 0 })).unwrap();
+
+        // Evicting chunk 0 fails, so it has to stay cached and dirty.
+        fail.set(true);
+        assert!(cache.set_chunk(1, 0, nbt!({ xPos: 1 })).is_err());
+        assert_eq!(cache.len(), 2);
+
+        fail.set(false);
+        let mut region: RegionFile<FailingStream> = cache.into_inner().unwrap();
+        assert_eq!(region.chunk(0, 0).unwrap(), Some(nbt!({ xPos: 0 })));
+        assert_eq!(region.chunk(1, 0).unwrap(), Some(nbt!({ xPos: 1 })));
+    }
+}
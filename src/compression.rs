@@ -1,58 +1,330 @@
+#[cfg(feature = "compression")]
 use flate2::write::{
     DeflateDecoder, DeflateEncoder, GzDecoder, GzEncoder, ZlibDecoder, ZlibEncoder,
 };
-use flate2::Compression;
-use std::io::{Result, Write};
+#[cfg(feature = "compression")]
+use flate2::{Compression, Crc};
+use lz4_flex::frame::{FrameDecoder, FrameEncoder};
+use std::io::{Error, ErrorKind, Read, Result, Write};
+use std::time::{Duration, Instant};
 
 // Enum for compression formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CompressionFormat {
     Deflate,
     Gzip,
     DeflateRaw,
+    Lz4,
 }
 
+// Default compression level, matching flate2's default
+pub const DEFAULT_COMPRESSION_LEVEL: u32 = 6;
+
 // Compress data
 pub fn compress(data: &[u8], format: CompressionFormat) -> Result<Vec<u8>> {
+    compress_with_level(data, format, DEFAULT_COMPRESSION_LEVEL)
+}
+
+// Compress data at an explicit level, from 0 (store) to 9 (best). LZ4 has no levels, so the
+// level is ignored for it.
+#[cfg_attr(not(feature = "compression"), allow(unused_variables))]
+pub fn compress_with_level(data: &[u8], format: CompressionFormat, level: u32) -> Result<Vec<u8>> {
+    #[cfg(feature = "compression")]
+    let level: Compression = Compression::new(level.min(9));
     match format {
+        #[cfg(feature = "compression")]
         CompressionFormat::Deflate => {
-            let mut encoder: ZlibEncoder<Vec<u8>> =
-                ZlibEncoder::new(Vec::new(), Compression::default());
+            let mut encoder: ZlibEncoder<Vec<u8>> = ZlibEncoder::new(Vec::new(), level);
             encoder.write_all(data)?;
             encoder.finish()
         }
+        #[cfg(feature = "compression")]
         CompressionFormat::Gzip => {
-            let mut encoder: GzEncoder<Vec<u8>> =
-                GzEncoder::new(Vec::new(), Compression::default());
+            let mut encoder: GzEncoder<Vec<u8>> = GzEncoder::new(Vec::new(), level);
             encoder.write_all(data)?;
             encoder.finish()
         }
+        #[cfg(feature = "compression")]
         CompressionFormat::DeflateRaw => {
-            let mut encoder: DeflateEncoder<Vec<u8>> =
-                DeflateEncoder::new(Vec::new(), Compression::default());
+            let mut encoder: DeflateEncoder<Vec<u8>> = DeflateEncoder::new(Vec::new(), level);
             encoder.write_all(data)?;
             encoder.finish()
         }
+        #[cfg(not(feature = "compression"))]
+        CompressionFormat::Deflate | CompressionFormat::Gzip | CompressionFormat::DeflateRaw => {
+            Err(unsupported(format))
+        }
+        CompressionFormat::Lz4 => {
+            let mut encoder: FrameEncoder<Vec<u8>> = FrameEncoder::new(Vec::new());
+            encoder.write_all(data)?;
+            encoder.finish().map_err(Error::other)
+        }
     }
 }
 
 // Decompress data
 pub fn decompress(data: &[u8], format: CompressionFormat) -> Result<Vec<u8>> {
     match format {
+        #[cfg(feature = "compression")]
         CompressionFormat::Deflate => {
             let mut decoder: ZlibDecoder<Vec<u8>> = ZlibDecoder::new(Vec::new());
             decoder.write_all(data)?;
             decoder.finish()
         }
+        #[cfg(feature = "compression")]
         CompressionFormat::Gzip => {
             let mut decoder: GzDecoder<Vec<u8>> = GzDecoder::new(Vec::new());
             decoder.write_all(data)?;
             decoder.finish()
         }
+        #[cfg(feature = "compression")]
         CompressionFormat::DeflateRaw => {
             let mut decoder: DeflateDecoder<Vec<u8>> = DeflateDecoder::new(Vec::new());
             decoder.write_all(data)?;
             decoder.finish()
         }
+        #[cfg(not(feature = "compression"))]
+        CompressionFormat::Deflate | CompressionFormat::Gzip | CompressionFormat::DeflateRaw => {
+            Err(unsupported(format))
+        }
+        CompressionFormat::Lz4 => {
+            let mut decoder: FrameDecoder<&[u8]> = FrameDecoder::new(data);
+            let mut output: Vec<u8> = Vec::new();
+            decoder.read_to_end(&mut output)?;
+            Ok(output)
+        }
+    }
+}
+
+// Header fields of a gzip stream, captured so a file can be re-compressed with identical
+// framing. Most files never set the optional fields.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GzipHeader {
+    pub mtime: u32,
+    pub extra_flags: u8,
+    pub os: u8,
+    pub extra: Option<Vec<u8>>,
+    pub filename: Option<Vec<u8>>,
+    pub comment: Option<Vec<u8>>,
+    pub header_crc: bool,
+}
+
+const GZIP_FHCRC: u8 = 0x02;
+const GZIP_FEXTRA: u8 = 0x04;
+const GZIP_FNAME: u8 = 0x08;
+const GZIP_FCOMMENT: u8 = 0x10;
+
+impl GzipHeader {
+    // Parse the header at the start of a gzip stream
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let truncated = || Error::new(ErrorKind::UnexpectedEof, "Truncated gzip header");
+        let [0x1f, 0x8b, 8, flags, m0, m1, m2, m3, extra_flags, os, ..] = *data else {
+            return Err(Error::new(ErrorKind::InvalidData, "Not a gzip stream"));
+        };
+        let mut rest: &[u8] = &data[10..];
+        let extra: Option<Vec<u8>> = if flags & GZIP_FEXTRA != 0 {
+            let [l0, l1, ..] = *rest else {
+                return Err(truncated());
+            };
+            let length: usize = u16::from_le_bytes([l0, l1]) as usize;
+            let field: &[u8] = rest.get(2..2 + length).ok_or_else(truncated)?;
+            rest = &rest[2 + length..];
+            Some(field.to_vec())
+        } else {
+            None
+        };
+        let mut take_zero_terminated = |flag: u8| -> Result<Option<Vec<u8>>> {
+            if flags & flag == 0 {
+                return Ok(None);
+            }
+            let end: usize = rest.iter().position(|b| *b == 0).ok_or_else(truncated)?;
+            let field: Vec<u8> = rest[..end].to_vec();
+            rest = &rest[end + 1..];
+            Ok(Some(field))
+        };
+        let filename: Option<Vec<u8>> = take_zero_terminated(GZIP_FNAME)?;
+        let comment: Option<Vec<u8>> = take_zero_terminated(GZIP_FCOMMENT)?;
+        Ok(GzipHeader {
+            mtime: u32::from_le_bytes([m0, m1, m2, m3]),
+            extra_flags,
+            os,
+            extra,
+            filename,
+            comment,
+            header_crc: flags & GZIP_FHCRC != 0,
+        })
+    }
+
+    // The deflate level the original encoder most likely used, going by the extra flags
+    pub fn level(&self) -> u32 {
+        match self.extra_flags {
+            2 => 9,
+            4 => 1,
+            _ => DEFAULT_COMPRESSION_LEVEL,
+        }
+    }
+
+    // Serialize the header, recomputing the header checksum if the original had one
+    #[cfg(feature = "compression")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut flags: u8 = 0;
+        for (field, flag) in [
+            (&self.extra, GZIP_FEXTRA),
+            (&self.filename, GZIP_FNAME),
+            (&self.comment, GZIP_FCOMMENT),
+        ] {
+            if field.is_some() {
+                flags |= flag;
+            }
+        }
+        if self.header_crc {
+            flags |= GZIP_FHCRC;
+        }
+        let mut header: Vec<u8> = vec![0x1f, 0x8b, 8, flags];
+        header.extend_from_slice(&self.mtime.to_le_bytes());
+        header.extend_from_slice(&[self.extra_flags, self.os]);
+        if let Some(extra) = &self.extra {
+            header.extend_from_slice(&(extra.len() as u16).to_le_bytes());
+            header.extend_from_slice(extra);
+        }
+        for field in [&self.filename, &self.comment].into_iter().flatten() {
+            header.extend_from_slice(field);
+            header.push(0);
+        }
+        if self.header_crc {
+            let mut crc: Crc = Crc::new();
+            crc.update(&header);
+            header.extend_from_slice(&(crc.sum() as u16).to_le_bytes());
+        }
+        header
+    }
+}
+
+// Gzip-compress data behind the given header, so an unchanged document re-compresses to the
+// same header and CRC32/ISIZE trailer as the original file. The deflate body in between only
+// matches when flate2 makes the same choices as the original encoder, which isn't guaranteed.
+#[cfg(feature = "compression")]
+pub fn compress_gzip_with_header(data: &[u8], header: &GzipHeader) -> Result<Vec<u8>> {
+    let output: Vec<u8> = header.to_bytes();
+    let mut encoder: DeflateEncoder<Vec<u8>> =
+        DeflateEncoder::new(output, Compression::new(header.level()));
+    encoder.write_all(data)?;
+    let mut output: Vec<u8> = encoder.finish()?;
+    let mut crc: Crc = Crc::new();
+    crc.update(data);
+    output.extend_from_slice(&crc.sum().to_le_bytes());
+    output.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    Ok(output)
+}
+
+#[cfg(not(feature = "compression"))]
+pub fn compress_gzip_with_header(_: &[u8], _: &GzipHeader) -> Result<Vec<u8>> {
+    Err(unsupported(CompressionFormat::Gzip))
+}
+
+// A format and level pair to try in compress_best
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionCandidate {
+    pub format: CompressionFormat,
+    pub level: u32,
+}
+
+impl CompressionCandidate {
+    pub fn new(format: CompressionFormat, level: u32) -> Self {
+        CompressionCandidate { format, level }
+    }
+}
+
+// Compressed bytes along with the candidate that produced them
+#[derive(Debug, Clone)]
+pub struct CompressedOutput {
+    pub data: Vec<u8>,
+    pub candidate: CompressionCandidate,
+}
+
+// Compress with every candidate and keep the smallest result
+pub fn compress_best(data: &[u8], candidates: &[CompressionCandidate]) -> Result<CompressedOutput> {
+    compress_best_within(data, candidates, Duration::MAX)
+}
+
+// Like compress_best, but stop trying further candidates once the time budget is spent.
+// Candidates are tried in order, so list the fastest ones first; the first one always runs.
+pub fn compress_best_within(
+    data: &[u8],
+    candidates: &[CompressionCandidate],
+    budget: Duration,
+) -> Result<CompressedOutput> {
+    let start: Instant = Instant::now();
+    let mut best: Option<CompressedOutput> = None;
+    for candidate in candidates {
+        if best.is_some() && start.elapsed() >= budget {
+            break;
+        }
+        let compressed: Vec<u8> = compress_with_level(data, candidate.format, candidate.level)?;
+        if best
+            .as_ref()
+            .is_none_or(|best| compressed.len() < best.data.len())
+        {
+            best = Some(CompressedOutput {
+                data: compressed,
+                candidate: *candidate,
+            });
+        }
+    }
+    best.ok_or_else(|| Error::new(ErrorKind::InvalidInput, "No compression candidates"))
+}
+
+// Wrap a reader so that reading from it yields decompressed data, without holding the whole
+// stream in memory. Pair it with read_from to parse large files incrementally.
+pub fn decompress_reader<'a, R: Read + 'a>(
+    reader: R,
+    format: CompressionFormat,
+) -> Box<dyn Read + 'a> {
+    match format {
+        #[cfg(feature = "compression")]
+        CompressionFormat::Deflate => Box::new(flate2::read::ZlibDecoder::new(reader)),
+        #[cfg(feature = "compression")]
+        CompressionFormat::Gzip => Box::new(flate2::read::GzDecoder::new(reader)),
+        #[cfg(feature = "compression")]
+        CompressionFormat::DeflateRaw => Box::new(flate2::read::DeflateDecoder::new(reader)),
+        #[cfg(not(feature = "compression"))]
+        CompressionFormat::Deflate | CompressionFormat::Gzip | CompressionFormat::DeflateRaw => {
+            Box::new(UnsupportedReader(format))
+        }
+        CompressionFormat::Lz4 => Box::new(FrameDecoder::new(reader)),
+    }
+}
+
+// Error for the zlib based formats when flate2 isn't compiled in. LZ4 is always available.
+#[cfg(not(feature = "compression"))]
+fn unsupported(format: CompressionFormat) -> Error {
+    Error::new(
+        ErrorKind::Unsupported,
+        format!("{:?} compression needs the compression feature", format),
+    )
+}
+
+// A reader that fails on its first read, standing in for a decoder that isn't compiled in
+#[cfg(not(feature = "compression"))]
+struct UnsupportedReader(CompressionFormat);
+
+#[cfg(not(feature = "compression"))]
+impl Read for UnsupportedReader {
+    fn read(&mut self, _: &mut [u8]) -> Result<usize> {
+        Err(unsupported(self.0))
+    }
+}
+
+// Guess the compression format from the leading magic bytes. Raw deflate has no header,
+// so it can't be detected and is reported as uncompressed (None).
+pub fn detect_compression(data: &[u8]) -> Option<CompressionFormat> {
+    match data {
+        [0x1f, 0x8b, ..] => Some(CompressionFormat::Gzip),
+        [0x04, 0x22, 0x4d, 0x18, ..] => Some(CompressionFormat::Lz4),
+        [cmf, flg, ..] if cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0 => {
+            Some(CompressionFormat::Deflate)
+        }
+        _ => None,
     }
 }
 
@@ -61,6 +333,7 @@ pub fn decompress(data: &[u8], format: CompressionFormat) -> Result<Vec<u8>> {
 mod tests {
     use super::*;
 
+    #[cfg(feature = "compression")]
     #[test]
     fn test_compression_and_decompression() {
         let input: &[u8; 37] = b"Hello, compression and decompression!";
@@ -99,5 +372,123 @@ mod tests {
             &decompressed_deflate_raw[..],
             "DeflateRaw compression/decompression mismatch"
         );
+
+        // Test Lz4
+        let compressed_lz4: Vec<u8> =
+            compress(input, CompressionFormat::Lz4).expect("Compression failed");
+        let decompressed_lz4: Vec<u8> =
+            decompress(&compressed_lz4, CompressionFormat::Lz4).expect("Decompression failed");
+        assert_eq!(
+            input,
+            &decompressed_lz4[..],
+            "Lz4 compression/decompression mismatch"
+        );
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_compress_best() {
+        let input: Vec<u8> = b"Hello, compression and decompression!".repeat(64);
+        let candidates: [CompressionCandidate; 3] = [
+            CompressionCandidate::new(CompressionFormat::Gzip, 0),
+            CompressionCandidate::new(CompressionFormat::DeflateRaw, 9),
+            CompressionCandidate::new(CompressionFormat::Deflate, 1),
+        ];
+        let best: CompressedOutput =
+            compress_best(&input, &candidates).expect("Compression failed");
+        assert_eq!(
+            best.candidate,
+            CompressionCandidate::new(CompressionFormat::DeflateRaw, 9)
+        );
+        let decompressed: Vec<u8> =
+            decompress(&best.data, best.candidate.format).expect("Decompression failed");
+        assert_eq!(input, decompressed);
+
+        let first: CompressedOutput =
+            compress_best_within(&input, &candidates, Duration::ZERO).expect("Compression failed");
+        assert_eq!(first.candidate, candidates[0]);
+
+        assert!(compress_best(&input, &[]).is_err());
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_detect_compression() {
+        let input: &[u8; 37] = b"Hello, compression and decompression!";
+        for format in [
+            CompressionFormat::Deflate,
+            CompressionFormat::Gzip,
+            CompressionFormat::Lz4,
+        ] {
+            let compressed: Vec<u8> = compress(input, format).expect("Compression failed");
+            assert_eq!(detect_compression(&compressed), Some(format));
+        }
+        assert_eq!(detect_compression(&[0x0a, 0x00, 0x00, 0x00]), None);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_gzip_header_round_trip() {
+        let input: &[u8; 37] = b"Hello, compression and decompression!";
+        let header: GzipHeader = GzipHeader {
+            mtime: 1_700_000_000,
+            extra_flags: 2,
+            os: 11,
+            extra: Some(vec![1, 2, 3]),
+            filename: Some(b"level.dat".to_vec()),
+            comment: None,
+            header_crc: true,
+        };
+        let compressed: Vec<u8> = compress_gzip_with_header(input, &header).unwrap();
+        assert_eq!(GzipHeader::parse(&compressed).unwrap(), header);
+        assert_eq!(
+            decompress(&compressed, CompressionFormat::Gzip).unwrap(),
+            input
+        );
+
+        let original: Vec<u8> = std::fs::read("./test/bigtest.nbt").unwrap();
+        let header: GzipHeader = GzipHeader::parse(&original).unwrap();
+        let data: Vec<u8> = decompress(&original, CompressionFormat::Gzip).unwrap();
+        let recompressed: Vec<u8> = compress_gzip_with_header(&data, &header).unwrap();
+        let header_length: usize = header.to_bytes().len();
+        assert_eq!(recompressed[..header_length], original[..header_length]);
+        assert_eq!(
+            recompressed[recompressed.len() - 8..],
+            original[original.len() - 8..]
+        );
+    }
+
+    #[cfg(not(feature = "compression"))]
+    #[test]
+    fn test_compression_disabled() {
+        let input: &[u8; 37] = b"Hello, compression and decompression!";
+        let compressed: Vec<u8> = compress(input, CompressionFormat::Lz4).unwrap();
+        assert_eq!(
+            detect_compression(&compressed),
+            Some(CompressionFormat::Lz4)
+        );
+        assert_eq!(
+            decompress(&compressed, CompressionFormat::Lz4).unwrap(),
+            input
+        );
+
+        let gzip: &[u8] = &[0x1f, 0x8b, 8, 0];
+        assert_eq!(detect_compression(gzip), Some(CompressionFormat::Gzip));
+        for format in [
+            CompressionFormat::Deflate,
+            CompressionFormat::Gzip,
+            CompressionFormat::DeflateRaw,
+        ] {
+            let error: Error = compress(input, format).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::Unsupported);
+            let error: Error = decompress(gzip, format).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::Unsupported);
+            let error: Error = decompress_reader(gzip, format)
+                .read_to_end(&mut Vec::new())
+                .unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::Unsupported);
+        }
+        let header: GzipHeader = GzipHeader::default();
+        assert!(compress_gzip_with_header(input, &header).is_err());
     }
 }
@@ -0,0 +1,324 @@
+use crate::{CompoundTag, Tag, TagID};
+use indexmap::IndexMap;
+use std::borrow::Cow;
+use std::io::{Error, ErrorKind, Result};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Converts a value into a tag. Derive it with `#[derive(NbtSerialize)]` to map a struct to a
+/// compound, using `#[nbt(...)]` attributes to pick each field's tag type.
+///
+/// ```
+/// use rust_nbt::{nbt, NbtDeserialize, NbtSerialize};
+///
+/// #[derive(NbtSerialize, NbtDeserialize, Debug, PartialEq)]
+/// struct Player {
+///     #[nbt(rename = "Health", float)]
+///     health: f64,
+///     #[nbt(rename = "UUID", int_array)]
+///     uuid: Vec<i32>,
+///     #[nbt(rename = "CustomName")]
+///     custom_name: Option<String>,
+/// }
+///
+/// let player: Player = Player { health: 20.0, uuid: vec![1, 2, 3, 4], custom_name: None };
+/// assert_eq!(player.to_nbt()?, nbt!({ Health: 20.0f32, UUID: [I; 1, 2, 3, 4] }));
+/// assert_eq!(Player::from_nbt(&player.to_nbt()?)?, player);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub trait NbtSerialize {
+    /// Fails if a field doesn't fit in the tag type its attribute asks for.
+    fn to_nbt(&self) -> Result<Tag>;
+}
+
+/// Builds a value from a tag. Derive it with `#[derive(NbtDeserialize)]`; see
+/// [`NbtSerialize`] for the attributes.
+pub trait NbtDeserialize: Sized {
+    fn from_nbt(tag: &Tag) -> Result<Self>;
+}
+
+/// Builds the error for a compound that lacks a required key.
+pub fn missing_key(key: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("Missing key {:?}", key))
+}
+
+/// Decodes a [`Tag::RawEncoded`] payload so it can be matched like any other tag, and borrows
+/// every other tag as is.
+pub fn decode_raw(tag: &Tag) -> Result<Cow<'_, Tag>> {
+    match tag {
+        Tag::RawEncoded(raw) => Ok(Cow::Owned(raw.decode()?)),
+        tag => Ok(Cow::Borrowed(tag)),
+    }
+}
+
+/// Converts a field to and from the payload of the numeric tag its `#[nbt(...)]` attribute
+/// names, such as `i8` for `#[nbt(byte)]`.
+///
+/// An unsigned integer of the payload's width is reinterpreted as two's complement, so a `u8`
+/// of 200 is stored as the byte -56 and reads back as 200. Any other integer must fit: writing
+/// an `i32` of 300 as a byte fails with [`ErrorKind::InvalidInput`], and reading a negative
+/// byte into a `u16` fails with [`ErrorKind::InvalidData`]. A `bool` is stored as a byte of 0
+/// or 1, and any nonzero byte reads as `true`.
+pub trait NbtCast<T>: Sized {
+    fn to_payload(&self) -> Result<T>;
+    fn from_payload(payload: T) -> Result<Self>;
+}
+
+macro_rules! impl_nbt_cast {
+    ($payload:ty; reinterpret $($same:ty),*; check $($field:ty),* $(,)?) => {
+        $(
+            impl NbtCast<$payload> for $same {
+                fn to_payload(&self) -> Result<$payload> {
+                    Ok(*self as $payload)
+                }
+
+                fn from_payload(payload: $payload) -> Result<Self> {
+                    Ok(payload as $same)
+                }
+            }
+        )*
+        $(
+            impl NbtCast<$payload> for $field {
+                fn to_payload(&self) -> Result<$payload> {
+                    <$payload>::try_from(*self).map_err(|_| {
+                        Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("{} doesn't fit in {}", self, stringify!($payload)),
+                        )
+                    })
+                }
+
+                fn from_payload(payload: $payload) -> Result<Self> {
+                    <$field>::try_from(payload).map_err(|_| {
+                        Error::new(
+                            ErrorKind::InvalidData,
+                            format!("{} doesn't fit in {}", payload, stringify!($field)),
+                        )
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_nbt_cast!(i8; reinterpret u8; check i8, i16, i32, i64, u16, u32, u64, isize, usize);
+impl_nbt_cast!(i16; reinterpret u16; check i8, i16, i32, i64, u8, u32, u64, isize, usize);
+impl_nbt_cast!(i32; reinterpret u32; check i8, i16, i32, i64, u8, u16, u64, isize, usize);
+impl_nbt_cast!(i64; reinterpret u64; check i8, i16, i32, i64, u8, u16, u32, isize, usize);
+impl_nbt_cast!(f32; reinterpret f32, f64; check);
+impl_nbt_cast!(f64; reinterpret f32, f64; check);
+
+impl NbtCast<i8> for bool {
+    fn to_payload(&self) -> Result<i8> {
+        Ok(*self as i8)
+    }
+
+    fn from_payload(payload: i8) -> Result<Self> {
+        Ok(payload != 0)
+    }
+}
+
+/// Stores a time as milliseconds since the Unix epoch, the way `LastPlayed` and similar keys
+/// do, so a `SystemTime` field can use `#[nbt(long)]`.
+impl NbtCast<i64> for SystemTime {
+    fn to_payload(&self) -> Result<i64> {
+        let millis: i128 = match self.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_millis() as i128,
+            Err(before) => -(before.duration().as_millis() as i128),
+        };
+        i64::try_from(millis).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "Time doesn't fit in i64 milliseconds",
+            )
+        })
+    }
+
+    fn from_payload(payload: i64) -> Result<Self> {
+        let offset: Duration = Duration::from_millis(payload.unsigned_abs());
+        let time: Option<SystemTime> = if payload < 0 {
+            UNIX_EPOCH.checked_sub(offset)
+        } else {
+            UNIX_EPOCH.checked_add(offset)
+        };
+        time.ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("{} milliseconds is out of range for a time", payload),
+            )
+        })
+    }
+}
+
+macro_rules! impl_nbt_convert {
+    ($($payload:ty),* $(,)?) => {
+        $(
+            impl NbtSerialize for $payload {
+                fn to_nbt(&self) -> Result<Tag> {
+                    Ok(Tag::from(self.clone()))
+                }
+            }
+
+            impl NbtDeserialize for $payload {
+                fn from_nbt(tag: &Tag) -> Result<Self> {
+                    <$payload>::try_from(tag.clone())
+                }
+            }
+        )*
+    };
+}
+
+impl_nbt_convert!(bool, i8, i16, i32, i64, f32, f64, String);
+
+impl NbtSerialize for Tag {
+    fn to_nbt(&self) -> Result<Tag> {
+        Ok(self.clone())
+    }
+}
+
+impl NbtDeserialize for Tag {
+    fn from_nbt(tag: &Tag) -> Result<Self> {
+        Ok(tag.clone())
+    }
+}
+
+impl<T: NbtSerialize> NbtSerialize for Vec<T> {
+    fn to_nbt(&self) -> Result<Tag> {
+        Ok(Tag::List(
+            self.iter()
+                .map(NbtSerialize::to_nbt)
+                .collect::<Result<_>>()?,
+        ))
+    }
+}
+
+impl<T: NbtDeserialize> NbtDeserialize for Vec<T> {
+    fn from_nbt(tag: &Tag) -> Result<Self> {
+        match tag {
+            Tag::List(entries) => entries.iter().map(T::from_nbt).collect(),
+            Tag::RawEncoded(raw) => Self::from_nbt(&raw.decode()?),
+            tag => Err(crate::unexpected_tag(TagID::List, tag)),
+        }
+    }
+}
+
+impl<T: NbtSerialize> NbtSerialize for IndexMap<String, T> {
+    fn to_nbt(&self) -> Result<Tag> {
+        let compound: CompoundTag = self
+            .iter()
+            .map(|(key, value)| Ok((key.clone(), value.to_nbt()?)))
+            .collect::<Result<_>>()?;
+        Ok(Tag::Compound(compound))
+    }
+}
+
+impl<T: NbtDeserialize> NbtDeserialize for IndexMap<String, T> {
+    fn from_nbt(tag: &Tag) -> Result<Self> {
+        match tag {
+            Tag::Compound(entries) => entries
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), T::from_nbt(value)?)))
+                .collect(),
+            Tag::RawEncoded(raw) => Self::from_nbt(&raw.decode()?),
+            tag => Err(crate::unexpected_tag(TagID::Compound, tag)),
+        }
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nbt, NbtDeserialize, NbtSerialize};
+
+    #[derive(NbtSerialize, NbtDeserialize, Debug, PartialEq)]
+    struct Section {
+        #[nbt(rename = "Y", byte)]
+        y: i32,
+        #[nbt(rename = "BlockLight", byte_array)]
+        block_light: Vec<u8>,
+        #[nbt(long_array)]
+        states: Option<Vec<u64>>,
+        palette: Vec<String>,
+        extra: CompoundTag,
+    }
+
+    #[test]
+    fn test_derive() {
+        let section: Section = Section {
+            y: -4,
+            block_light: vec![0, 255],
+            states: Some(vec![u64::MAX]),
+            palette: vec!["minecraft:air".to_string()],
+            extra: CompoundTag::from([("a".to_string(), nbt!(true))]),
+        };
+        let tag: Tag = section.to_nbt().unwrap();
+        assert_eq!(
+            tag,
+            nbt!({
+                Y: -4i8,
+                BlockLight: [B; 0, -1],
+                states: [L; -1],
+                palette: ["minecraft:air"],
+                extra: { a: 1i8 },
+            })
+        );
+        assert_eq!(Section::from_nbt(&tag).unwrap(), section);
+
+        let mut sparse: Tag = tag.clone();
+        sparse.remove_path("states");
+        assert_eq!(Section::from_nbt(&sparse).unwrap().states, None);
+        sparse.remove_path("palette");
+        assert!(Section::from_nbt(&sparse).is_err());
+        assert!(Section::from_nbt(&nbt!({ Y: 1 })).is_err());
+
+        let mut raw: Tag = tag.clone();
+        for key in ["Y", "BlockLight", "states"] {
+            let value: &mut Tag = raw.get_path_mut(key).unwrap();
+            *value = Tag::RawEncoded(crate::RawTag::encode(value).unwrap());
+        }
+        let raw: Tag = Tag::RawEncoded(crate::RawTag::encode(&raw).unwrap());
+        assert_eq!(Section::from_nbt(&raw).unwrap(), section);
+    }
+
+    #[derive(NbtSerialize, NbtDeserialize, Debug, PartialEq)]
+    struct Flags {
+        #[nbt(byte)]
+        flag: bool,
+        #[nbt(byte)]
+        count: i32,
+        #[nbt(long)]
+        time: SystemTime,
+        #[nbt(extra)]
+        extra: CompoundTag,
+    }
+
+    #[test]
+    fn test_derive_casts() {
+        let flags: Flags = Flags {
+            flag: true,
+            count: 100,
+            time: UNIX_EPOCH - Duration::from_millis(1500),
+            extra: CompoundTag::from([("count".to_string(), nbt!("stale"))]),
+        };
+        let tag: Tag = flags.to_nbt().unwrap();
+        assert_eq!(tag, nbt!({ flag: 1i8, count: 100i8, time: -1500i64 }));
+        assert_eq!(Flags::from_nbt(&tag).unwrap().time, flags.time);
+        let nonzero: Tag = nbt!({ flag: 2i8, count: 0i8, time: 0i64 });
+        assert!(Flags::from_nbt(&nonzero).unwrap().flag);
+
+        let flags: Flags = Flags {
+            count: 300,
+            ..Flags::from_nbt(&tag).unwrap()
+        };
+        let error: Error = flags.to_nbt().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
+        #[derive(NbtDeserialize, Debug)]
+        struct Unsigned {
+            #[nbt(short)]
+            _value: u8,
+        }
+        let error: Error = Unsigned::from_nbt(&nbt!({ _value: -1i16 })).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,277 @@
+/// Number of blocks along each horizontal axis of a chunk.
+pub const CHUNK_SIZE: i32 = 16;
+
+/// Number of blocks along each axis of a chunk section.
+pub const SECTION_SIZE: i32 = 16;
+
+/// Number of chunks along each axis of a region file.
+pub const REGION_SIZE: i32 = 32;
+
+/// A block position in world space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+/// A chunk position, in units of 16 blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChunkPos {
+    pub x: i32,
+    pub z: i32,
+}
+
+/// A region position, in units of 32 chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RegionPos {
+    pub x: i32,
+    pub z: i32,
+}
+
+impl BlockPos {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        BlockPos { x, y, z }
+    }
+
+    /// Returns the chunk containing this block.
+    pub fn chunk(&self) -> ChunkPos {
+        ChunkPos::new(self.x.div_euclid(CHUNK_SIZE), self.z.div_euclid(CHUNK_SIZE))
+    }
+
+    /// Returns the region containing this block.
+    pub fn region(&self) -> RegionPos {
+        self.chunk().region()
+    }
+
+    /// Returns the vertical section index containing this block, which may be negative.
+    pub fn section_y(&self) -> i32 {
+        self.y.div_euclid(SECTION_SIZE)
+    }
+
+    /// Returns the block's offset within its chunk, as `(x, y, z)` with `x` and `z` in `0..16`.
+    pub fn local_in_chunk(&self) -> (u8, i32, u8) {
+        (
+            self.x.rem_euclid(CHUNK_SIZE) as u8,
+            self.y,
+            self.z.rem_euclid(CHUNK_SIZE) as u8,
+        )
+    }
+
+    /// Returns the block's offset within its section, with every axis in `0..16`.
+    pub fn local_in_section(&self) -> (u8, u8, u8) {
+        (
+            self.x.rem_euclid(SECTION_SIZE) as u8,
+            self.y.rem_euclid(SECTION_SIZE) as u8,
+            self.z.rem_euclid(SECTION_SIZE) as u8,
+        )
+    }
+
+    /// Returns the YZX-ordered index of this block within its section's 4096 entries.
+    pub fn section_index(&self) -> usize {
+        let (x, y, z): (u8, u8, u8) = self.local_in_section();
+        (y as usize) << 8 | (z as usize) << 4 | x as usize
+    }
+}
+
+impl ChunkPos {
+    pub fn new(x: i32, z: i32) -> Self {
+        ChunkPos { x, z }
+    }
+
+    /// Returns the region containing this chunk.
+    pub fn region(&self) -> RegionPos {
+        RegionPos::new(
+            self.x.div_euclid(REGION_SIZE),
+            self.z.div_euclid(REGION_SIZE),
+        )
+    }
+
+    /// Returns the chunk's offset within its region, with both axes in `0..32`.
+    pub fn local_in_region(&self) -> (u8, u8) {
+        (
+            self.x.rem_euclid(REGION_SIZE) as u8,
+            self.z.rem_euclid(REGION_SIZE) as u8,
+        )
+    }
+
+    /// Returns the index of this chunk in a region file's 1024-entry header tables.
+    pub fn region_index(&self) -> usize {
+        let (x, z): (u8, u8) = self.local_in_region();
+        x as usize + z as usize * REGION_SIZE as usize
+    }
+
+    /// Returns the lowest-coordinate block column of this chunk at the given height.
+    pub fn min_block(&self, y: i32) -> BlockPos {
+        BlockPos::new(self.x * CHUNK_SIZE, y, self.z * CHUNK_SIZE)
+    }
+}
+
+impl RegionPos {
+    pub fn new(x: i32, z: i32) -> Self {
+        RegionPos { x, z }
+    }
+
+    /// Returns the lowest-coordinate chunk of this region.
+    pub fn min_chunk(&self) -> ChunkPos {
+        ChunkPos::new(self.x * REGION_SIZE, self.z * REGION_SIZE)
+    }
+
+    /// Returns the chunk at the given local offset, which must be in `0..32` on both axes.
+    pub fn chunk_at(&self, local_x: u8, local_z: u8) -> ChunkPos {
+        let min: ChunkPos = self.min_chunk();
+        ChunkPos::new(min.x + local_x as i32, min.z + local_z as i32)
+    }
+
+    /// Returns the conventional Anvil file name for this region, such as `r.-1.2.mca`.
+    pub fn file_name(&self) -> String {
+        format!("r.{}.{}.mca", self.x, self.z)
+    }
+}
+
+impl From<BlockPos> for ChunkPos {
+    fn from(pos: BlockPos) -> Self {
+        pos.chunk()
+    }
+}
+
+impl From<ChunkPos> for RegionPos {
+    fn from(pos: ChunkPos) -> Self {
+        pos.region()
+    }
+}
+
+impl From<BlockPos> for RegionPos {
+    fn from(pos: BlockPos) -> Self {
+        pos.region()
+    }
+}
+
+/// The vanilla dimensions, with their vertical extents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dimension {
+    Overworld,
+    Nether,
+    End,
+}
+
+impl Dimension {
+    /// Returns the legacy numeric dimension ID (`0`, `-1`, `1`).
+    pub fn id(&self) -> i32 {
+        match self {
+            Dimension::Overworld => 0,
+            Dimension::Nether => -1,
+            Dimension::End => 1,
+        }
+    }
+
+    /// Returns the lowest buildable Y coordinate.
+    pub fn min_y(&self) -> i32 {
+        match self {
+            Dimension::Overworld => -64,
+            Dimension::Nether | Dimension::End => 0,
+        }
+    }
+
+    /// Returns the number of buildable blocks between the bottom and top of the world.
+    pub fn height(&self) -> i32 {
+        match self {
+            Dimension::Overworld => 384,
+            Dimension::Nether | Dimension::End => 256,
+        }
+    }
+
+    /// Returns the number of sections stacked in each chunk.
+    pub fn section_count(&self) -> usize {
+        (self.height() / SECTION_SIZE) as usize
+    }
+
+    /// Returns the zero-based position of the section holding `y` within a chunk's section
+    /// list, or `None` if `y` lies outside the dimension.
+    pub fn section_index(&self, y: i32) -> Option<usize> {
+        let offset: i32 = y - self.min_y();
+        if offset < 0 || offset >= self.height() {
+            return None;
+        }
+        Some((offset / SECTION_SIZE) as usize)
+    }
+}
+
+impl TryFrom<i32> for Dimension {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Dimension::Overworld),
+            -1 => Ok(Dimension::Nether),
+            1 => Ok(Dimension::End),
+            _ => Err(value),
+        }
+    }
+}
+
+/// A position tagged with the dimension it belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DimensionPos<P> {
+    pub dimension: Dimension,
+    pub pos: P,
+}
+
+impl<P> DimensionPos<P> {
+    pub fn new(dimension: Dimension, pos: P) -> Self {
+        DimensionPos { dimension, pos }
+    }
+}
+
+impl DimensionPos<BlockPos> {
+    pub fn chunk(&self) -> DimensionPos<ChunkPos> {
+        DimensionPos::new(self.dimension, self.pos.chunk())
+    }
+
+    pub fn region(&self) -> DimensionPos<RegionPos> {
+        DimensionPos::new(self.dimension, self.pos.region())
+    }
+
+    /// Returns the section index within the chunk's section list, if the block is in bounds.
+    pub fn section_index(&self) -> Option<usize> {
+        self.dimension.section_index(self.pos.y)
+    }
+}
+
+impl DimensionPos<ChunkPos> {
+    pub fn region(&self) -> DimensionPos<RegionPos> {
+        DimensionPos::new(self.dimension, self.pos.region())
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coordinate_conversions() {
+        let block: BlockPos = BlockPos::new(-1, -65, 529);
+        assert_eq!(block.chunk(), ChunkPos::new(-1, 33));
+        assert_eq!(block.region(), RegionPos::new(-1, 1));
+        assert_eq!(block.section_y(), -5);
+        assert_eq!(block.local_in_chunk(), (15, -65, 1));
+        assert_eq!(block.local_in_section(), (15, 15, 1));
+        assert_eq!(block.section_index(), 15 << 8 | 1 << 4 | 15);
+
+        let chunk: ChunkPos = ChunkPos::new(-33, 31);
+        assert_eq!(chunk.region(), RegionPos::new(-2, 0));
+        assert_eq!(chunk.local_in_region(), (31, 31));
+        assert_eq!(chunk.region_index(), 1023);
+        assert_eq!(chunk.region().chunk_at(31, 31), chunk);
+        assert_eq!(RegionPos::new(-1, 2).file_name(), "r.-1.2.mca");
+
+        assert_eq!(Dimension::Overworld.section_index(-64), Some(0));
+        assert_eq!(Dimension::Overworld.section_index(319), Some(23));
+        assert_eq!(Dimension::Overworld.section_index(320), None);
+        assert_eq!(Dimension::Nether.section_index(-1), None);
+        let wrapped: DimensionPos<BlockPos> = DimensionPos::new(Dimension::Overworld, block);
+        assert_eq!(wrapped.section_index(), None);
+        assert_eq!(wrapped.region().pos, RegionPos::new(-1, 1));
+    }
+}
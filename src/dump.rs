@@ -0,0 +1,212 @@
+use crate::{
+    decode_mutf8, read_id, read_length_at, read_list_header_at, read_u16, take, take_array,
+    ReadOptions, TagID,
+};
+use std::fmt::Write;
+use std::io::{Error, ErrorKind, Result};
+
+/// Bytes shown per row before the rest are elided.
+const ROW_BYTES: usize = 16;
+
+/// Renders an uncompressed NBT document as a hex dump, labelling each byte range with the
+/// tag ID, name or value it encodes.
+///
+/// Parsing stops at the first malformed byte, which is marked with the error, so a corrupt
+/// or hand-crafted file can be dumped up to the point where it goes wrong. Any trailing bytes
+/// after the root tag are listed at the end.
+///
+/// ```text
+/// 00000000  0a                                                 Compound
+/// 00000001  00 0b 68 65 6c 6c 6f 20 77 6f 72 6c 64             name "hello world"
+/// 0000000e  08                                                   String
+/// ```
+pub fn dump_annotated(data: &[u8]) -> String {
+    let mut dump: Dump = Dump {
+        data,
+        position: 0,
+        output: String::new(),
+        options: ReadOptions::default(),
+    };
+    if let Err(error) = dump.named_tag(0) {
+        let position: usize = dump.position;
+        let _ = writeln!(dump.output, "{:08x}  error: {}", position, error);
+        return dump.output;
+    }
+    if dump.position < data.len() {
+        dump.row(dump.position, data.len(), 0, "trailing bytes");
+    }
+    dump.output
+}
+
+struct Dump<'a> {
+    data: &'a [u8],
+    position: usize,
+    output: String,
+    options: ReadOptions,
+}
+
+impl Dump<'_> {
+    fn row(&mut self, start: usize, end: usize, depth: usize, label: &str) {
+        let bytes: &[u8] = &self.data[start..end];
+        let mut hex: String = bytes
+            .iter()
+            .take(ROW_BYTES)
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<Vec<String>>()
+            .join(" ");
+        if bytes.len() > ROW_BYTES {
+            hex.push_str(" ..");
+        }
+        let _ = writeln!(
+            self.output,
+            "{:08x}  {:<50} {}{}",
+            start,
+            hex,
+            "  ".repeat(depth),
+            label
+        );
+    }
+
+    fn named_tag(&mut self, depth: usize) -> Result<TagID> {
+        let start: usize = self.position;
+        let tag_id: TagID = read_id(self.data, &mut self.position)?;
+        self.row(start, self.position, depth, &format!("{:?}", tag_id));
+        if tag_id == TagID::End {
+            return Ok(tag_id);
+        }
+        let name: String = self.string()?;
+        self.row(start + 1, self.position, depth, &format!("name {:?}", name));
+        self.payload(tag_id, depth, "")?;
+        Ok(tag_id)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let length: usize = read_u16(self.data, &mut self.position)? as usize;
+        decode_mutf8(take(self.data, &mut self.position, length)?)
+    }
+
+    fn payload(&mut self, tag_id: TagID, depth: usize, prefix: &str) -> Result<()> {
+        let start: usize = self.position;
+        let fixed = |dump: &mut Self, size: usize| -> Result<[u8; 8]> {
+            let mut bytes: [u8; 8] = [0; 8];
+            bytes[..size].copy_from_slice(take(dump.data, &mut dump.position, size)?);
+            Ok(bytes)
+        };
+        let label: String = match tag_id {
+            TagID::End => return Ok(()),
+            TagID::Byte => format!("{}", fixed(self, 1)?[0] as i8),
+            TagID::Short => {
+                let [a, b, ..] = fixed(self, 2)?;
+                format!("{}s", i16::from_be_bytes([a, b]))
+            }
+            TagID::Int => {
+                let [a, b, c, d, ..] = fixed(self, 4)?;
+                format!("{}", i32::from_be_bytes([a, b, c, d]))
+            }
+            TagID::Float => {
+                let [a, b, c, d, ..] = fixed(self, 4)?;
+                format!("{}f", f32::from_be_bytes([a, b, c, d]))
+            }
+            TagID::Long => format!("{}L", i64::from_be_bytes(fixed(self, 8)?)),
+            TagID::Double => format!("{}d", f64::from_be_bytes(fixed(self, 8)?)),
+            TagID::String => format!("{:?}", self.string()?),
+            TagID::ByteArray | TagID::IntArray | TagID::LongArray => {
+                let size: usize = match tag_id {
+                    TagID::ByteArray => 1,
+                    TagID::IntArray => 4,
+                    _ => 8,
+                };
+                let length: usize = read_length_at(self.data, &mut self.position, &self.options)?;
+                let data_start: usize = self.position;
+                self.row(
+                    start,
+                    data_start,
+                    depth,
+                    &format!("{}{:?} of {} entries", prefix, tag_id, length),
+                );
+                self.position = start;
+                take_array(self.data, &mut self.position, size, &self.options)?;
+                if self.position > data_start {
+                    self.row(data_start, self.position, depth + 1, "array data");
+                }
+                return Ok(());
+            }
+            TagID::List | TagID::Compound if depth >= self.options.max_depth => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Nesting exceeds the depth limit of {}",
+                        self.options.max_depth
+                    ),
+                ));
+            }
+            TagID::List => {
+                let (element_id, length): (TagID, usize) =
+                    read_list_header_at(self.data, &mut self.position, &self.options)?;
+                self.row(
+                    start,
+                    self.position,
+                    depth,
+                    &format!("{}List of {:?}, {} entries", prefix, element_id, length),
+                );
+                for index in 0..length {
+                    self.payload(element_id, depth + 1, &format!("[{}] ", index))?;
+                }
+                return Ok(());
+            }
+            TagID::Compound => {
+                if !prefix.is_empty() {
+                    self.row(start, start, depth, prefix.trim_end());
+                }
+                while self.named_tag(depth + 1)? != TagID::End {}
+                return Ok(());
+            }
+        };
+        self.row(start, self.position, depth, &format!("{}{}", prefix, label));
+        Ok(())
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nbt, write, Tag};
+
+    #[test]
+    fn test_dump_annotated() {
+        let tag: Tag = nbt!({ id: 3i16, list: [{ a: [I; 1, 2] }] });
+        let data: Vec<u8> = write(&tag, "root").unwrap();
+        let dump: String = dump_annotated(&data);
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(
+            lines[0].split_whitespace().collect::<Vec<_>>(),
+            ["00000000", "0a", "Compound"]
+        );
+        assert!(lines[1].ends_with("name \"root\""));
+        assert!(lines.iter().any(|line| line.ends_with("  3s")));
+        assert!(lines
+            .iter()
+            .any(|line| line.ends_with("List of Compound, 1 entries")));
+        assert!(lines
+            .iter()
+            .any(|line| line.ends_with("IntArray of 2 entries")));
+        assert!(lines.last().unwrap().ends_with("End"));
+
+        let dump: String = dump_annotated(&data[..data.len() - 3]);
+        assert!(dump
+            .lines()
+            .last()
+            .unwrap()
+            .contains("error: Unexpected end of input"));
+        let mut trailing: Vec<u8> = data.clone();
+        trailing.push(0xff);
+        assert!(dump_annotated(&trailing).ends_with("trailing bytes\n"));
+
+        // An End list claiming a huge length, and an array whose byte size overflows.
+        let end_list: [u8; 8] = [9, 0, 0, 0, 0x7f, 0xff, 0xff, 0xff];
+        assert!(dump_annotated(&end_list).contains("error:"));
+        let long_array: [u8; 7] = [12, 0, 0, 0x7f, 0xff, 0xff, 0xff];
+        assert!(dump_annotated(&long_array).contains("error:"));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{
+    compress, compress_gzip_with_header, decompress, detect_compression, read,
+    read_compressed_from, read_from, write, CompressionFormat, GzipHeader, NamedTag, Tag,
+};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Result};
+use std::path::{Path, PathBuf};
+
+/// How a file's compression is chosen when reading or writing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileCompression {
+    /// Detect gzip or zlib from the magic bytes when reading; gzip when writing.
+    #[default]
+    Auto,
+    /// Plain, uncompressed NBT.
+    None,
+    /// Always use the given format.
+    Format(CompressionFormat),
+}
+
+/// Options for [`read_nbt_file`] and [`write_nbt_file`].
+#[derive(Debug, Clone, Default)]
+pub struct FileOptions {
+    pub compression: FileCompression,
+}
+
+impl FileOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn compression(mut self, compression: FileCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+}
+
+/// Reads an NBT file, handling compression, and returns its root tag along with its name.
+/// The file is decompressed and parsed as it streams in, rather than loaded up front.
+pub fn read_nbt_file<P: AsRef<Path>>(path: P, options: &FileOptions) -> Result<NamedTag> {
+    let mut reader: BufReader<File> = BufReader::new(File::open(path)?);
+    let format: Option<CompressionFormat> = match options.compression {
+        FileCompression::Auto => detect_compression(reader.fill_buf()?),
+        FileCompression::None => None,
+        FileCompression::Format(format) => Some(format),
+    };
+    match format {
+        Some(format) => read_compressed_from(reader, format),
+        None => read_from(&mut reader),
+    }
+}
+
+/// Writes an NBT file with the given root name, compressing it as configured.
+pub fn write_nbt_file<P: AsRef<Path>>(
+    path: P,
+    tag: &Tag,
+    root_name: &str,
+    options: &FileOptions,
+) -> Result<()> {
+    let data: Vec<u8> = write(tag, root_name)?;
+    let data: Vec<u8> = match options.compression {
+        FileCompression::Auto => compress(&data, CompressionFormat::Gzip)?,
+        FileCompression::None => data,
+        FileCompression::Format(format) => compress(&data, format)?,
+    };
+    fs::write(path, data)
+}
+
+/// An NBT file on disk, remembering how it was encoded so it can be saved back unchanged.
+#[derive(Debug, Clone)]
+pub struct NbtFile {
+    pub path: PathBuf,
+    pub root_name: String,
+    pub compression: Option<CompressionFormat>,
+    /// The original gzip header, re-emitted on save so unchanged files keep identical bytes.
+    pub gzip_header: Option<GzipHeader>,
+    pub tag: Tag,
+}
+
+impl NbtFile {
+    /// Reads an NBT file, detecting gzip or zlib compression from its magic bytes.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data: Vec<u8> = fs::read(path.as_ref())?;
+        Self::from_bytes(path, &data)
+    }
+
+    /// Parses the contents of an NBT file that was already read, remembering `path` for
+    /// [`NbtFile::save`].
+    pub fn from_bytes<P: AsRef<Path>>(path: P, data: &[u8]) -> Result<Self> {
+        let path: PathBuf = path.as_ref().to_path_buf();
+        let compression: Option<CompressionFormat> = detect_compression(data);
+        let NamedTag {
+            name: root_name,
+            tag,
+        } = match compression {
+            Some(format) => read(&decompress(data, format)?)?,
+            None => read(data)?,
+        };
+        let gzip_header: Option<GzipHeader> = match compression {
+            Some(CompressionFormat::Gzip) => Some(GzipHeader::parse(data)?),
+            _ => None,
+        };
+        Ok(NbtFile {
+            path,
+            root_name,
+            compression,
+            gzip_header,
+            tag,
+        })
+    }
+
+    /// Encodes the tag with the original root name and compression format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let data: Vec<u8> = write(&self.tag, &self.root_name)?;
+        match (self.compression, &self.gzip_header) {
+            (Some(CompressionFormat::Gzip), Some(header)) => {
+                compress_gzip_with_header(&data, header)
+            }
+            (Some(format), _) => compress(&data, format),
+            (None, _) => Ok(data),
+        }
+    }
+
+    /// Writes the file back to the path it was opened from.
+    pub fn save(&self) -> Result<()> {
+        self.save_as(&self.path)
+    }
+
+    /// Writes the file to another path, keeping the original encoding.
+    pub fn save_as<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_bytes()?)
+    }
+}
+
+// Test module. The fixtures are gzip compressed.
+#[cfg(all(test, feature = "compression"))]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_nbt_file_round_trip() {
+        let file: NbtFile = NbtFile::open("./test/bigtest.nbt").expect("Open failed");
+        assert_eq!(file.root_name, "Level");
+        assert_eq!(file.compression, Some(CompressionFormat::Gzip));
+
+        let output: PathBuf = env::temp_dir().join("rust_nbt_test_nbt_file_round_trip.nbt");
+        file.save_as(&output).expect("Save failed");
+        let reopened: NbtFile = NbtFile::open(&output).expect("Reopen failed");
+        fs::remove_file(&output).expect("Cleanup failed");
+        assert_eq!(reopened.root_name, "Level");
+        assert_eq!(reopened.compression, Some(CompressionFormat::Gzip));
+        assert_eq!(reopened.gzip_header, file.gzip_header);
+
+        let original: Vec<u8> = decompress(
+            &fs::read("./test/bigtest.nbt").unwrap(),
+            CompressionFormat::Gzip,
+        )
+        .expect("Decompression failed");
+        let saved: Vec<u8> = write(&reopened.tag, &reopened.root_name).expect("Write failed");
+        assert_eq!(original, saved);
+
+        let file: NbtFile = NbtFile::open("./test/hello_world.nbt").expect("Open failed");
+        assert_eq!(file.root_name, "hello world");
+        assert_eq!(file.compression, None);
+        assert_eq!(
+            file.to_bytes().unwrap(),
+            fs::read("./test/hello_world.nbt").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_nbt_file_helpers() {
+        let NamedTag {
+            name: root_name,
+            tag,
+        } = read_nbt_file("./test/bigtest.nbt", &FileOptions::new()).expect("Read failed");
+        assert_eq!(root_name, "Level");
+
+        let output: PathBuf = env::temp_dir().join("rust_nbt_test_nbt_file_helpers.nbt");
+        let options: FileOptions =
+            FileOptions::new().compression(FileCompression::Format(CompressionFormat::Deflate));
+        write_nbt_file(&output, &tag, &root_name, &options).expect("Write failed");
+        let raw: Vec<u8> = fs::read(&output).unwrap();
+        assert_eq!(detect_compression(&raw), Some(CompressionFormat::Deflate));
+        let reread: NamedTag = read_nbt_file(&output, &FileOptions::new()).expect("Reread failed");
+        fs::remove_file(&output).expect("Cleanup failed");
+        assert_eq!(reread, NamedTag::new(root_name, tag));
+
+        let uncompressed: FileOptions = FileOptions::new().compression(FileCompression::None);
+        assert!(read_nbt_file("./test/bigtest.nbt", &uncompressed).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use crate::TagID;
+
+/// The binary flavour of an NBT document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NbtFormat {
+    /// Big-endian Java Edition NBT.
+    Java,
+    /// Little-endian Bedrock Edition NBT.
+    Bedrock,
+    /// Little-endian Bedrock NBT behind the 8-byte `level.dat` header (storage version and
+    /// payload length).
+    BedrockWithHeader,
+}
+
+/// Guesses the format of an uncompressed NBT document from its first bytes.
+///
+/// This checks for the Bedrock `level.dat` header, then whether the root tag ID, root name
+/// and first compound entry make sense when read in each byte order. When both orders are
+/// plausible, such as for an empty root name, Java is assumed. Returns `None` if neither
+/// order fits.
+pub fn detect_format(data: &[u8]) -> Option<NbtFormat> {
+    if let [_, _, _, _, l0, l1, l2, l3, rest @ ..] = data {
+        let length: usize = u32::from_le_bytes([*l0, *l1, *l2, *l3]) as usize;
+        if length == rest.len() && is_plausible(rest, false) {
+            return Some(NbtFormat::BedrockWithHeader);
+        }
+    }
+    match (is_plausible(data, true), is_plausible(data, false)) {
+        (true, _) => Some(NbtFormat::Java),
+        (false, true) => Some(NbtFormat::Bedrock),
+        (false, false) => None,
+    }
+}
+
+/// Checks the root tag header, and the first entry if the root is a compound.
+fn is_plausible(data: &[u8], big_endian: bool) -> bool {
+    let Some((root_id, after_name)) = plausible_entry(data, big_endian) else {
+        return false;
+    };
+    if root_id != TagID::Compound {
+        return true;
+    }
+    match data.get(after_name) {
+        None => false,
+        Some(0) => true,
+        Some(_) => plausible_entry(&data[after_name..], big_endian).is_some(),
+    }
+}
+
+/// Reads a named tag header, returning its ID and the offset just past the name.
+fn plausible_entry(data: &[u8], big_endian: bool) -> Option<(TagID, usize)> {
+    let [id, b0, b1, ..] = *data else {
+        return None;
+    };
+    let tag_id: TagID = TagID::try_from(id).ok()?;
+    if tag_id == TagID::End {
+        return None;
+    }
+    let length: usize = if big_endian {
+        u16::from_be_bytes([b0, b1])
+    } else {
+        u16::from_le_bytes([b0, b1])
+    } as usize;
+    let name: &[u8] = data.get(3..3 + length)?;
+    std::str::from_utf8(name).ok()?;
+    Some((tag_id, 3 + length))
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nbt, write, Tag};
+
+    #[test]
+    fn test_detect_format() {
+        let java: Vec<u8> = std::fs::read("./test/hello_world.nbt").unwrap();
+        assert_eq!(detect_format(&java), Some(NbtFormat::Java));
+
+        let level: Vec<u8> = std::fs::read("./test/level.dat").unwrap();
+        assert_eq!(detect_format(&level), Some(NbtFormat::BedrockWithHeader));
+        assert_eq!(detect_format(&level[8..]), Some(NbtFormat::Bedrock));
+
+        let tag: Tag = nbt!({ a: 1 });
+        assert_eq!(
+            detect_format(&write(&tag, "").unwrap()),
+            Some(NbtFormat::Java)
+        );
+        assert_eq!(detect_format(&[0x0a, 0xff]), None);
+        assert_eq!(detect_format(&[0x2a, 0x00, 0x00]), None);
+    }
+}
@@ -0,0 +1,406 @@
+use crate::{CompoundTag, Tag, TagID};
+use serde_json::{Map, Number, Value};
+use std::io::{Error, ErrorKind, Result};
+
+/// How [`json_to_tag`] picks a tag type for JSON numbers, which carry no type of their own.
+///
+/// Booleans always become `Byte` 0 or 1. Numbers with a fraction or exponent, or outside the
+/// `Long` range, always become `Double`. Lists must hold a single tag type, so numeric
+/// elements of differing types are widened to the widest among them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NumberPolicy {
+    /// Integers become the narrowest of `Byte`, `Short`, `Int` or `Long` that fits.
+    Narrowest,
+    /// Integers become `Int`, or `Long` if they don't fit.
+    #[default]
+    IntOrLong,
+    /// Every number becomes `Double`.
+    Double,
+}
+
+/// Converts a tag to JSON. Integers and floats become numbers, arrays and lists become
+/// arrays, and compounds become objects in their original order. The tag types are lost, so
+/// a round trip through [`json_to_tag`] may not give back the same types. Non-finite floats
+/// have no JSON form and become `null`, as does `End`.
+pub fn tag_to_json(tag: &Tag) -> Result<Value> {
+    Ok(match tag {
+        Tag::End => Value::Null,
+        Tag::Byte(value) => Value::from(*value),
+        Tag::Short(value) => Value::from(*value),
+        Tag::Int(value) => Value::from(*value),
+        Tag::Long(value) => Value::from(*value),
+        Tag::Float(value) => Number::from_f64(f64::from(*value)).map_or(Value::Null, Value::Number),
+        Tag::Double(value) => Number::from_f64(*value).map_or(Value::Null, Value::Number),
+        Tag::ByteArray(values) => Value::from(values.clone()),
+        Tag::String(value) => Value::from(value.clone()),
+        Tag::List(entries) => Value::Array(entries.iter().map(tag_to_json).collect::<Result<_>>()?),
+        Tag::Compound(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, entry)| Ok((key.clone(), tag_to_json(entry)?)))
+                .collect::<Result<Map<String, Value>>>()?,
+        ),
+        Tag::IntArray(values) => Value::from(values.clone()),
+        Tag::LongArray(values) => Value::from(values.clone()),
+        Tag::RawEncoded(raw) => tag_to_json(&raw.decode()?)?,
+    })
+}
+
+/// Converts JSON to a tag, typing numbers according to `policy`. Fails on `null`, which has
+/// no NBT equivalent, and on arrays mixing non-numeric types.
+pub fn json_to_tag(value: &Value, policy: NumberPolicy) -> Result<Tag> {
+    Ok(match value {
+        Value::Null => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "JSON null has no NBT equivalent",
+            ))
+        }
+        Value::Bool(value) => Tag::from(*value),
+        Value::Number(number) => number_to_tag(number, policy),
+        Value::String(value) => Tag::String(value.clone()),
+        Value::Array(values) => {
+            let entries: Vec<Tag> = values
+                .iter()
+                .map(|value| json_to_tag(value, policy))
+                .collect::<Result<_>>()?;
+            Tag::List(unify_list(entries)?)
+        }
+        Value::Object(fields) => Tag::Compound(
+            fields
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), json_to_tag(value, policy)?)))
+                .collect::<Result<CompoundTag>>()?,
+        ),
+    })
+}
+
+fn number_to_tag(number: &Number, policy: NumberPolicy) -> Tag {
+    let Some(value) = number.as_i64().filter(|_| policy != NumberPolicy::Double) else {
+        return Tag::Double(number.as_f64().unwrap_or(f64::NAN));
+    };
+    match policy {
+        NumberPolicy::Narrowest if i8::try_from(value).is_ok() => Tag::Byte(value as i8),
+        NumberPolicy::Narrowest if i16::try_from(value).is_ok() => Tag::Short(value as i16),
+        _ if i32::try_from(value).is_ok() => Tag::Int(value as i32),
+        _ => Tag::Long(value),
+    }
+}
+
+/// Widens mixed numeric list elements to a common type.
+fn unify_list(entries: Vec<Tag>) -> Result<Vec<Tag>> {
+    let rank = |id: TagID| match id {
+        TagID::Byte => Some(0),
+        TagID::Short => Some(1),
+        TagID::Int => Some(2),
+        TagID::Long => Some(3),
+        TagID::Double => Some(4),
+        _ => None,
+    };
+    let Some(first) = entries.first().map(Tag::id) else {
+        return Ok(entries);
+    };
+    if entries.iter().all(|entry| entry.id() == first) {
+        return Ok(entries);
+    }
+    let widest: TagID = entries
+        .iter()
+        .map(|entry| rank(entry.id()).map(|rank| (rank, entry.id())))
+        .collect::<Option<Vec<(u8, TagID)>>>()
+        .and_then(|ranks| ranks.into_iter().max_by_key(|(rank, _)| *rank))
+        .map(|(_, id)| id)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Mixed-type JSON array"))?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| {
+            let value: f64 = match entry {
+                Tag::Byte(value) => f64::from(value),
+                Tag::Short(value) => f64::from(value),
+                Tag::Int(value) => f64::from(value),
+                Tag::Long(value) if widest == TagID::Long => return Tag::Long(value),
+                Tag::Long(value) => value as f64,
+                entry => return entry,
+            };
+            match widest {
+                TagID::Short => Tag::Short(value as i16),
+                TagID::Int => Tag::Int(value as i32),
+                TagID::Long => Tag::Long(value as i64),
+                _ => Tag::Double(value),
+            }
+        })
+        .collect())
+}
+
+/// Converts a tag to typed JSON, which records every tag's type so that
+/// [`typed_json_to_tag`] gives back exactly the same tag.
+///
+/// Each tag becomes an object such as `{"type":"long","value":"123"}`. Type names are the
+/// snake case [`TagID`] names. Longs are written as strings, since JavaScript numbers can't
+/// hold them exactly, and non-finite floats as `"NaN"`, `"Infinity"` or `"-Infinity"`.
+/// Compound values are objects of typed entries, and list values are arrays of them.
+pub fn tag_to_typed_json(tag: &Tag) -> Result<Value> {
+    let value: Value = match tag {
+        Tag::End => Value::Null,
+        Tag::Byte(value) => Value::from(*value),
+        Tag::Short(value) => Value::from(*value),
+        Tag::Int(value) => Value::from(*value),
+        Tag::Long(value) => Value::from(value.to_string()),
+        Tag::Float(value) => typed_float(f64::from(*value), value.to_string()),
+        Tag::Double(value) => typed_float(*value, value.to_string()),
+        Tag::ByteArray(values) => Value::from(values.clone()),
+        Tag::String(value) => Value::from(value.clone()),
+        Tag::List(entries) => Value::Array(
+            entries
+                .iter()
+                .map(tag_to_typed_json)
+                .collect::<Result<_>>()?,
+        ),
+        Tag::Compound(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, entry)| Ok((key.clone(), tag_to_typed_json(entry)?)))
+                .collect::<Result<Map<String, Value>>>()?,
+        ),
+        Tag::IntArray(values) => Value::from(values.clone()),
+        Tag::LongArray(values) => Value::from(
+            values
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<String>>(),
+        ),
+        Tag::RawEncoded(raw) => return tag_to_typed_json(&raw.decode()?),
+    };
+    let mut object: Map<String, Value> = Map::new();
+    object.insert("type".to_string(), Value::from(type_name(tag.id())));
+    object.insert("value".to_string(), value);
+    Ok(Value::Object(object))
+}
+
+/// Writes a float so it parses back to the same bits, using its shortest decimal form.
+fn typed_float(value: f64, shortest: String) -> Value {
+    match shortest.parse::<f64>().ok().and_then(Number::from_f64) {
+        Some(number) => Value::Number(number),
+        None if value.is_nan() => Value::from("NaN"),
+        None if value > 0.0 => Value::from("Infinity"),
+        None => Value::from("-Infinity"),
+    }
+}
+
+/// Converts typed JSON, as written by [`tag_to_typed_json`], back to a tag.
+pub fn typed_json_to_tag(value: &Value) -> Result<Tag> {
+    let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_string());
+    let (Some(type_name), Some(value)) = (
+        value.get("type").and_then(Value::as_str),
+        value.get("value"),
+    ) else {
+        return Err(invalid("Typed JSON value must have a type and a value"));
+    };
+    let int = |value: &Value| -> Result<i64> {
+        match value {
+            Value::String(value) => value.parse().ok(),
+            value => value.as_i64(),
+        }
+        .ok_or_else(|| invalid("Expected an integer"))
+    };
+    let float = |value: &Value| -> Result<f64> {
+        match value {
+            Value::String(value) => value.parse().ok(),
+            value => value.as_f64(),
+        }
+        .ok_or_else(|| invalid("Expected a number"))
+    };
+    let array = |value: &Value| -> Result<Vec<i64>> {
+        value
+            .as_array()
+            .ok_or_else(|| invalid("Expected an array"))?
+            .iter()
+            .map(int)
+            .collect()
+    };
+    let tag_id: TagID = parse_type_name(type_name)
+        .ok_or_else(|| invalid(&format!("Unknown tag type {:?}", type_name)))?;
+    let out_of_range = |_| invalid("Integer out of range");
+    Ok(match tag_id {
+        TagID::End => Tag::End,
+        TagID::Byte => Tag::Byte(int(value)?.try_into().map_err(out_of_range)?),
+        TagID::Short => Tag::Short(int(value)?.try_into().map_err(out_of_range)?),
+        TagID::Int => Tag::Int(int(value)?.try_into().map_err(out_of_range)?),
+        TagID::Long => Tag::Long(int(value)?),
+        TagID::Float => Tag::Float(float(value)? as f32),
+        TagID::Double => Tag::Double(float(value)?),
+        TagID::ByteArray => Tag::ByteArray(
+            array(value)?
+                .into_iter()
+                .map(|value| value.try_into().map_err(out_of_range))
+                .collect::<Result<_>>()?,
+        ),
+        TagID::String => Tag::String(
+            value
+                .as_str()
+                .ok_or_else(|| invalid("Expected a string"))?
+                .to_string(),
+        ),
+        TagID::List => {
+            let entries: Vec<Tag> = value
+                .as_array()
+                .ok_or_else(|| invalid("Expected an array"))?
+                .iter()
+                .map(typed_json_to_tag)
+                .collect::<Result<_>>()?;
+            if let Some(first) = entries.first() {
+                if entries.iter().any(|entry| entry.id() != first.id()) {
+                    return Err(invalid("List elements must all have the same type"));
+                }
+            }
+            Tag::List(entries)
+        }
+        TagID::Compound => Tag::Compound(
+            value
+                .as_object()
+                .ok_or_else(|| invalid("Expected an object"))?
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), typed_json_to_tag(value)?)))
+                .collect::<Result<_>>()?,
+        ),
+        TagID::IntArray => Tag::IntArray(
+            array(value)?
+                .into_iter()
+                .map(|value| value.try_into().map_err(out_of_range))
+                .collect::<Result<_>>()?,
+        ),
+        TagID::LongArray => Tag::LongArray(array(value)?),
+    })
+}
+
+fn type_name(tag_id: TagID) -> &'static str {
+    match tag_id {
+        TagID::End => "end",
+        TagID::Byte => "byte",
+        TagID::Short => "short",
+        TagID::Int => "int",
+        TagID::Long => "long",
+        TagID::Float => "float",
+        TagID::Double => "double",
+        TagID::ByteArray => "byte_array",
+        TagID::String => "string",
+        TagID::List => "list",
+        TagID::Compound => "compound",
+        TagID::IntArray => "int_array",
+        TagID::LongArray => "long_array",
+    }
+}
+
+fn parse_type_name(name: &str) -> Option<TagID> {
+    Some(match name {
+        "end" => TagID::End,
+        "byte" => TagID::Byte,
+        "short" => TagID::Short,
+        "int" => TagID::Int,
+        "long" => TagID::Long,
+        "float" => TagID::Float,
+        "double" => TagID::Double,
+        "byte_array" => TagID::ByteArray,
+        "string" => TagID::String,
+        "list" => TagID::List,
+        "compound" => TagID::Compound,
+        "int_array" => TagID::IntArray,
+        "long_array" => TagID::LongArray,
+        _ => return None,
+    })
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_round_trip() {
+        let tag: Tag = nbt!({
+            name: "Steve",
+            health: 20.5,
+            level: 30,
+            seed: 4_000_000_000i64,
+            flags: [B; 1, 0],
+            pos: [1, 300, 70_000],
+        });
+        let value: Value = tag_to_json(&tag).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "name": "Steve",
+                "health": 20.5,
+                "level": 30,
+                "seed": 4_000_000_000i64,
+                "flags": [1, 0],
+                "pos": [1, 300, 70_000],
+            })
+        );
+        assert_eq!(
+            value.as_object().unwrap().keys().collect::<Vec<_>>(),
+            ["name", "health", "level", "seed", "flags", "pos"]
+        );
+
+        assert_eq!(
+            json_to_tag(&value, NumberPolicy::IntOrLong).unwrap(),
+            nbt!({
+                name: "Steve",
+                health: 20.5,
+                level: 30,
+                seed: 4_000_000_000i64,
+                flags: [1, 0],
+                pos: [1, 300, 70_000],
+            })
+        );
+        assert_eq!(
+            json_to_tag(&json!([1, 300, true]), NumberPolicy::Narrowest).unwrap(),
+            nbt!([1i16, 300i16, 1i16])
+        );
+        assert_eq!(
+            json_to_tag(&json!([1, 2.5]), NumberPolicy::Narrowest).unwrap(),
+            nbt!([1.0, 2.5])
+        );
+        assert_eq!(
+            json_to_tag(&json!(7), NumberPolicy::Double).unwrap(),
+            Tag::Double(7.0)
+        );
+        assert!(json_to_tag(&json!([1, "a"]), NumberPolicy::default()).is_err());
+        assert!(json_to_tag(&json!({ "a": null }), NumberPolicy::default()).is_err());
+    }
+
+    #[test]
+    fn test_typed_json_round_trip() {
+        let tag: Tag = nbt!({
+            seed: i64::MAX,
+            scale: 0.1f32,
+            ratio: f64::NAN,
+            flag: 1i8,
+            data: [B; -1, 2],
+            states: [L; i64::MIN],
+            list: [[1i16], []],
+        });
+        let value: Value = tag_to_typed_json(&tag).unwrap();
+        assert_eq!(
+            value["value"]["seed"],
+            json!({ "type": "long", "value": "9223372036854775807" })
+        );
+        assert_eq!(value["value"]["scale"]["value"], json!(0.1));
+        assert_eq!(value["value"]["ratio"]["value"], json!("NaN"));
+        assert_eq!(
+            value["value"]["states"]["value"],
+            json!(["-9223372036854775808"])
+        );
+
+        let parsed: Tag = typed_json_to_tag(&value).unwrap();
+        assert_eq!(parsed.to_snbt(), tag.to_snbt());
+        assert!(typed_json_to_tag(&json!({ "type": "byte", "value": 300 })).is_err());
+        assert!(typed_json_to_tag(&json!({ "type": "uuid", "value": 0 })).is_err());
+        let mixed: Value = json!({
+            "type": "list",
+            "value": [{ "type": "byte", "value": 1 }, { "type": "string", "value": "a" }],
+        });
+        assert!(typed_json_to_tag(&mixed).is_err());
+    }
+}
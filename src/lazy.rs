@@ -0,0 +1,304 @@
+use crate::{
+    decode_mutf8, read_length, read_list_header, read_payload_with, ReadOptions, Tag, TagID,
+};
+use indexmap::IndexMap;
+use std::io::{Error, ErrorKind, Result};
+use std::ops::Range;
+
+/// A view of an encoded NBT document that only decodes the parts that are accessed.
+///
+/// Creating a view scans the payload once to index the positions of its direct children,
+/// skipping over nested data without allocating it. Children are indexed when they're
+/// looked up, and [`LazyNbt::decode`] materializes a subtree as a regular [`Tag`]. The depth
+/// and length limits of the [`ReadOptions`] apply while scanning.
+///
+/// ```
+/// use rust_nbt::{nbt, write, LazyNbt, Tag};
+///
+/// let data: Vec<u8> = write(&nbt!({ Level: { Sections: [{ Y: 0 }] } }), "").unwrap();
+/// let lazy: LazyNbt = LazyNbt::new(&data).unwrap();
+/// let y: Tag = lazy.get("Level")?.get("Sections")?.index(0)?.get("Y")?.decode()?;
+/// assert_eq!(y, Tag::Int(0));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct LazyNbt<'a> {
+    data: &'a [u8],
+    tag_id: TagID,
+    children: Children,
+    options: ReadOptions,
+}
+
+/// Positions of a container's direct children within its payload.
+#[derive(Debug, Clone)]
+enum Children {
+    None,
+    List(TagID, Vec<Range<usize>>),
+    Compound(IndexMap<String, (TagID, Range<usize>)>),
+}
+
+impl<'a> LazyNbt<'a> {
+    /// Indexes an uncompressed NBT document. The root name is skipped.
+    pub fn new(data: &'a [u8]) -> Result<Self> {
+        LazyNbt::new_with(data, &ReadOptions::default())
+    }
+
+    /// Indexes an uncompressed NBT document with the limits of the given options.
+    pub fn new_with(data: &'a [u8], options: &ReadOptions) -> Result<Self> {
+        let mut position: usize = 0;
+        let tag_id: TagID = read_id(data, &mut position)?;
+        let name_length: usize = read_u16(data, &mut position)? as usize;
+        take(data, &mut position, name_length)?;
+        let start: usize = position;
+        skip_payload(data, &mut position, tag_id, options, 1)?;
+        LazyNbt::from_payload(&data[start..position], tag_id, options.clone())
+    }
+
+    /// Indexes the children of a single payload.
+    fn from_payload(data: &'a [u8], tag_id: TagID, options: ReadOptions) -> Result<Self> {
+        let mut position: usize = 0;
+        let children: Children = match tag_id {
+            TagID::List => {
+                let (element_id, length): (TagID, usize) =
+                    read_list_header_at(data, &mut position, &options)?;
+                let mut entries: Vec<Range<usize>> = Vec::new();
+                for _ in 0..length {
+                    let start: usize = position;
+                    skip_payload(data, &mut position, element_id, &options, 1)?;
+                    entries.push(start..position);
+                }
+                Children::List(element_id, entries)
+            }
+            TagID::Compound => {
+                let mut entries: IndexMap<String, (TagID, Range<usize>)> = IndexMap::new();
+                loop {
+                    let entry_id: TagID = read_id(data, &mut position)?;
+                    if entry_id == TagID::End {
+                        break;
+                    }
+                    let name_length: usize = read_u16(data, &mut position)? as usize;
+                    let name: String = decode_mutf8(take(data, &mut position, name_length)?)?;
+                    let start: usize = position;
+                    skip_payload(data, &mut position, entry_id, &options, 1)?;
+                    entries.insert(name, (entry_id, start..position));
+                }
+                Children::Compound(entries)
+            }
+            _ => Children::None,
+        };
+        Ok(LazyNbt {
+            data,
+            tag_id,
+            children,
+            options,
+        })
+    }
+
+    pub fn tag_id(&self) -> TagID {
+        self.tag_id
+    }
+
+    /// The encoded payload of this tag.
+    pub fn payload(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Number of entries in a compound or list, or zero for other tags.
+    pub fn len(&self) -> usize {
+        match &self.children {
+            Children::None => 0,
+            Children::List(_, entries) => entries.len(),
+            Children::Compound(entries) => entries.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The keys of a compound, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        let entries: Option<&IndexMap<String, (TagID, Range<usize>)>> = match &self.children {
+            Children::Compound(entries) => Some(entries),
+            _ => None,
+        };
+        entries
+            .into_iter()
+            .flat_map(|entries| entries.keys().map(String::as_str))
+    }
+
+    /// Looks up a compound entry, indexing it on the way.
+    pub fn get(&self, key: &str) -> Result<LazyNbt<'a>> {
+        let Children::Compound(entries) = &self.children else {
+            return Err(Error::new(ErrorKind::InvalidInput, "Tag is not a compound"));
+        };
+        let (tag_id, span) = entries
+            .get(key)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("Missing key {:?}", key)))?;
+        LazyNbt::from_payload(&self.data[span.clone()], *tag_id, self.options.clone())
+    }
+
+    /// Looks up a list element, indexing it on the way.
+    pub fn index(&self, index: usize) -> Result<LazyNbt<'a>> {
+        let Children::List(tag_id, entries) = &self.children else {
+            return Err(Error::new(ErrorKind::InvalidInput, "Tag is not a list"));
+        };
+        let span: &Range<usize> = entries.get(index).ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("Index {} out of bounds", index),
+            )
+        })?;
+        LazyNbt::from_payload(&self.data[span.clone()], *tag_id, self.options.clone())
+    }
+
+    /// Decodes this subtree into a regular tag.
+    pub fn decode(&self) -> Result<Tag> {
+        read_payload_with(self.data, self.tag_id, &self.options)
+    }
+}
+
+/// Slice reading helpers shared with the borrowed parser.
+pub(crate) fn take<'a>(data: &'a [u8], position: &mut usize, length: usize) -> Result<&'a [u8]> {
+    let bytes: &[u8] = position
+        .checked_add(length)
+        .and_then(|end| data.get(*position..end))
+        .ok_or_else(|| Error::new(ErrorKind::UnexpectedEof, "Unexpected end of input"))?;
+    *position += length;
+    Ok(bytes)
+}
+
+pub(crate) fn read_id(data: &[u8], position: &mut usize) -> Result<TagID> {
+    TagID::try_from(take(data, position, 1)?[0])
+}
+
+pub(crate) fn read_u16(data: &[u8], position: &mut usize) -> Result<u16> {
+    let bytes: &[u8] = take(data, position, 2)?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+/// Reads a list or array length at `position`, with the checks of [`read_length`].
+pub(crate) fn read_length_at(
+    data: &[u8],
+    position: &mut usize,
+    options: &ReadOptions,
+) -> Result<usize> {
+    let mut bytes: &[u8] = take(data, position, 4)?;
+    read_length(&mut bytes, options)
+}
+
+/// Reads a list's element ID and length at `position`, with the checks of
+/// [`read_list_header`].
+pub(crate) fn read_list_header_at(
+    data: &[u8],
+    position: &mut usize,
+    options: &ReadOptions,
+) -> Result<(TagID, usize)> {
+    let mut bytes: &[u8] = take(data, position, 5)?;
+    read_list_header(&mut bytes, options)
+}
+
+/// Reads an array length and takes that many elements of `size` bytes each.
+pub(crate) fn take_array<'a>(
+    data: &'a [u8],
+    position: &mut usize,
+    size: usize,
+    options: &ReadOptions,
+) -> Result<&'a [u8]> {
+    let length: usize = read_length_at(data, position, options)?;
+    let bytes: usize = length.checked_mul(size).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Array of {} elements is too large", length),
+        )
+    })?;
+    take(data, position, bytes)
+}
+
+/// Advances past a payload without decoding it.
+fn skip_payload(
+    data: &[u8],
+    position: &mut usize,
+    tag_id: TagID,
+    options: &ReadOptions,
+    depth: usize,
+) -> Result<()> {
+    let skip_array = |position: &mut usize, size: usize| -> Result<()> {
+        take_array(data, position, size, options).map(|_| ())
+    };
+    match tag_id {
+        TagID::End => Ok(()),
+        TagID::Byte => take(data, position, 1).map(|_| ()),
+        TagID::Short => take(data, position, 2).map(|_| ()),
+        TagID::Int | TagID::Float => take(data, position, 4).map(|_| ()),
+        TagID::Long | TagID::Double => take(data, position, 8).map(|_| ()),
+        TagID::ByteArray => skip_array(position, 1),
+        TagID::IntArray => skip_array(position, 4),
+        TagID::LongArray => skip_array(position, 8),
+        TagID::String => {
+            let length: usize = read_u16(data, position)? as usize;
+            take(data, position, length).map(|_| ())
+        }
+        TagID::List | TagID::Compound if depth > options.max_depth => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Nesting exceeds the depth limit of {}", options.max_depth),
+        )),
+        TagID::List => {
+            let (element_id, length): (TagID, usize) =
+                read_list_header_at(data, position, options)?;
+            for _ in 0..length {
+                skip_payload(data, position, element_id, options, depth + 1)?;
+            }
+            Ok(())
+        }
+        TagID::Compound => loop {
+            let entry_id: TagID = read_id(data, position)?;
+            if entry_id == TagID::End {
+                return Ok(());
+            }
+            let name_length: usize = read_u16(data, position)? as usize;
+            take(data, position, name_length)?;
+            skip_payload(data, position, entry_id, options, depth + 1)?;
+        },
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lazy_nbt() {
+        let data: Vec<u8> = std::fs::read("./test/bigtest_raw.nbt").unwrap();
+        let tag: Tag = crate::read(&data).unwrap().tag;
+        let lazy: LazyNbt = LazyNbt::new(&data).unwrap();
+        assert_eq!(lazy.tag_id(), TagID::Compound);
+        assert_eq!(lazy.keys().count(), lazy.len());
+        assert_eq!(lazy.decode().unwrap(), tag);
+
+        let egg: LazyNbt = lazy
+            .get("nested compound test")
+            .unwrap()
+            .get("egg")
+            .unwrap();
+        assert_eq!(
+            egg.decode().unwrap(),
+            *tag.get_path("\"nested compound test\".egg").unwrap()
+        );
+        let first: LazyNbt = lazy.get("listTest (long)").unwrap().index(0).unwrap();
+        assert_eq!(first.decode().unwrap(), Tag::Long(11));
+
+        assert_eq!(lazy.get("missing").unwrap_err().kind(), ErrorKind::NotFound);
+        assert!(first.get("x").is_err());
+        assert!(LazyNbt::new(&data[..data.len() - 1]).is_err());
+
+        let limited: ReadOptions = ReadOptions::new().max_array_length(4);
+        assert!(LazyNbt::new_with(&data, &limited).is_err());
+        let ends: Vec<u8> = vec![0x09, 0, 0, 0x00, 0x7f, 0xff, 0xff, 0xff];
+        assert_eq!(
+            LazyNbt::new(&ends).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{
+    read_nbt_file, write_nbt_file, CompoundTag, FileOptions, NbtDeserialize, NbtSerialize,
+};
+use indexmap::IndexMap;
+use std::io::Result;
+use std::path::Path;
+
+/// A Java Edition `level.dat` file.
+///
+/// Only commonly used keys get their own fields. Everything else is kept in the `extra`
+/// compounds, so loading and saving a file doesn't lose data from newer or modded versions.
+///
+/// ```no_run
+/// use rust_nbt::LevelDat;
+///
+/// let mut level: LevelDat = LevelDat::load("world/level.dat")?;
+/// level.data.level_name = "Renamed".to_string();
+/// level.save("world/level.dat")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct LevelDat {
+    #[nbt(rename = "Data")]
+    pub data: LevelData,
+    #[nbt(extra)]
+    pub extra: CompoundTag,
+}
+
+/// The `Data` compound of a `level.dat` file.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct LevelData {
+    #[nbt(rename = "DataVersion")]
+    pub data_version: Option<i32>,
+    #[nbt(rename = "LevelName")]
+    pub level_name: String,
+    /// The storage format version, 19133 for Anvil.
+    pub version: Option<i32>,
+    #[nbt(rename = "Version")]
+    pub game_version: Option<GameVersion>,
+    #[nbt(rename = "GameType")]
+    pub game_type: Option<i32>,
+    #[nbt(rename = "Difficulty")]
+    pub difficulty: Option<i8>,
+    pub hardcore: Option<bool>,
+    #[nbt(rename = "allowCommands")]
+    pub allow_commands: Option<bool>,
+    #[nbt(rename = "SpawnX")]
+    pub spawn_x: i32,
+    #[nbt(rename = "SpawnY")]
+    pub spawn_y: i32,
+    #[nbt(rename = "SpawnZ")]
+    pub spawn_z: i32,
+    #[nbt(rename = "Time")]
+    pub time: i64,
+    #[nbt(rename = "DayTime")]
+    pub day_time: Option<i64>,
+    #[nbt(rename = "LastPlayed")]
+    pub last_played: i64,
+    pub raining: Option<bool>,
+    pub thundering: Option<bool>,
+    /// Game rule values, which the game stores as strings.
+    #[nbt(rename = "GameRules")]
+    pub game_rules: Option<IndexMap<String, String>>,
+    #[nbt(rename = "WorldGenSettings")]
+    pub world_gen_settings: Option<WorldGenSettings>,
+    /// The player in a singleplayer world.
+    #[nbt(rename = "Player")]
+    pub player: Option<CompoundTag>,
+    #[nbt(extra)]
+    pub extra: CompoundTag,
+}
+
+/// The game version that last saved a world.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct GameVersion {
+    #[nbt(rename = "Id")]
+    pub id: i32,
+    #[nbt(rename = "Name")]
+    pub name: String,
+    #[nbt(rename = "Series")]
+    pub series: Option<String>,
+    #[nbt(rename = "Snapshot")]
+    pub snapshot: bool,
+}
+
+/// World generation settings, stored in `level.dat` since 1.16.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct WorldGenSettings {
+    pub seed: i64,
+    pub generate_features: bool,
+    pub bonus_chest: bool,
+    /// Generator settings for each dimension, keyed by dimension ID.
+    pub dimensions: CompoundTag,
+    #[nbt(extra)]
+    pub extra: CompoundTag,
+}
+
+impl LevelDat {
+    /// Reads a `level.dat` file, detecting its compression.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        LevelDat::from_nbt(&read_nbt_file(path, &FileOptions::new())?.tag)
+    }
+
+    /// Writes a gzip compressed `level.dat` file, as the game does.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_nbt_file(path, &self.to_nbt()?, "", &FileOptions::new())
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nbt, Tag};
+
+    #[test]
+    fn test_level_dat() {
+        let tag: Tag = nbt!({
+            Data: {
+                DataVersion: 3465,
+                LevelName: "New World",
+                version: 19133,
+                Version: { Id: 3465, Name: "1.20.1", Series: "main", Snapshot: false },
+                SpawnX: 0,
+                SpawnY: 64,
+                SpawnZ: 0,
+                Time: 1000i64,
+                LastPlayed: 1700000000000i64,
+                GameRules: { doDaylightCycle: "true" },
+                WorldGenSettings: {
+                    seed: -5i64,
+                    generate_features: true,
+                    bonus_chest: false,
+                    dimensions: {},
+                    legacy_custom_options: "",
+                },
+                WanderingTraderSpawnChance: 25,
+                DragonFight: { Dragon: { UUID: [I; 1, 2, 3, 4] } },
+            },
+            ModData: 1i8,
+        });
+        let level: LevelDat = LevelDat::from_nbt(&tag).unwrap();
+        assert_eq!(level.data.level_name, "New World");
+        assert_eq!(level.data.game_version.as_ref().unwrap().name, "1.20.1");
+        assert_eq!(
+            level.data.game_rules.as_ref().unwrap()["doDaylightCycle"],
+            "true"
+        );
+        assert_eq!(level.data.world_gen_settings.as_ref().unwrap().seed, -5);
+        assert_eq!(level.data.extra.len(), 2);
+        assert_eq!(level.extra["ModData"], Tag::Byte(1));
+        assert_eq!(level.to_nbt().unwrap(), tag);
+
+        // level.dat is gzip compressed
+        #[cfg(feature = "compression")]
+        {
+            let path: std::path::PathBuf = std::env::temp_dir().join("rust_nbt_test_level.dat");
+            level.save(&path).unwrap();
+            assert_eq!(read_nbt_file(&path, &FileOptions::new()).unwrap().tag, tag);
+            assert_eq!(LevelDat::load(&path).unwrap(), level);
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+}
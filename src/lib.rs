@@ -1,8 +1,82 @@
+extern crate self as rust_nbt;
+
+mod bedrock_key;
+mod chunk;
+mod chunk_cache;
 mod compression;
+mod convert;
+mod coords;
+mod dump;
+mod file;
+mod format;
+#[cfg(feature = "json")]
+mod json;
+mod lazy;
+mod level;
+mod macros;
+mod map;
+mod migrate;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod mutf8;
+mod patch;
+mod path;
+mod player;
 mod read;
+mod region;
+mod snbt;
+mod span;
+mod stats;
+mod stream;
+mod structure;
 mod tag;
+mod tag_ref;
+#[cfg(feature = "json")]
+mod text;
+#[cfg(feature = "json")]
+mod transcode;
+mod uuid;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod world;
 mod write;
+pub use self::uuid::*;
+pub use bedrock_key::*;
+pub use chunk::*;
+pub use chunk_cache::*;
 pub use compression::*;
+pub use convert::*;
+pub use coords::*;
+pub use dump::*;
+pub use file::*;
+pub use format::*;
+#[cfg(feature = "json")]
+pub use json::*;
+pub use lazy::*;
+pub use level::*;
+pub use map::*;
+pub use migrate::*;
+#[cfg(feature = "mmap")]
+pub use mmap::*;
+pub use mutf8::*;
+pub use patch::*;
+pub use path::*;
+pub use player::*;
 pub use read::*;
+pub use region::*;
+pub use rust_nbt_derive::{NbtDeserialize, NbtSerialize};
+pub use snbt::*;
+pub use span::*;
+pub use stats::*;
+pub use stream::*;
+pub use structure::*;
 pub use tag::*;
+pub use tag_ref::*;
+#[cfg(feature = "json")]
+pub use text::*;
+#[cfg(feature = "json")]
+pub use transcode::*;
+#[cfg(feature = "wasm")]
+pub use wasm::*;
+pub use world::*;
 pub use write::*;
@@ -0,0 +1,106 @@
+/// Builds a [`Tag`](crate::Tag) from SNBT-like syntax.
+///
+/// Values are Rust expressions converted with `Tag::from`, so Rust's literal suffixes pick the
+/// tag type: `1i8` is a Byte, `1i16` a Short, `1` an Int, `1i64` a Long, `1.0f32` a Float and
+/// `1.0` a Double. Keys may be string literals or bare identifiers, and typed arrays use the
+/// SNBT `[B; ...]`, `[I; ...]` and `[L; ...]` prefixes.
+///
+/// ```
+/// use rust_nbt::{nbt, Tag};
+///
+/// let player: Tag = nbt!({
+///     "Name": "Steve",
+///     Pos: [1.0, 64.0, -3.5],
+///     Inventory: [{ id: "minecraft:stone", Count: 64i8 }],
+///     UUID: [I; 1, 2, 3, 4],
+/// });
+/// assert_eq!(player.to_snbt(), r#"{Name:"Steve",Pos:[1d,64d,-3.5d],Inventory:[{id:"minecraft:stone",Count:64b}],UUID:[I;1,2,3,4]}"#);
+/// ```
+#[macro_export]
+macro_rules! nbt {
+    ([B; $($value:expr),* $(,)?]) => {
+        $crate::Tag::ByteArray(vec![$($value),*])
+    };
+    ([I; $($value:expr),* $(,)?]) => {
+        $crate::Tag::IntArray(vec![$($value),*])
+    };
+    ([L; $($value:expr),* $(,)?]) => {
+        $crate::Tag::LongArray(vec![$($value),*])
+    };
+    ([$($entries:tt)*]) => {
+        $crate::Tag::List($crate::nbt!(@list [] $($entries)*))
+    };
+    ({$($entries:tt)*}) => {
+        $crate::Tag::Compound({
+            #[allow(unused_mut)]
+            let mut compound: $crate::CompoundTag = $crate::CompoundTag::new();
+            $crate::nbt!(@compound compound $($entries)*);
+            compound
+        })
+    };
+    ($value:expr) => {
+        $crate::Tag::from($value)
+    };
+
+    // List entries, accumulated until the input runs out.
+    (@list [$($done:expr,)*]) => {
+        vec![$($done,)*]
+    };
+    (@list [$($done:expr,)*] {$($value:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::nbt!(@list [$($done,)* $crate::nbt!({$($value)*}),] $($($rest)*)?)
+    };
+    (@list [$($done:expr,)*] [$($value:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::nbt!(@list [$($done,)* $crate::nbt!([$($value)*]),] $($($rest)*)?)
+    };
+    (@list [$($done:expr,)*] $value:expr $(, $($rest:tt)*)?) => {
+        $crate::nbt!(@list [$($done,)* $crate::nbt!($value),] $($($rest)*)?)
+    };
+
+    // Compound entries, inserted one at a time.
+    (@compound $compound:ident) => {};
+    (@compound $compound:ident $key:tt : {$($value:tt)*} $(, $($rest:tt)*)?) => {
+        $compound.insert($crate::nbt!(@key $key), $crate::nbt!({$($value)*}));
+        $crate::nbt!(@compound $compound $($($rest)*)?);
+    };
+    (@compound $compound:ident $key:tt : [$($value:tt)*] $(, $($rest:tt)*)?) => {
+        $compound.insert($crate::nbt!(@key $key), $crate::nbt!([$($value)*]));
+        $crate::nbt!(@compound $compound $($($rest)*)?);
+    };
+    (@compound $compound:ident $key:tt : $value:expr $(, $($rest:tt)*)?) => {
+        $compound.insert($crate::nbt!(@key $key), $crate::nbt!($value));
+        $crate::nbt!(@compound $compound $($($rest)*)?);
+    };
+
+    (@key $key:ident) => {
+        ::std::string::String::from(stringify!($key))
+    };
+    (@key $key:literal) => {
+        ::std::string::String::from($key)
+    };
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use crate::Tag;
+
+    #[test]
+    fn test_nbt_macro() {
+        let count: i8 = 3;
+        let tag: Tag = nbt!({
+            empty_list: [],
+            empty_compound: {},
+            empty_bytes: [B;],
+            nested: [[1i16, -2i16], [B; 1, -1]],
+            "quoted key": count,
+            computed: count as i64 * 2,
+            flag: true,
+            longs: [L; 1, 2,],
+        });
+        assert_eq!(
+            tag.to_snbt(),
+            r#"{empty_list:[],empty_compound:{},empty_bytes:[B;],nested:[[1s,-2s],[B;1b,-1b]],"quoted key":3b,computed:6L,flag:1b,longs:[L;1L,2L]}"#
+        );
+        assert_eq!(nbt!("text").to_snbt(), r#""text""#);
+    }
+}
@@ -1,24 +1,346 @@
-use rust_nbt::{decompress, read, write, Tag};
+use rust_nbt::{
+    compress, decompress, detect_compression, detect_format, dump_annotated, format_path, write,
+    CompressionFormat, NbtFile, NbtFormat, SnbtFormatter, Tag, TagChange, TagID,
+};
+use std::env;
 use std::fs;
-use std::io::Result;
+use std::io::{self, Error, ErrorKind, Result, Write};
+use std::process::ExitCode;
 
-fn main() -> Result<()> {
-    let file: &str = "./test/bigtest.nbt";
-    println!("{}", file);
+const USAGE: &str = "\
+Usage:
+  nbt print <file>
+  nbt convert <input> [output] --to nbt|snbt|json [--compression gzip|zlib|none]
+  nbt diff <old> <new>
+  nbt get <file> <path>
+  nbt set <file> <path> <value> [--type byte|short|int|long|float|double|string]
+  nbt hexdump <file>
 
-    let nbt_bytes: Vec<u8> =
-        decompress(&fs::read(file).unwrap(), rust_nbt::CompressionFormat::Gzip)?;
-    println!("{:?}", &nbt_bytes[0..10]);
+Compression is detected automatically when reading. Text output goes to stdout when no
+output path is given. diff exits with status 1 when the files differ. set keeps the
+existing value's type unless --type is given, and saves with the original compression.";
 
-    // Example usage: Pass an NBT file's binary contents as a Vec<u8>
-    let nbt_data: Tag = read(&nbt_bytes)?;
-    println!("{:#?}", nbt_data);
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args, &mut io::stdout().lock()) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("nbt: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs a command, writing its output to `out` so it can be captured in tests.
+fn run(args: &[String], out: &mut dyn Write) -> Result<ExitCode> {
+    let Some((command, args)) = args.split_first() else {
+        writeln!(out, "{}", USAGE)?;
+        return Ok(ExitCode::SUCCESS);
+    };
+    let args: Args = Args::parse(args)?;
+    match command.as_str() {
+        "print" => print(&args, out),
+        "convert" => convert(&args, out),
+        "diff" => diff(&args, out),
+        "get" => get(&args, out),
+        "set" => set(&args),
+        "hexdump" => hexdump(&args, out),
+        "help" | "--help" | "-h" => {
+            writeln!(out, "{}", USAGE)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        command => Err(usage_error(&format!("Unknown command {:?}", command))),
+    }
+}
+
+/// Positional arguments and `--flag value` options, in any order.
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut positional: Vec<String> = Vec::new();
+        let mut options: Vec<(String, String)> = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(flag) => {
+                    let value: &String = args
+                        .next()
+                        .ok_or_else(|| usage_error(&format!("Missing value for --{}", flag)))?;
+                    options.push((flag.to_string(), value.clone()));
+                }
+                None => positional.push(arg.clone()),
+            }
+        }
+        Ok(Args {
+            positional,
+            options,
+        })
+    }
+
+    fn path(&self, index: usize) -> Result<&str> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| usage_error("Missing file argument"))
+    }
+
+    fn option(&self, flag: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(name, _)| name == flag)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn usage_error(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, format!("{}\n\n{}", message, USAGE))
+}
+
+/// Opens an NBT file, rejecting Bedrock files with a clear message rather than a parse error.
+fn open(path: &str) -> Result<NbtFile> {
+    let data: Vec<u8> = fs::read(path)?;
+    NbtFile::from_bytes(path, &data).map_err(|error| {
+        let decompressed: Option<Vec<u8>> = match detect_compression(&data) {
+            Some(format) => decompress(&data, format).ok(),
+            None => Some(data),
+        };
+        match decompressed.as_deref().and_then(detect_format) {
+            Some(NbtFormat::Bedrock | NbtFormat::BedrockWithHeader) => Error::new(
+                ErrorKind::Unsupported,
+                format!(
+                    "{} is little-endian Bedrock NBT, which isn't supported",
+                    path
+                ),
+            ),
+            _ => error,
+        }
+    })
+}
+
+fn print(args: &Args, out: &mut dyn Write) -> Result<ExitCode> {
+    let file: NbtFile = open(args.path(0)?)?;
+    let compression: String = match file.compression {
+        Some(format) => format!("{:?}", format).to_lowercase(),
+        None => "none".to_string(),
+    };
+    writeln!(
+        out,
+        "# root name: {:?}, compression: {}",
+        file.root_name, compression
+    )?;
+    writeln!(out, "{}", SnbtFormatter::new().format(&file.tag))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn convert(args: &Args, out: &mut dyn Write) -> Result<ExitCode> {
+    if let Some(endian) = args.option("endian").filter(|endian| *endian != "big") {
+        return Err(Error::new(
+            ErrorKind::Unsupported,
+            format!("Only big-endian NBT is supported, not {:?}", endian),
+        ));
+    }
+    let file: NbtFile = open(args.path(0)?)?;
+    let output: Vec<u8> = match args.option("to") {
+        Some("nbt") => {
+            let data: Vec<u8> = write(&file.tag, &file.root_name)?;
+            match args.option("compression").unwrap_or("gzip") {
+                "gzip" => compress(&data, CompressionFormat::Gzip)?,
+                "zlib" => compress(&data, CompressionFormat::Deflate)?,
+                "none" => data,
+                other => return Err(usage_error(&format!("Unknown compression {:?}", other))),
+            }
+        }
+        Some("snbt") => format!("{}\n", SnbtFormatter::new().format(&file.tag)).into_bytes(),
+        Some("json") => to_json(&file)?,
+        Some(other) => return Err(usage_error(&format!("Unknown output format {:?}", other))),
+        None => return Err(usage_error("Missing --to")),
+    };
+    match args.positional.get(1) {
+        Some(path) => fs::write(path, output)?,
+        None => out.write_all(&output)?,
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn diff(args: &Args, out: &mut dyn Write) -> Result<ExitCode> {
+    let old: NbtFile = open(args.path(0)?)?;
+    let new: NbtFile = open(args.path(1)?)?;
+    let changes: Vec<TagChange> = old.tag.diff(&new.tag);
+    if old.root_name != new.root_name {
+        writeln!(
+            out,
+            "~ <root name>: {:?} -> {:?}",
+            old.root_name, new.root_name
+        )?;
+    }
+    for change in &changes {
+        let path: String = match format_path(&change.path) {
+            path if path.is_empty() => "<root>".to_string(),
+            path => path,
+        };
+        match (&change.old, &change.new) {
+            (Some(old), Some(new)) => writeln!(out, "~ {}: {} -> {}", path, old, new)?,
+            (None, Some(new)) => writeln!(out, "+ {}: {}", path, new)?,
+            (Some(old), None) => writeln!(out, "- {}: {}", path, old)?,
+            (None, None) => {}
+        }
+    }
+    if changes.is_empty() && old.root_name == new.root_name {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::from(1))
+    }
+}
+
+fn get(args: &Args, out: &mut dyn Write) -> Result<ExitCode> {
+    let file: NbtFile = open(args.path(0)?)?;
+    let path: &str = args.path(1)?;
+    let tag: &Tag = file
+        .tag
+        .get_path(path)
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("No value at {}", path)))?;
+    writeln!(out, "{}", SnbtFormatter::new().format(tag))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn set(args: &Args) -> Result<ExitCode> {
+    let mut file: NbtFile = open(args.path(0)?)?;
+    let path: &str = args.path(1)?;
+    let value: &str = args.path(2)?;
+    let tag_id: TagID = match args.option("type") {
+        Some(name) => parse_type(name)?,
+        None => file.tag.get_path(path).map_or(TagID::String, Tag::id),
+    };
+    file.tag.set_path(path, parse_value(value, tag_id)?)?;
+    file.save()?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn hexdump(args: &Args, out: &mut dyn Write) -> Result<ExitCode> {
+    let data: Vec<u8> = fs::read(args.path(0)?)?;
+    let data: Vec<u8> = match detect_compression(&data) {
+        Some(format) => decompress(&data, format)?,
+        None => data,
+    };
+    write!(out, "{}", dump_annotated(&data))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn parse_type(name: &str) -> Result<TagID> {
+    Ok(match name {
+        "byte" => TagID::Byte,
+        "short" => TagID::Short,
+        "int" => TagID::Int,
+        "long" => TagID::Long,
+        "float" => TagID::Float,
+        "double" => TagID::Double,
+        "string" => TagID::String,
+        other => return Err(usage_error(&format!("Unknown type {:?}", other))),
+    })
+}
+
+fn parse_value(value: &str, tag_id: TagID) -> Result<Tag> {
+    let invalid = || {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("{:?} is not a valid {:?}", value, tag_id),
+        )
+    };
+    Ok(match tag_id {
+        TagID::Byte => match value {
+            "true" => Tag::Byte(1),
+            "false" => Tag::Byte(0),
+            value => Tag::Byte(value.parse().map_err(|_| invalid())?),
+        },
+        TagID::Short => Tag::Short(value.parse().map_err(|_| invalid())?),
+        TagID::Int => Tag::Int(value.parse().map_err(|_| invalid())?),
+        TagID::Long => Tag::Long(value.parse().map_err(|_| invalid())?),
+        TagID::Float => Tag::Float(value.parse().map_err(|_| invalid())?),
+        TagID::Double => Tag::Double(value.parse().map_err(|_| invalid())?),
+        TagID::String => Tag::String(value.to_string()),
+        tag_id => {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                format!("Setting {:?} values isn't supported", tag_id),
+            ))
+        }
+    })
+}
+
+#[cfg(feature = "json")]
+fn to_json(file: &NbtFile) -> Result<Vec<u8>> {
+    let mut output: Vec<u8> = serde_json::to_vec_pretty(&rust_nbt::tag_to_json(&file.tag)?)?;
+    output.push(b'\n');
+    Ok(output)
+}
+
+#[cfg(not(feature = "json"))]
+fn to_json(_: &NbtFile) -> Result<Vec<u8>> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "JSON output needs the json feature",
+    ))
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_nbt::{nbt, read};
+    use std::path::PathBuf;
+
+    fn run_args(args: &[&str]) -> (Result<ExitCode>, String) {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let mut out: Vec<u8> = Vec::new();
+        let result: Result<ExitCode> = run(&args, &mut out);
+        (result, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_set() {
+        let path: PathBuf = env::temp_dir().join("rust_nbt_test_cli_set.nbt");
+        let data: Vec<u8> = write(&nbt!({ Health: 20.0f32, Name: "Steve" }), "").unwrap();
+        fs::write(&path, compress(&data, CompressionFormat::Lz4).unwrap()).unwrap();
+        let file: &str = path.to_str().unwrap();
+
+        assert!(run_args(&["set", file, "Health", "15.5"]).0.is_ok());
+        assert!(run_args(&["set", file, "Level", "3", "--type", "short"])
+            .0
+            .is_ok());
+        let saved: Vec<u8> = fs::read(&path).unwrap();
+        assert_eq!(detect_compression(&saved), Some(CompressionFormat::Lz4));
+        assert_eq!(
+            read(&decompress(&saved, CompressionFormat::Lz4).unwrap())
+                .unwrap()
+                .tag,
+            nbt!({ Health: 15.5f32, Name: "Steve", Level: 3i16 })
+        );
+        assert_eq!(run_args(&["get", file, "Level"]).1, "3s\n".to_string());
 
-    let recompile: Vec<u8> = write(&nbt_data, "Level")?;
-    println!("{:?}", &recompile[0..10]);
+        let error: Error = run_args(&["set", file, "Health", "lots"]).0.unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert!(run_args(&["set", file, "Level", "1", "--type", "list"])
+            .0
+            .is_err());
+        assert!(run_args(&["set", file, "Level", "--type"]).0.is_err());
+        assert_eq!(fs::read(&path).unwrap(), saved);
+        fs::remove_file(&path).unwrap();
+    }
 
-    assert_eq!(&nbt_bytes, &recompile);
-    println!("Successful r/w!");
+    #[test]
+    fn test_nan_values() {
+        let (result, output): (Result<ExitCode>, String) =
+            run_args(&["diff", "./test/nan.nbt", "./test/nan.nbt"]);
+        assert_eq!(result.unwrap(), ExitCode::SUCCESS);
+        assert_eq!(output, "");
 
-    Ok(())
+        let (result, output): (Result<ExitCode>, String) = run_args(&["print", "./test/nan.nbt"]);
+        assert_eq!(result.unwrap(), ExitCode::SUCCESS);
+        assert!(output.ends_with("{\n  f: NaNf,\n  d: NaNd\n}\n"));
+    }
 }
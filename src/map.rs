@@ -0,0 +1,136 @@
+use crate::{
+    read_nbt_file, write_nbt_file, CompoundTag, FileOptions, NbtDeserialize, NbtSerialize, Tag,
+};
+use std::io::Result;
+use std::path::Path;
+
+/// Width and height of a map in pixels.
+pub const MAP_SIZE: usize = 128;
+
+/// A `data/map_<id>.dat` file, holding what a filled map item shows.
+///
+/// Keys without their own field are kept in the `extra` compounds, so saving doesn't lose
+/// data.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct MapData {
+    #[nbt(rename = "DataVersion")]
+    pub data_version: Option<i32>,
+    pub data: MapItemData,
+    #[nbt(extra)]
+    pub extra: CompoundTag,
+}
+
+/// The `data` compound of a map file.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct MapItemData {
+    /// Zoom level from 0 to 4, where each pixel covers `2^scale` blocks.
+    #[nbt(byte)]
+    pub scale: u8,
+    /// A dimension ID such as `"minecraft:overworld"` since 1.16, a `Byte` or `Int` before.
+    pub dimension: Option<Tag>,
+    #[nbt(rename = "xCenter")]
+    pub x_center: i32,
+    #[nbt(rename = "zCenter")]
+    pub z_center: i32,
+    #[nbt(rename = "trackingPosition")]
+    pub tracking_position: Option<bool>,
+    #[nbt(rename = "unlimitedTracking")]
+    pub unlimited_tracking: Option<bool>,
+    pub locked: Option<bool>,
+    /// One color ID per pixel, row by row. See [`map_color`].
+    #[nbt(byte_array)]
+    pub colors: Vec<u8>,
+    pub banners: Option<Vec<CompoundTag>>,
+    pub frames: Option<Vec<CompoundTag>>,
+    #[nbt(extra)]
+    pub extra: CompoundTag,
+}
+
+impl MapData {
+    /// Reads a map file, detecting its compression.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        MapData::from_nbt(&read_nbt_file(path, &FileOptions::new())?.tag)
+    }
+
+    /// Writes a gzip compressed map file, as the game does.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_nbt_file(path, &self.to_nbt()?, "", &FileOptions::new())
+    }
+}
+
+impl MapItemData {
+    /// Renders the map as RGBA pixels, four bytes each, row by row.
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.colors
+            .iter()
+            .flat_map(|color| map_color(*color))
+            .collect()
+    }
+}
+
+/// Base colors of the vanilla map palette as of 1.17, indexed by `color / 4`. The first
+/// entry is transparent.
+const MAP_BASE_COLORS: [u32; 62] = [
+    0x000000, 0x7fb238, 0xf7e9a3, 0xc7c7c7, 0xff0000, 0xa0a0ff, 0xa7a7a7, 0x007c00, 0xffffff,
+    0xa4a8b8, 0x976d4d, 0x707070, 0x4040ff, 0x8f7748, 0xfffcf5, 0xd87f33, 0xb24cd8, 0x6699d8,
+    0xe5e533, 0x7fcc19, 0xf27fa5, 0x4c4c4c, 0x999999, 0x4c7f99, 0x7f3fb2, 0x334cb2, 0x664c33,
+    0x667f33, 0x993333, 0x191919, 0xfaee4d, 0x5cdbd5, 0x4a80ff, 0x00d93a, 0x815631, 0x700200,
+    0xd1b1a1, 0x9f5224, 0x95576c, 0x706c8a, 0xba8524, 0x677535, 0xa04d4e, 0x392923, 0x876b62,
+    0x575c5c, 0x7a4958, 0x4c3e5c, 0x4c3223, 0x4c522a, 0x8e3c2e, 0x251610, 0xbd3031, 0x943f61,
+    0x5c191d, 0x167e86, 0x3a8e8c, 0x562c3e, 0x14b485, 0x646464, 0xd8af93, 0x7fa796,
+];
+
+/// Brightness multipliers out of 255, indexed by `color % 4`.
+const MAP_SHADES: [u32; 4] = [180, 220, 255, 135];
+
+/// Converts a map color ID into an RGBA pixel. IDs outside the palette, and those of the
+/// first base color, are transparent.
+pub fn map_color(color: u8) -> [u8; 4] {
+    let Some(base) = MAP_BASE_COLORS
+        .get(color as usize / 4)
+        .filter(|_| color >= 4)
+    else {
+        return [0; 4];
+    };
+    let shade: u32 = MAP_SHADES[color as usize % 4];
+    let channel = |shift: u32| ((base >> shift & 0xff) * shade / 255) as u8;
+    [channel(16), channel(8), channel(0), 255]
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt;
+
+    #[test]
+    fn test_map_data() {
+        let mut colors: Vec<u8> = vec![0; MAP_SIZE * MAP_SIZE];
+        // Grass at full brightness, darkest water and an unassigned ID.
+        colors[1] = 6;
+        colors[2] = 4 * 12;
+        colors[3] = 4 * 63;
+        let tag: Tag = nbt!({
+            DataVersion: 3465,
+            data: {
+                scale: 0i8,
+                dimension: "minecraft:overworld",
+                xCenter: 64,
+                zCenter: 64,
+                locked: true,
+                colors: Tag::ByteArray(colors.iter().map(|color| *color as i8).collect()),
+                banners: [],
+            },
+        });
+        let map: MapData = MapData::from_nbt(&tag).unwrap();
+        assert_eq!(map.data.x_center, 64);
+        assert_eq!(map.to_nbt().unwrap(), tag);
+
+        let pixels: Vec<u8> = map.data.to_rgba();
+        assert_eq!(pixels.len(), MAP_SIZE * MAP_SIZE * 4);
+        assert_eq!(pixels[0..4], [0, 0, 0, 0]);
+        assert_eq!(pixels[4..8], [0x7f, 0xb2, 0x38, 255]);
+        assert_eq!(pixels[8..12], [45, 45, 180, 255]);
+        assert_eq!(pixels[12..16], [0, 0, 0, 0]);
+    }
+}
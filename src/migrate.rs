@@ -0,0 +1,470 @@
+use crate::{parse_path, CompoundTag, CompoundTagExt, PathSegment, Tag, TagID};
+use indexmap::IndexMap;
+use std::io::Result;
+use std::ops::Range;
+
+/// A single transformation applied to a document by a [`MigrationRule`].
+///
+/// Paths use the [`parse_path`](crate::parse_path) syntax relative to the root, such as
+/// `Data.Player` or `Level.Sections[0]`.
+#[derive(Debug, Clone)]
+pub enum MigrationAction {
+    /// Renames `from` to `to` inside the compound at `path`, keeping its position.
+    RenameKey {
+        path: String,
+        from: String,
+        to: String,
+    },
+    /// Moves the value at `from` to `to`, creating intermediate compounds as needed. If the
+    /// destination can't be set, the value stays where it was and the rule is reported as
+    /// failed.
+    MovePath { from: String, to: String },
+    /// Converts the value at `path` to another numeric or string tag type. Values that don't
+    /// fit the target type, including fractions converted to an integer type, fail the rule.
+    Retype { path: String, to: TagID },
+    /// Replaces the string at `path` using a lookup table, leaving unmapped values alone.
+    MapString {
+        path: String,
+        mapping: IndexMap<String, String>,
+    },
+}
+
+/// A migration action gated on the document's `DataVersion`.
+#[derive(Debug, Clone)]
+pub struct MigrationRule {
+    pub versions: Range<i32>,
+    pub action: MigrationAction,
+}
+
+impl MigrationRule {
+    pub fn new(versions: Range<i32>, action: MigrationAction) -> Self {
+        MigrationRule { versions, action }
+    }
+}
+
+/// What happened to a single rule during [`apply_migrations`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleOutcome {
+    Applied,
+    OutOfRange,
+    PathNotFound,
+    Failed(String),
+}
+
+/// The result of running a rule set, with one outcome per rule in order.
+#[derive(Debug, Clone)]
+pub struct MigrationReport {
+    pub data_version: Option<i32>,
+    pub outcomes: Vec<RuleOutcome>,
+}
+
+impl MigrationReport {
+    pub fn applied_count(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome| **outcome == RuleOutcome::Applied)
+            .count()
+    }
+}
+
+/// Applies every rule whose version range contains the document's `DataVersion`.
+///
+/// The version is read from a root-level `DataVersion` key, falling back to
+/// `Data.DataVersion` as used by level.dat. Documents without one skip every rule.
+pub fn apply_migrations(tag: &mut Tag, rules: &[MigrationRule]) -> MigrationReport {
+    let data_version: Option<i32> = data_version(tag);
+    let mut outcomes: Vec<RuleOutcome> = Vec::with_capacity(rules.len());
+    for rule in rules {
+        let outcome: RuleOutcome = match data_version {
+            Some(version) if rule.versions.contains(&version) => apply_action(tag, &rule.action),
+            _ => RuleOutcome::OutOfRange,
+        };
+        outcomes.push(outcome);
+    }
+    MigrationReport {
+        data_version,
+        outcomes,
+    }
+}
+
+/// A migration hook registered with a [`Migrator`].
+type MigrationHook = Box<dyn Fn(&mut Tag) -> Result<()> + Send + Sync>;
+
+/// A set of user-provided transforms keyed by the `DataVersion` ranges they upgrade.
+///
+/// Where [`MigrationRule`] covers simple declarative edits, hooks are arbitrary code, so
+/// game-specific fixes can be written with the crate's path utilities.
+///
+/// ```
+/// use rust_nbt::{nbt, Migrator, Tag};
+///
+/// let mut migrator: Migrator = Migrator::new();
+/// migrator.register(2580..2700, |tag: &mut Tag| {
+///     if let Some(Tag::String(id)) = tag.get_path_mut("id") {
+///         id.insert_str(0, "minecraft:");
+///     }
+///     Ok(())
+/// });
+/// let mut tag: Tag = nbt!({ DataVersion: 2586, id: "stone" });
+/// assert_eq!(migrator.migrate(&mut tag, 2586, 3465)?, 1);
+/// assert_eq!(tag, nbt!({ DataVersion: 3465, id: "minecraft:stone" }));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Default)]
+pub struct Migrator {
+    hooks: Vec<(Range<i32>, MigrationHook)>,
+}
+
+impl Migrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a hook that upgrades documents from versions within `versions`.
+    pub fn register<F>(&mut self, versions: Range<i32>, hook: F) -> &mut Self
+    where
+        F: Fn(&mut Tag) -> Result<()> + Send + Sync + 'static,
+    {
+        self.hooks.push((versions, Box::new(hook)));
+        self
+    }
+
+    /// Upgrades a document from version `from` to `to`, returning how many hooks ran.
+    ///
+    /// Every hook whose range overlaps `from..to` runs, ordered by the start of its range and
+    /// then by registration. The first error stops the migration. Afterwards the document's
+    /// `DataVersion`, at the root or in `Data` as for level.dat, is set to `to` if it has one.
+    pub fn migrate(&self, tag: &mut Tag, from: i32, to: i32) -> Result<usize> {
+        let mut hooks: Vec<&(Range<i32>, MigrationHook)> = self
+            .hooks
+            .iter()
+            .filter(|(versions, _)| versions.start < to && from < versions.end)
+            .collect();
+        hooks.sort_by_key(|(versions, _)| versions.start);
+        for (_, hook) in &hooks {
+            hook(tag)?;
+        }
+        if let Some(version) = data_version_mut(tag) {
+            *version = to;
+        }
+        Ok(hooks.len())
+    }
+}
+
+fn data_version_mut(tag: &mut Tag) -> Option<&mut i32> {
+    let Tag::Compound(root) = tag else {
+        return None;
+    };
+    let root: &mut CompoundTag = match root.contains_key("DataVersion") {
+        true => root,
+        false => match root.get_mut("Data") {
+            Some(Tag::Compound(data)) => data,
+            _ => return None,
+        },
+    };
+    match root.get_mut("DataVersion") {
+        Some(Tag::Int(version)) => Some(version),
+        _ => None,
+    }
+}
+
+fn data_version(tag: &Tag) -> Option<i32> {
+    let Tag::Compound(root) = tag else {
+        return None;
+    };
+    let version: Option<&Tag> = root.get("DataVersion").or_else(|| match root.get("Data") {
+        Some(Tag::Compound(data)) => data.get("DataVersion"),
+        _ => None,
+    });
+    match version {
+        Some(Tag::Int(version)) => Some(*version),
+        _ => None,
+    }
+}
+
+fn apply_action(tag: &mut Tag, action: &MigrationAction) -> RuleOutcome {
+    match action {
+        MigrationAction::RenameKey { path, from, to } => match tag.get_path_mut(path) {
+            Some(Tag::Compound(compound)) => match compound.rename_key(from, to) {
+                true => RuleOutcome::Applied,
+                false => RuleOutcome::PathNotFound,
+            },
+            _ => RuleOutcome::PathNotFound,
+        },
+        MigrationAction::MovePath { from, to } => move_path(tag, from, to),
+        MigrationAction::Retype { path, to } => {
+            let Some(value) = tag.get_path_mut(path) else {
+                return RuleOutcome::PathNotFound;
+            };
+            match retype(value, *to) {
+                Some(converted) => {
+                    *value = converted;
+                    RuleOutcome::Applied
+                }
+                None => RuleOutcome::Failed(format!("Cannot convert {:?} to {:?}", value.id(), to)),
+            }
+        }
+        MigrationAction::MapString { path, mapping } => {
+            let Some(value) = tag.get_path_mut(path) else {
+                return RuleOutcome::PathNotFound;
+            };
+            let Tag::String(string) = value else {
+                return RuleOutcome::Failed(format!("Expected String, found {:?}", value.id()));
+            };
+            if let Some(replacement) = mapping.get(string.as_str()) {
+                *string = replacement.clone();
+            }
+            RuleOutcome::Applied
+        }
+    }
+}
+
+/// Moves a value, putting it back in its original position if the destination is invalid.
+fn move_path(tag: &mut Tag, from: &str, to: &str) -> RuleOutcome {
+    let (from, to): (Vec<PathSegment>, Vec<PathSegment>) = match (parse_path(from), parse_path(to))
+    {
+        (Ok(from), Ok(to)) => (from, to),
+        (Err(error), _) | (_, Err(error)) => return RuleOutcome::Failed(error.to_string()),
+    };
+    let Some((last, parents)) = from.split_last() else {
+        return RuleOutcome::PathNotFound;
+    };
+    let (index, value): (usize, Tag) = match (tag.get_segments_mut(parents), last) {
+        (Some(Tag::Compound(compound)), PathSegment::Key(key)) => {
+            match compound.shift_remove_full(key) {
+                Some((index, _, value)) => (index, value),
+                None => return RuleOutcome::PathNotFound,
+            }
+        }
+        (Some(Tag::List(list)), PathSegment::Index(index)) if *index < list.len() => {
+            (*index, list.remove(*index))
+        }
+        _ => return RuleOutcome::PathNotFound,
+    };
+    // The destination is checked after removal, since removing can shift list indices.
+    if let Err(error) = tag.check_segments(&to, &value, true) {
+        match (tag.get_segments_mut(parents), last) {
+            (Some(Tag::Compound(compound)), PathSegment::Key(key)) => {
+                compound.shift_insert(index, key.clone(), value);
+            }
+            (Some(Tag::List(list)), _) => list.insert(index, value),
+            _ => {}
+        }
+        return RuleOutcome::Failed(error.to_string());
+    }
+    match tag.set_segments(&to, value, true) {
+        Ok(_) => RuleOutcome::Applied,
+        Err(error) => RuleOutcome::Failed(error.to_string()),
+    }
+}
+
+/// Converts between numeric tag types, and to or from strings holding numbers. Returns
+/// `None` when the value doesn't fit the target type.
+fn retype(value: &Tag, to: TagID) -> Option<Tag> {
+    match (value, to) {
+        (Tag::Byte(value), _) => retype_integer(i64::from(*value), to),
+        (Tag::Short(value), _) => retype_integer(i64::from(*value), to),
+        (Tag::Int(value), _) => retype_integer(i64::from(*value), to),
+        (Tag::Long(value), _) => retype_integer(*value, to),
+        (Tag::Float(value), TagID::String) => Some(Tag::String(value.to_string())),
+        (Tag::Float(value), _) => retype_float(f64::from(*value), to),
+        (Tag::Double(value), _) => retype_float(*value, to),
+        (Tag::String(value), TagID::String) => Some(Tag::String(value.clone())),
+        (Tag::String(value), _) => match value.trim().parse::<i64>() {
+            Ok(number) => retype_integer(number, to),
+            Err(_) => retype_float(value.trim().parse().ok()?, to),
+        },
+        _ => None,
+    }
+}
+
+fn retype_integer(number: i64, to: TagID) -> Option<Tag> {
+    match to {
+        TagID::Byte => i8::try_from(number).ok().map(Tag::Byte),
+        TagID::Short => i16::try_from(number).ok().map(Tag::Short),
+        TagID::Int => i32::try_from(number).ok().map(Tag::Int),
+        TagID::Long => Some(Tag::Long(number)),
+        TagID::Float => Some(Tag::Float(number as f32)),
+        TagID::Double => Some(Tag::Double(number as f64)),
+        TagID::String => Some(Tag::String(number.to_string())),
+        _ => None,
+    }
+}
+
+fn retype_float(number: f64, to: TagID) -> Option<Tag> {
+    match to {
+        TagID::Float if number.is_finite() && number.abs() > f64::from(f32::MAX) => None,
+        TagID::Float => Some(Tag::Float(number as f32)),
+        TagID::Double => Some(Tag::Double(number)),
+        TagID::String => Some(Tag::String(number.to_string())),
+        // Casting is exact here: the value is whole and within the range of a long.
+        _ if number.fract() == 0.0 && (-(2f64.powi(63))..2f64.powi(63)).contains(&number) => {
+            retype_integer(number as i64, to)
+        }
+        _ => None,
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt;
+    use std::io::{Error, ErrorKind};
+
+    #[test]
+    fn test_apply_migrations() {
+        let mut player: CompoundTag = IndexMap::new();
+        player.insert("Health".to_string(), Tag::Short(20));
+        player.insert(
+            "Dimension".to_string(),
+            Tag::String("overworld".to_string()),
+        );
+        player.insert("XpTotal".to_string(), Tag::Int(7));
+        player.insert("Score".to_string(), Tag::Int(300));
+        let mut root: CompoundTag = IndexMap::new();
+        root.insert("DataVersion".to_string(), Tag::Int(2580));
+        root.insert("Player".to_string(), Tag::Compound(player));
+        let mut tag: Tag = Tag::Compound(root);
+
+        let rules: Vec<MigrationRule> = vec![
+            MigrationRule::new(
+                0..2600,
+                MigrationAction::RenameKey {
+                    path: "Player".to_string(),
+                    from: "Health".to_string(),
+                    to: "health".to_string(),
+                },
+            ),
+            MigrationRule::new(
+                0..2600,
+                MigrationAction::Retype {
+                    path: "Player.health".to_string(),
+                    to: TagID::Float,
+                },
+            ),
+            MigrationRule::new(
+                0..2600,
+                MigrationAction::MapString {
+                    path: "Player.Dimension".to_string(),
+                    mapping: IndexMap::from([(
+                        "overworld".to_string(),
+                        "minecraft:overworld".to_string(),
+                    )]),
+                },
+            ),
+            MigrationRule::new(
+                0..2600,
+                MigrationAction::MovePath {
+                    from: "Player.XpTotal".to_string(),
+                    to: "Player.Stats.XpTotal".to_string(),
+                },
+            ),
+            MigrationRule::new(
+                2600..3000,
+                MigrationAction::RenameKey {
+                    path: "Player".to_string(),
+                    from: "health".to_string(),
+                    to: "Health".to_string(),
+                },
+            ),
+            MigrationRule::new(
+                0..2600,
+                MigrationAction::Retype {
+                    path: "Player.Missing".to_string(),
+                    to: TagID::Int,
+                },
+            ),
+            MigrationRule::new(
+                0..2600,
+                MigrationAction::Retype {
+                    path: "Player.Score".to_string(),
+                    to: TagID::Byte,
+                },
+            ),
+            MigrationRule::new(
+                0..2600,
+                MigrationAction::MovePath {
+                    from: "Player.Dimension".to_string(),
+                    to: "Player.health.Dimension".to_string(),
+                },
+            ),
+        ];
+        let report: MigrationReport = apply_migrations(&mut tag, &rules);
+
+        assert_eq!(report.data_version, Some(2580));
+        assert_eq!(report.applied_count(), 4);
+        assert_eq!(report.outcomes[4], RuleOutcome::OutOfRange);
+        assert_eq!(report.outcomes[5], RuleOutcome::PathNotFound);
+        assert!(matches!(report.outcomes[6], RuleOutcome::Failed(_)));
+        assert!(matches!(report.outcomes[7], RuleOutcome::Failed(_)));
+
+        let Tag::Compound(root) = &tag else {
+            panic!("Root is not a compound");
+        };
+        let Some(Tag::Compound(player)) = root.get("Player") else {
+            panic!("Player is not a compound");
+        };
+        assert_eq!(player.get_index_of("health"), Some(0));
+        assert_eq!(player.get_index_of("Dimension"), Some(1));
+        assert!(matches!(player.get("Score"), Some(Tag::Int(300))));
+        assert!(matches!(player.get("health"), Some(Tag::Float(value)) if *value == 20.0));
+        assert!(
+            matches!(player.get("Dimension"), Some(Tag::String(value)) if value == "minecraft:overworld")
+        );
+        assert!(matches!(
+            player.get("Stats"),
+            Some(Tag::Compound(stats)) if matches!(stats.get("XpTotal"), Some(Tag::Int(7)))
+        ));
+    }
+
+    #[test]
+    fn test_retype() {
+        assert_eq!(retype(&Tag::Int(-128), TagID::Byte), Some(Tag::Byte(-128)));
+        assert_eq!(retype(&Tag::Int(300), TagID::Byte), None);
+        assert_eq!(retype(&Tag::Long(i64::MAX), TagID::Int), None);
+        assert_eq!(
+            retype(&Tag::Double(20.0), TagID::Short),
+            Some(Tag::Short(20))
+        );
+        assert_eq!(retype(&Tag::Double(2.5), TagID::Int), None);
+        assert_eq!(retype(&Tag::Double(1e300), TagID::Float), None);
+        assert_eq!(
+            retype(&Tag::Long(i64::MAX), TagID::String),
+            Some(Tag::String(i64::MAX.to_string()))
+        );
+        assert_eq!(
+            retype(&Tag::String(" 42 ".to_string()), TagID::Byte),
+            Some(Tag::Byte(42))
+        );
+        assert_eq!(retype(&Tag::String("x".to_string()), TagID::Int), None);
+    }
+
+    #[test]
+    fn test_migrator() {
+        let mut migrator: Migrator = Migrator::new();
+        migrator
+            .register(3000..3100, |tag: &mut Tag| {
+                tag.set_path_create("Second", Tag::Int(2)).map(|_| ())
+            })
+            .register(2000..2500, |tag: &mut Tag| {
+                let first: Option<Tag> = tag.get_path("Second").cloned();
+                tag.set_path_create("First", first.unwrap_or(Tag::Int(1)))
+                    .map(|_| ())
+            })
+            .register(4000..5000, |_: &mut Tag| {
+                Err(Error::new(ErrorKind::InvalidData, "Too new"))
+            });
+
+        let mut tag: Tag = nbt!({ Data: { DataVersion: 2400 } });
+        assert_eq!(migrator.migrate(&mut tag, 2400, 3465).unwrap(), 2);
+        assert_eq!(
+            tag,
+            nbt!({ Data: { DataVersion: 3465 }, First: 1, Second: 2 })
+        );
+
+        let mut tag: Tag = nbt!({});
+        assert_eq!(migrator.migrate(&mut tag, 2500, 3000).unwrap(), 0);
+        assert!(migrator.migrate(&mut tag, 3465, 4100).is_err());
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{read_ref_with, LazyNbt, ReadOptions, TagRef};
+use memmap2::Mmap;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::Result;
+use std::path::Path;
+
+/// An uncompressed NBT file mapped into memory, so it can be parsed with the borrowed
+/// [`TagRef`] or [`LazyNbt`] readers without copying the file onto the heap.
+///
+/// Compressed files have to be decompressed into memory anyway, so read those with
+/// [`crate::read_nbt_file`] instead.
+#[derive(Debug)]
+pub struct MappedNbt {
+    mmap: Mmap,
+}
+
+impl MappedNbt {
+    /// Maps a file into memory.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this process or any other, while the
+    /// map is alive. Changes show through the borrowed tags, and reading past a truncated
+    /// end is undefined behavior. This is the same contract as [`memmap2::Mmap::map`].
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file: File = File::open(path)?;
+        // SAFETY: The caller guarantees the file isn't changed while the map is alive.
+        let mmap: Mmap = unsafe { Mmap::map(&file)? };
+        Ok(MappedNbt { mmap })
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.mmap
+    }
+
+    /// Parses the whole file, borrowing strings and arrays from the map.
+    pub fn read_ref(&self) -> Result<(Cow<'_, str>, TagRef<'_>)> {
+        self.read_ref_with(&ReadOptions::default())
+    }
+
+    /// Parses the whole file with the given limits.
+    pub fn read_ref_with(&self, options: &ReadOptions) -> Result<(Cow<'_, str>, TagRef<'_>)> {
+        read_ref_with(&self.mmap, options)
+    }
+
+    /// Indexes the file for on-demand decoding.
+    pub fn lazy(&self) -> Result<LazyNbt<'_>> {
+        self.lazy_with(&ReadOptions::default())
+    }
+
+    /// Indexes the file for on-demand decoding with the given limits.
+    pub fn lazy_with(&self, options: &ReadOptions) -> Result<LazyNbt<'_>> {
+        LazyNbt::new_with(&self.mmap, options)
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapped_nbt() {
+        // SAFETY: The fixture isn't modified while the tests run.
+        let file: MappedNbt = unsafe { MappedNbt::open("./test/hello_world.nbt") }.unwrap();
+        let (name, tag): (Cow<str>, TagRef) = file.read_ref().unwrap();
+        assert_eq!(name, "hello world");
+        assert_eq!(tag.to_tag(), crate::nbt!({ name: "Bananrama" }));
+        assert_eq!(
+            file.lazy().unwrap().get("name").unwrap().decode().unwrap(),
+            crate::nbt!("Bananrama")
+        );
+
+        let shallow: ReadOptions = ReadOptions::new().max_depth(0);
+        assert!(file.read_ref_with(&shallow).is_err());
+        assert!(file.lazy_with(&shallow).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use std::borrow::Cow;
+use std::io::{Error, ErrorKind, Result};
+
+/// Decodes Java's modified UTF-8, as used for NBT strings.
+///
+/// Modified UTF-8 encodes NUL as the two bytes `C0 80` and characters outside the Basic
+/// Multilingual Plane as a surrogate pair of three-byte sequences. Plain four-byte UTF-8 is
+/// also accepted, since some third-party tools write it.
+pub fn decode_mutf8(bytes: &[u8]) -> Result<String> {
+    if let Ok(value) = std::str::from_utf8(bytes) {
+        return Ok(value.to_string());
+    }
+    let invalid = || Error::new(ErrorKind::InvalidData, "Invalid modified UTF-8 string");
+    let units: Vec<u16> = decode_units(bytes, false).ok_or_else(invalid)?;
+    String::from_utf16(&units).map_err(|_| invalid())
+}
+
+/// Decodes modified UTF-8 like [`decode_mutf8`], but replaces each malformed byte and each
+/// unpaired surrogate with U+FFFD instead of failing.
+pub fn decode_mutf8_lossy(bytes: &[u8]) -> String {
+    if let Ok(value) = std::str::from_utf8(bytes) {
+        return value.to_string();
+    }
+    // Decoding never fails when invalid sequences are replaced.
+    String::from_utf16_lossy(&decode_units(bytes, true).unwrap_or_default())
+}
+
+/// Decodes modified UTF-8 into UTF-16 code units, which may hold unpaired surrogates. When
+/// `lossy`, a malformed sequence becomes U+FFFD and decoding resumes at the next byte;
+/// otherwise it returns `None`.
+fn decode_units(bytes: &[u8], lossy: bool) -> Option<Vec<u16>> {
+    let continuation = |index: usize| -> Option<u32> {
+        match bytes.get(index) {
+            Some(byte) if byte & 0xc0 == 0x80 => Some(u32::from(byte & 0x3f)),
+            _ => None,
+        }
+    };
+    let mut units: Vec<u16> = Vec::with_capacity(bytes.len());
+    let mut index: usize = 0;
+    while index < bytes.len() {
+        let lead: u32 = u32::from(bytes[index]);
+        let sequence: Option<(u32, usize)> = match lead {
+            0x00..=0x7f => Some((lead, 1)),
+            0xc0..=0xdf => continuation(index + 1).map(|c1| ((lead & 0x1f) << 6 | c1, 2)),
+            0xe0..=0xef => continuation(index + 1)
+                .zip(continuation(index + 2))
+                .map(|(c1, c2)| ((lead & 0x0f) << 12 | c1 << 6 | c2, 3)),
+            0xf0..=0xf7 => continuation(index + 1)
+                .zip(continuation(index + 2))
+                .zip(continuation(index + 3))
+                .map(|((c1, c2), c3)| ((lead & 0x07) << 18 | c1 << 12 | c2 << 6 | c3, 4))
+                .filter(|(code_point, _)| char::from_u32(*code_point).is_some()),
+            _ => None,
+        };
+        match sequence {
+            Some((code_point, 4)) => {
+                let c: char = char::from_u32(code_point).expect("Code point was just checked");
+                units.extend_from_slice(c.encode_utf16(&mut [0; 2]));
+                index += 4;
+            }
+            Some((unit, length)) => {
+                units.push(unit as u16);
+                index += length;
+            }
+            None if lossy => {
+                units.push(0xfffd);
+                index += 1;
+            }
+            None => return None,
+        }
+    }
+    Some(units)
+}
+
+/// Encodes a string as Java's modified UTF-8. Strings without NUL or supplementary
+/// characters are already valid modified UTF-8 and are borrowed as-is.
+pub fn encode_mutf8(value: &str) -> Cow<'_, [u8]> {
+    if !value.bytes().any(|byte| byte == 0 || byte >= 0xf0) {
+        return Cow::Borrowed(value.as_bytes());
+    }
+    let mut bytes: Vec<u8> = Vec::with_capacity(value.len() + 2);
+    for unit in value.encode_utf16() {
+        match unit {
+            0x01..=0x7f => bytes.push(unit as u8),
+            0x00 | 0x80..=0x7ff => {
+                bytes.push(0xc0 | (unit >> 6) as u8);
+                bytes.push(0x80 | (unit & 0x3f) as u8);
+            }
+            _ => {
+                bytes.push(0xe0 | (unit >> 12) as u8);
+                bytes.push(0x80 | (unit >> 6 & 0x3f) as u8);
+                bytes.push(0x80 | (unit & 0x3f) as u8);
+            }
+        }
+    }
+    Cow::Owned(bytes)
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mutf8_round_trip() {
+        for value in [
+            "plain ascii",
+            "caf\u{e9}",
+            "nul\0byte",
+            "emoji \u{1f600}!",
+            "",
+        ] {
+            let encoded: Cow<[u8]> = encode_mutf8(value);
+            assert_eq!(decode_mutf8(&encoded).unwrap(), value);
+        }
+        assert_eq!(&*encode_mutf8("a\0b"), &[0x61, 0xc0, 0x80, 0x62]);
+        assert_eq!(
+            &*encode_mutf8("\u{1f600}"),
+            &[0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80]
+        );
+        assert_eq!(decode_mutf8("\u{1f600}".as_bytes()).unwrap(), "\u{1f600}");
+        assert!(decode_mutf8(&[0xed, 0xa0, 0xbd]).is_err());
+        assert!(decode_mutf8(&[0xc3]).is_err());
+
+        // NUL and a surrogate pair decode as usual, while a bad byte and a lone surrogate
+        // are each replaced.
+        let mixed: [u8; 12] = [
+            0xc0, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80, 0xff, 0xed, 0xa0, 0xbd,
+        ];
+        assert_eq!(decode_mutf8_lossy(&mixed), "\0\u{1f600}\u{fffd}\u{fffd}");
+        assert_eq!(decode_mutf8_lossy(b"plain"), "plain");
+    }
+}
@@ -0,0 +1,268 @@
+use crate::{format_path, parse_path, read, write, CompoundTag, PathSegment, Tag};
+use std::io::{Error, ErrorKind, Result};
+
+/// A single edit in an [`NbtPatch`].
+#[derive(Debug, Clone)]
+pub enum PatchOp {
+    /// Sets the value at a path, creating missing parent compounds.
+    Set { path: Vec<PathSegment>, value: Tag },
+    /// Removes the value at a path, which must exist.
+    Remove { path: Vec<PathSegment> },
+}
+
+/// An ordered list of edits that can be replayed against another document.
+#[derive(Debug, Clone, Default)]
+pub struct NbtPatch {
+    pub ops: Vec<PatchOp>,
+}
+
+impl NbtPatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a set operation, parsing `path` with [`parse_path`].
+    pub fn set(&mut self, path: &str, value: Tag) -> Result<()> {
+        self.ops.push(PatchOp::Set {
+            path: parse_path(path)?,
+            value,
+        });
+        Ok(())
+    }
+
+    /// Appends a remove operation, parsing `path` with [`parse_path`].
+    pub fn remove(&mut self, path: &str) -> Result<()> {
+        self.ops.push(PatchOp::Remove {
+            path: parse_path(path)?,
+        });
+        Ok(())
+    }
+
+    /// Represents the patch as a compound: `{ops: [{op: "set", path: "...", value: ...}]}`.
+    pub fn to_tag(&self) -> Tag {
+        let ops: Vec<Tag> = self
+            .ops
+            .iter()
+            .map(|op| {
+                let mut entry: CompoundTag = CompoundTag::new();
+                match op {
+                    PatchOp::Set { path, value } => {
+                        entry.insert("op".to_string(), Tag::from("set"));
+                        entry.insert("path".to_string(), Tag::String(format_path(path)));
+                        entry.insert("value".to_string(), value.clone());
+                    }
+                    PatchOp::Remove { path } => {
+                        entry.insert("op".to_string(), Tag::from("remove"));
+                        entry.insert("path".to_string(), Tag::String(format_path(path)));
+                    }
+                }
+                Tag::Compound(entry)
+            })
+            .collect();
+        Tag::Compound(CompoundTag::from([("ops".to_string(), Tag::List(ops))]))
+    }
+
+    /// Parses a patch from the compound layout produced by [`NbtPatch::to_tag`].
+    pub fn from_tag(tag: &Tag) -> Result<Self> {
+        let Some(Tag::List(entries)) = tag.get_path("ops") else {
+            return Err(invalid_patch("Missing ops list"));
+        };
+        let mut ops: Vec<PatchOp> = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let Some(Tag::String(path)) = entry.get_path("path") else {
+                return Err(invalid_patch("Operation is missing its path"));
+            };
+            let path: Vec<PathSegment> = parse_path(path)?;
+            let op: PatchOp = match entry.get_path("op") {
+                Some(Tag::String(op)) if op == "set" => PatchOp::Set {
+                    path,
+                    value: entry
+                        .get_path("value")
+                        .cloned()
+                        .ok_or_else(|| invalid_patch("Set operation is missing its value"))?,
+                },
+                Some(Tag::String(op)) if op == "remove" => PatchOp::Remove { path },
+                _ => return Err(invalid_patch("Unknown operation")),
+            };
+            ops.push(op);
+        }
+        Ok(NbtPatch { ops })
+    }
+
+    /// Builds the patch that turns `old` into `new`, as found by [`Tag::diff`].
+    pub fn from_diff(old: &Tag, new: &Tag) -> Self {
+        let ops: Vec<PatchOp> = old
+            .diff(new)
+            .into_iter()
+            .map(|change| match change.new {
+                Some(value) => PatchOp::Set {
+                    path: change.path,
+                    value,
+                },
+                None => PatchOp::Remove { path: change.path },
+            })
+            .collect();
+        NbtPatch { ops }
+    }
+
+    /// Encodes the patch as an uncompressed NBT document.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        write(&self.to_tag(), "NbtPatch")
+    }
+
+    /// Decodes a patch written by [`NbtPatch::to_bytes`].
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        Self::from_tag(&read(data)?.tag)
+    }
+}
+
+fn invalid_patch(reason: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("Invalid patch: {}", reason))
+}
+
+/// A difference found by [`Tag::diff`]. `old` is `None` for added values and `new` is
+/// `None` for removed ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagChange {
+    pub path: Vec<PathSegment>,
+    pub old: Option<Tag>,
+    pub new: Option<Tag>,
+}
+
+impl Tag {
+    /// Structurally compares two tags, returning the changed paths in document order.
+    ///
+    /// Compounds are compared key by key. Lists of the same length and element type are
+    /// compared element by element; any other list change replaces the whole list.
+    pub fn diff(&self, other: &Tag) -> Vec<TagChange> {
+        let mut changes: Vec<TagChange> = Vec::new();
+        diff_into(&mut Vec::new(), self, other, &mut changes);
+        changes
+    }
+
+    /// Applies every operation in order. If any operation fails, the tag is left unchanged.
+    pub fn apply_patch(&mut self, patch: &NbtPatch) -> Result<()> {
+        let mut patched: Tag = self.clone();
+        for op in &patch.ops {
+            match op {
+                PatchOp::Set { path, value } => {
+                    patched.set_segments(path, value.clone(), true)?;
+                }
+                PatchOp::Remove { path } => {
+                    if patched.remove_segments(path).is_none() {
+                        return Err(Error::new(
+                            ErrorKind::NotFound,
+                            format!("Cannot remove {}: Path not found", format_path(path)),
+                        ));
+                    }
+                }
+            }
+        }
+        *self = patched;
+        Ok(())
+    }
+}
+
+fn diff_into(path: &mut Vec<PathSegment>, old: &Tag, new: &Tag, changes: &mut Vec<TagChange>) {
+    match (old, new) {
+        (Tag::Compound(old), Tag::Compound(new)) => {
+            for (key, old_entry) in old {
+                path.push(PathSegment::Key(key.clone()));
+                match new.get(key) {
+                    Some(new_entry) => diff_into(path, old_entry, new_entry, changes),
+                    None => changes.push(TagChange {
+                        path: path.clone(),
+                        old: Some(old_entry.clone()),
+                        new: None,
+                    }),
+                }
+                path.pop();
+            }
+            for (key, new_entry) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+                path.push(PathSegment::Key(key.clone()));
+                changes.push(TagChange {
+                    path: path.clone(),
+                    old: None,
+                    new: Some(new_entry.clone()),
+                });
+                path.pop();
+            }
+        }
+        (Tag::List(old_list), Tag::List(new_list))
+            if old_list.len() == new_list.len()
+                && old_list.first().map(Tag::id) == new_list.first().map(Tag::id) =>
+        {
+            for (index, (old_entry, new_entry)) in old_list.iter().zip(new_list).enumerate() {
+                path.push(PathSegment::Index(index));
+                diff_into(path, old_entry, new_entry, changes);
+                path.pop();
+            }
+        }
+        // Floats compare by their bits, so a NaN that is stored unchanged isn't reported.
+        (old, new) if old != new => changes.push(TagChange {
+            path: path.clone(),
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt;
+
+    #[test]
+    fn test_apply_patch() {
+        let mut patch: NbtPatch = NbtPatch::new();
+        patch
+            .set("Data.GameRules.doDaylightCycle", nbt!("false"))
+            .unwrap();
+        patch.set("Data.\"odd.key\"", nbt!([I; 1, 2])).unwrap();
+        patch.remove("Data.Time").unwrap();
+
+        let decoded: NbtPatch = NbtPatch::from_bytes(&patch.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.ops.len(), 3);
+
+        let mut tag: Tag = nbt!({ Data: { Time: 5i64 } });
+        tag.apply_patch(&decoded).unwrap();
+        assert_eq!(
+            tag.to_snbt(),
+            r#"{Data:{GameRules:{doDaylightCycle:"false"},odd.key:[I;1,2]}}"#
+        );
+
+        let error: Error = tag.apply_patch(&decoded).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+        assert_eq!(
+            tag.to_snbt(),
+            r#"{Data:{GameRules:{doDaylightCycle:"false"},odd.key:[I;1,2]}}"#
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let old: Tag = nbt!({ Time: 5i64, Pos: [1.0, 2.0], Tags: ["a"], Gone: 1 });
+        let new: Tag = nbt!({ Time: 5i64, Pos: [1.0, 3.0], Tags: ["a", "b"], Added: 2 });
+        let changes: Vec<TagChange> = old.diff(&new);
+        let paths: Vec<String> = changes
+            .iter()
+            .map(|change| format_path(&change.path))
+            .collect();
+        assert_eq!(paths, ["Pos[1]", "Tags", "Gone", "Added"]);
+        assert_eq!(changes[0].old, Some(Tag::Double(2.0)));
+        assert_eq!(changes[2].new, None);
+
+        let mut patched: Tag = old.clone();
+        patched
+            .apply_patch(&NbtPatch::from_diff(&old, &new))
+            .unwrap();
+        assert_eq!(patched, new);
+        assert!(new.diff(&new).is_empty());
+
+        let nan: Tag = nbt!({ f: f32::NAN, d: [f64::NAN] });
+        assert!(nan.diff(&nan.clone()).is_empty());
+        assert_eq!(nbt!(0.0).diff(&nbt!(-0.0)).len(), 1);
+    }
+}
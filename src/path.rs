@@ -0,0 +1,385 @@
+use crate::{CompoundTag, Tag};
+use std::io::{Error, ErrorKind, Result};
+
+/// One step of a tag path: a compound key or a list index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Parses a path such as `Level.Sections[3].Palette[0].Name` into its segments.
+///
+/// Keys are separated by dots and list indices go in brackets. Keys containing dots,
+/// brackets or other special characters can be double-quoted (`Data."my.key"`), and a
+/// backslash escapes the next character in either form.
+pub fn parse_path(path: &str) -> Result<Vec<PathSegment>> {
+    let mut segments: Vec<PathSegment> = Vec::new();
+    let mut chars: std::iter::Peekable<std::str::Chars<'_>> = path.chars().peekable();
+    let mut expect_key: bool = true;
+    while let Some(&c) = chars.peek() {
+        match c {
+            '[' => {
+                chars.next();
+                let mut digits: String = String::new();
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some(c) => digits.push(c),
+                        None => return Err(invalid_path(path, "Unterminated list index")),
+                    }
+                }
+                let index: usize = digits
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid_path(path, "List index must be a non-negative integer"))?;
+                segments.push(PathSegment::Index(index));
+                expect_key = false;
+            }
+            '.' if !expect_key => {
+                chars.next();
+                expect_key = true;
+            }
+            '"' if expect_key => {
+                chars.next();
+                let mut key: String = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => key.extend(chars.next()),
+                        Some(c) => key.push(c),
+                        None => return Err(invalid_path(path, "Unterminated quoted key")),
+                    }
+                }
+                segments.push(PathSegment::Key(key));
+                expect_key = false;
+            }
+            _ if expect_key => {
+                let mut key: String = String::new();
+                while let Some(&c) = chars.peek() {
+                    match c {
+                        '.' | '[' => break,
+                        '\\' => {
+                            chars.next();
+                            key.extend(chars.next());
+                        }
+                        c => {
+                            chars.next();
+                            key.push(c);
+                        }
+                    }
+                }
+                segments.push(PathSegment::Key(key));
+                expect_key = false;
+            }
+            _ => return Err(invalid_path(path, "Expected '.' or '[' after segment")),
+        }
+    }
+    if expect_key && !segments.is_empty() {
+        return Err(invalid_path(path, "Path ends with '.'"));
+    }
+    Ok(segments)
+}
+
+fn invalid_path(path: &str, reason: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("Invalid path {:?}: {}", path, reason),
+    )
+}
+
+impl Tag {
+    /// Looks up a nested tag by path, returning `None` if the path is malformed or any
+    /// segment is missing. See [`parse_path`] for the syntax.
+    pub fn get_path(&self, path: &str) -> Option<&Tag> {
+        self.get_segments(&parse_path(path).ok()?)
+    }
+
+    /// Mutable counterpart of [`Tag::get_path`].
+    pub fn get_path_mut(&mut self, path: &str) -> Option<&mut Tag> {
+        self.get_segments_mut(&parse_path(path).ok()?)
+    }
+
+    /// Looks up a nested tag by pre-parsed segments.
+    pub fn get_segments(&self, segments: &[PathSegment]) -> Option<&Tag> {
+        let mut current: &Tag = self;
+        for segment in segments {
+            current = match (current, segment) {
+                (Tag::Compound(compound), PathSegment::Key(key)) => compound.get(key)?,
+                (Tag::List(list), PathSegment::Index(index)) => list.get(*index)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    /// Mutable counterpart of [`Tag::get_segments`].
+    pub fn get_segments_mut(&mut self, segments: &[PathSegment]) -> Option<&mut Tag> {
+        let mut current: &mut Tag = self;
+        for segment in segments {
+            current = match (current, segment) {
+                (Tag::Compound(compound), PathSegment::Key(key)) => compound.get_mut(key)?,
+                (Tag::List(list), PathSegment::Index(index)) => list.get_mut(*index)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    /// Replaces the tag at `path`, returning the previous value if there was one. The parent
+    /// must already exist; new keys are added to compounds, while list indices must be in
+    /// bounds and the new value must match the list's element type.
+    pub fn set_path(&mut self, path: &str, value: Tag) -> Result<Option<Tag>> {
+        self.set_segments(&parse_path(path)?, value, false)
+    }
+
+    /// Like [`Tag::set_path`], but creates missing intermediate compounds along the way.
+    pub fn set_path_create(&mut self, path: &str, value: Tag) -> Result<Option<Tag>> {
+        self.set_segments(&parse_path(path)?, value, true)
+    }
+
+    /// Removes and returns the tag at `path`. Compound entries keep their neighbours' order.
+    pub fn remove_path(&mut self, path: &str) -> Option<Tag> {
+        self.remove_segments(&parse_path(path).ok()?)
+    }
+
+    /// Sets the tag at pre-parsed segments, optionally creating missing compounds. The whole
+    /// path is checked first, so a failed call leaves the tag unchanged.
+    pub fn set_segments(
+        &mut self,
+        segments: &[PathSegment],
+        value: Tag,
+        create_missing: bool,
+    ) -> Result<Option<Tag>> {
+        self.check_segments(segments, &value, create_missing)?;
+        let Some((last, parents)) = segments.split_last() else {
+            return Ok(Some(std::mem::replace(self, value)));
+        };
+        let mut parent: &mut Tag = self;
+        for segment in parents {
+            parent = match (parent, segment) {
+                (Tag::Compound(compound), PathSegment::Key(key)) => {
+                    if create_missing && !compound.contains_key(key) {
+                        compound.insert(key.clone(), Tag::Compound(CompoundTag::new()));
+                    }
+                    compound
+                        .get_mut(key)
+                        .ok_or_else(|| path_error(segments, "Missing parent"))?
+                }
+                (Tag::List(list), PathSegment::Index(index)) => list
+                    .get_mut(*index)
+                    .ok_or_else(|| path_error(segments, "List index out of bounds"))?,
+                _ => return Err(path_error(segments, "Segment doesn't match tag type")),
+            };
+        }
+        match (parent, last) {
+            (Tag::Compound(compound), PathSegment::Key(key)) => {
+                Ok(compound.insert(key.clone(), value))
+            }
+            (Tag::List(list), PathSegment::Index(index)) => {
+                if list
+                    .iter()
+                    .enumerate()
+                    .any(|(i, entry)| i != *index && entry.id() != value.id())
+                {
+                    return Err(path_error(
+                        segments,
+                        "Value doesn't match list element type",
+                    ));
+                }
+                let entry: &mut Tag = list
+                    .get_mut(*index)
+                    .ok_or_else(|| path_error(segments, "List index out of bounds"))?;
+                Ok(Some(std::mem::replace(entry, value)))
+            }
+            _ => Err(path_error(segments, "Segment doesn't match tag type")),
+        }
+    }
+
+    /// Checks that [`Tag::set_segments`] would succeed with `value`, without changing anything.
+    pub(crate) fn check_segments(
+        &self,
+        segments: &[PathSegment],
+        value: &Tag,
+        create_missing: bool,
+    ) -> Result<()> {
+        let Some((last, parents)) = segments.split_last() else {
+            return Ok(());
+        };
+        let mut parent: &Tag = self;
+        for (depth, segment) in parents.iter().enumerate() {
+            parent = match (parent, segment) {
+                (Tag::Compound(compound), PathSegment::Key(key)) => match compound.get(key) {
+                    Some(child) => child,
+                    // Only keys can follow a compound that is about to be created.
+                    None if create_missing => {
+                        return match segments[depth + 1..]
+                            .iter()
+                            .all(|segment| matches!(segment, PathSegment::Key(_)))
+                        {
+                            true => Ok(()),
+                            false => Err(path_error(segments, "Segment doesn't match tag type")),
+                        };
+                    }
+                    None => return Err(path_error(segments, "Missing parent")),
+                },
+                (Tag::List(list), PathSegment::Index(index)) => list
+                    .get(*index)
+                    .ok_or_else(|| path_error(segments, "List index out of bounds"))?,
+                _ => return Err(path_error(segments, "Segment doesn't match tag type")),
+            };
+        }
+        match (parent, last) {
+            (Tag::Compound(_), PathSegment::Key(_)) => Ok(()),
+            (Tag::List(list), PathSegment::Index(index)) => {
+                if *index >= list.len() {
+                    return Err(path_error(segments, "List index out of bounds"));
+                }
+                if list
+                    .iter()
+                    .enumerate()
+                    .any(|(i, entry)| i != *index && entry.id() != value.id())
+                {
+                    return Err(path_error(
+                        segments,
+                        "Value doesn't match list element type",
+                    ));
+                }
+                Ok(())
+            }
+            _ => Err(path_error(segments, "Segment doesn't match tag type")),
+        }
+    }
+
+    /// Removes the tag at pre-parsed segments.
+    pub fn remove_segments(&mut self, segments: &[PathSegment]) -> Option<Tag> {
+        let (last, parents) = segments.split_last()?;
+        match (self.get_segments_mut(parents)?, last) {
+            (Tag::Compound(compound), PathSegment::Key(key)) => compound.shift_remove(key),
+            (Tag::List(list), PathSegment::Index(index)) if *index < list.len() => {
+                Some(list.remove(*index))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn path_error(segments: &[PathSegment], reason: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("Cannot set {}: {}", format_path(segments), reason),
+    )
+}
+
+/// Formats segments back into path syntax, quoting keys that need it.
+pub fn format_path(segments: &[PathSegment]) -> String {
+    let mut path: String = String::new();
+    for segment in segments {
+        match segment {
+            PathSegment::Key(key) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                if !key.is_empty() && !key.contains(['.', '[', ']', '"', '\\']) {
+                    path.push_str(key);
+                } else {
+                    path.push('"');
+                    for c in key.chars() {
+                        if matches!(c, '"' | '\\') {
+                            path.push('\\');
+                        }
+                        path.push(c);
+                    }
+                    path.push('"');
+                }
+            }
+            PathSegment::Index(index) => path.push_str(&format!("[{}]", index)),
+        }
+    }
+    path
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt;
+
+    #[test]
+    fn test_get_path() {
+        let tag: Tag = nbt!({
+            Level: {
+                Sections: [
+                    { Palette: [] },
+                    { Palette: [{ Name: "minecraft:stone" }] },
+                ],
+                "odd.key[0]": 1,
+            },
+        });
+        assert!(matches!(
+            tag.get_path("Level.Sections[1].Palette[0].Name"),
+            Some(Tag::String(name)) if name == "minecraft:stone"
+        ));
+        assert!(matches!(
+            tag.get_path(r#"Level."odd.key[0]""#),
+            Some(Tag::Int(1))
+        ));
+        assert!(matches!(
+            tag.get_path(r"Level.odd\.key\[0]"),
+            Some(Tag::Int(1))
+        ));
+        assert!(matches!(tag.get_path(""), Some(Tag::Compound(_))));
+        assert!(tag.get_path("Level.Sections[2]").is_none());
+        assert!(tag.get_path("Level.Sections.Palette").is_none());
+        assert!(tag.get_path("Level.").is_none());
+
+        assert_eq!(
+            parse_path(r#"a[1][2]."b.c""#).unwrap(),
+            [
+                PathSegment::Key("a".to_string()),
+                PathSegment::Index(1),
+                PathSegment::Index(2),
+                PathSegment::Key("b.c".to_string()),
+            ]
+        );
+        assert!(parse_path("a[x]").is_err());
+        assert!(parse_path("a[0]b").is_err());
+        assert!(parse_path("\"a").is_err());
+        assert!(parse_path("a[1").is_err());
+    }
+
+    #[test]
+    fn test_set_and_remove_path() {
+        let mut tag: Tag = nbt!({
+            Data: { GameRules: { doDaylightCycle: "true" }, Time: 5i64 },
+            Pos: [1.0, 2.0],
+        });
+        let previous: Option<Tag> = tag
+            .set_path("Data.GameRules.doDaylightCycle", nbt!("false"))
+            .unwrap();
+        assert!(matches!(previous, Some(Tag::String(value)) if value == "true"));
+        assert!(tag.set_path("Data.Missing.Key", nbt!(1)).is_err());
+        assert!(tag
+            .set_path_create("Data.Missing.Key", nbt!(1))
+            .unwrap()
+            .is_none());
+        let unchanged: Tag = tag.clone();
+        assert!(tag.set_path_create("New.Child[0]", nbt!(1)).is_err());
+        assert!(tag.set_path_create("New.Child.Pos[0]", nbt!(1)).is_err());
+        assert_eq!(tag, unchanged);
+        assert!(tag.set_path("Pos[1]", nbt!(3.0)).is_ok());
+        assert!(tag.set_path("Pos[0]", nbt!(3)).is_err());
+        assert!(tag.set_path("Pos[2]", nbt!(3.0)).is_err());
+
+        assert!(matches!(tag.remove_path("Data.Time"), Some(Tag::Long(5))));
+        assert!(matches!(tag.remove_path("Pos[0]"), Some(Tag::Double(_))));
+        assert!(tag.remove_path("Pos[5]").is_none());
+        assert_eq!(
+            tag.to_snbt(),
+            r#"{Data:{GameRules:{doDaylightCycle:"false"},Missing:{Key:1}},Pos:[3d]}"#
+        );
+
+        let segments: Vec<PathSegment> = parse_path(r#"a."b.c"[2].d"#).unwrap();
+        assert_eq!(format_path(&segments), r#"a."b.c"[2].d"#);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{
+    read_nbt_file, write_nbt_file, CompoundTag, FileOptions, NbtDeserialize, NbtSerialize, Tag,
+};
+use std::io::Result;
+use std::path::Path;
+
+/// A Java Edition player file, as stored in `playerdata/<uuid>.dat` or as the `Player` compound
+/// of a singleplayer `level.dat`.
+///
+/// Keys without their own field are kept in `extra`, so saving doesn't lose data.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct PlayerData {
+    #[nbt(rename = "DataVersion")]
+    pub data_version: Option<i32>,
+    #[nbt(rename = "UUID", int_array)]
+    pub uuid: Option<Vec<i32>>,
+    /// Position as x, y and z.
+    #[nbt(rename = "Pos")]
+    pub pos: Vec<f64>,
+    #[nbt(rename = "Motion")]
+    pub motion: Option<Vec<f64>>,
+    /// Yaw and pitch in degrees.
+    #[nbt(rename = "Rotation")]
+    pub rotation: Vec<f32>,
+    /// A dimension ID such as `"minecraft:overworld"` since 1.16, an `Int` before.
+    #[nbt(rename = "Dimension")]
+    pub dimension: Option<Tag>,
+    #[nbt(rename = "Health")]
+    pub health: Option<f32>,
+    #[nbt(rename = "foodLevel")]
+    pub food_level: Option<i32>,
+    #[nbt(rename = "XpLevel")]
+    pub xp_level: Option<i32>,
+    #[nbt(rename = "XpTotal")]
+    pub xp_total: Option<i32>,
+    #[nbt(rename = "playerGameType")]
+    pub game_type: Option<i32>,
+    #[nbt(rename = "SelectedItemSlot")]
+    pub selected_item_slot: Option<i32>,
+    #[nbt(rename = "Inventory")]
+    pub inventory: Vec<ItemStack>,
+    #[nbt(rename = "EnderItems")]
+    pub ender_items: Option<Vec<ItemStack>>,
+    pub abilities: Option<Abilities>,
+    #[nbt(extra)]
+    pub extra: CompoundTag,
+}
+
+/// What a player is allowed to do, mostly decided by their game mode.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct Abilities {
+    pub flying: bool,
+    #[nbt(rename = "mayfly")]
+    pub may_fly: bool,
+    #[nbt(rename = "instabuild")]
+    pub instabuild: bool,
+    pub invulnerable: bool,
+    #[nbt(rename = "mayBuild")]
+    pub may_build: bool,
+    #[nbt(rename = "flySpeed")]
+    pub fly_speed: f32,
+    #[nbt(rename = "walkSpeed")]
+    pub walk_speed: f32,
+    #[nbt(extra)]
+    pub extra: CompoundTag,
+}
+
+/// A stack of items in an inventory, container or item entity.
+///
+/// Since 1.20.5 items store a `count` int and a `components` compound; older versions use
+/// a `Count` byte and a `tag` compound. Both layouts are read and written as they are.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct ItemStack {
+    pub id: String,
+    /// The inventory slot, when the stack is part of one.
+    #[nbt(rename = "Slot")]
+    pub slot: Option<i8>,
+    pub count: Option<i32>,
+    #[nbt(rename = "Count")]
+    pub legacy_count: Option<i8>,
+    pub components: Option<CompoundTag>,
+    pub tag: Option<CompoundTag>,
+    #[nbt(extra)]
+    pub extra: CompoundTag,
+}
+
+impl ItemStack {
+    /// The number of items in the stack, from whichever count the stack has. A missing count
+    /// means one item.
+    pub fn amount(&self) -> i32 {
+        self.count.or(self.legacy_count.map(i32::from)).unwrap_or(1)
+    }
+}
+
+impl PlayerData {
+    /// Reads a player file, detecting its compression.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        PlayerData::from_nbt(&read_nbt_file(path, &FileOptions::new())?.tag)
+    }
+
+    /// Writes a gzip compressed player file, as the game does.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_nbt_file(path, &self.to_nbt()?, "", &FileOptions::new())
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt;
+
+    #[test]
+    fn test_player_data() {
+        let tag: Tag = nbt!({
+            DataVersion: 3465,
+            UUID: [I; 1, 2, 3, 4],
+            Pos: [0.5, 64.0, -3.5],
+            Rotation: [90.0f32, 0.0f32],
+            Dimension: "minecraft:overworld",
+            Inventory: [
+                { Slot: 0i8, id: "minecraft:stone", Count: 64i8 },
+                { Slot: 1i8, id: "minecraft:diamond_sword", count: 1, components: {} },
+            ],
+            abilities: {
+                flying: false,
+                mayfly: true,
+                instabuild: true,
+                invulnerable: true,
+                mayBuild: true,
+                flySpeed: 0.05f32,
+                walkSpeed: 0.1f32,
+            },
+            recipeBook: {},
+        });
+        let player: PlayerData = PlayerData::from_nbt(&tag).unwrap();
+        assert_eq!(player.uuid, Some(vec![1, 2, 3, 4]));
+        assert_eq!(player.inventory[0].amount(), 64);
+        assert_eq!(player.inventory[1].amount(), 1);
+        assert!(player.abilities.as_ref().unwrap().may_fly);
+        assert!(player.extra.contains_key("recipeBook"));
+        assert_eq!(player.to_nbt().unwrap(), tag);
+
+        // Player files are gzip compressed
+        #[cfg(feature = "compression")]
+        {
+            let path: std::path::PathBuf = std::env::temp_dir().join("rust_nbt_test_player.dat");
+            player.save(&path).unwrap();
+            assert_eq!(PlayerData::load(&path).unwrap(), player);
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+}
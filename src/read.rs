@@ -1,24 +1,537 @@
 use crate::{
-    ByteArrayTag, ByteTag, CompoundTag, DoubleTag, FloatTag, IntArrayTag, IntTag, ListTag,
-    LongArrayTag, LongTag, ShortTag, StringTag, Tag, TagID,
+    decode_mutf8, decode_mutf8_lossy, decompress_reader, format_path, ByteArrayTag, ByteTag,
+    CompoundTag, CompressionFormat, DoubleTag, FloatTag, IntArrayTag, IntTag, ListTag,
+    LongArrayTag, LongTag, NamedTag, PathSegment, RawTag, ShortTag, Tag, TagID,
 };
 use indexmap::IndexMap;
-use std::io::{Cursor, Read, Result};
-
-/// Reads an NBT file from a byte vector and returns its root compound tag.
-pub fn read(data: &[u8]) -> Result<Tag> {
-    let mut cursor: Cursor<&[u8]> = Cursor::new(&data);
-    let root_tag_id: TagID = read_tag_id(&mut cursor)?;
-    let name_length: usize = read_unsigned_short(&mut cursor)? as usize;
-    let mut name_buffer: Vec<u8> = vec![0; name_length];
-    cursor.read_exact(&mut name_buffer)?;
-    let root_name: String = String::from_utf8(name_buffer).unwrap();
-    println!("{:?}", root_name);
-    read_tag(&mut cursor, &root_tag_id)
-}
-
-/// Reads a single NBT tag from the given reader.
-fn read_tag<R: Read>(reader: &mut R, tag_id: &TagID) -> Result<Tag> {
+use std::fmt;
+use std::io::{BufReader, Cursor, Error, ErrorKind, Read, Result};
+use std::sync::Arc;
+
+/// What to do with string payloads that aren't valid modified UTF-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InvalidStringPolicy {
+    /// Fail the read with an `InvalidData` error.
+    #[default]
+    Error,
+    /// Decode as modified UTF-8, replacing each malformed byte and unpaired surrogate with
+    /// U+FFFD.
+    Lossy,
+    /// Keep the original bytes in a [`Tag::RawEncoded`] string so they're written back
+    /// unchanged. Compound keys and the root name can't hold raw bytes, so they're decoded
+    /// lossily instead.
+    Preserve,
+}
+
+/// Whether the root tag carries a name. Java network NBT (1.20.2+) omits it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RootName {
+    #[default]
+    Named,
+    Omitted,
+}
+
+/// Where a read failed. Errors from the root readers such as [`read_with`] carry this as
+/// their inner error, so it can be recovered with `error.get_ref()` and `downcast_ref`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadErrorContext {
+    pub message: String,
+    /// Number of bytes consumed when the error was detected.
+    pub offset: u64,
+    /// Path from the root tag to the value being read.
+    pub path: Vec<PathSegment>,
+}
+
+impl fmt::Display for ReadErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset 0x{:X}", self.message, self.offset)?;
+        if !self.path.is_empty() {
+            write!(f, " in {}", format_path(&self.path))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ReadErrorContext {}
+
+/// A callback reporting how far a read has got, set with [`ReadOptions::progress`].
+#[derive(Clone)]
+pub struct ReadProgress {
+    interval: u64,
+    callback: Arc<dyn Fn(u64) -> bool + Send + Sync>,
+}
+
+impl fmt::Debug for ReadProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadProgress")
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Counts the bytes read through it, for error offsets and progress reports.
+struct CountingReader<'a, R: Read> {
+    inner: &'a mut R,
+    position: u64,
+    progress: Option<&'a ReadProgress>,
+    next_report: u64,
+    last_report: u64,
+}
+
+impl<'a, R: Read> CountingReader<'a, R> {
+    fn new(inner: &'a mut R, options: &'a ReadOptions) -> Self {
+        CountingReader {
+            inner,
+            position: 0,
+            progress: options.progress.as_ref(),
+            next_report: options
+                .progress
+                .as_ref()
+                .map_or(0, |progress| progress.interval),
+            last_report: 0,
+        }
+    }
+
+    /// Reports the final position once a read has completed, so the last report always
+    /// matches the number of bytes consumed. The read can no longer be cancelled by then.
+    fn finish(&mut self) {
+        if let Some(progress) = self.progress.filter(|_| self.position > self.last_report) {
+            self.last_report = self.position;
+            (progress.callback)(self.position);
+        }
+    }
+}
+
+impl<R: Read> Read for CountingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let count: usize = self.inner.read(buf)?;
+        self.position += count as u64;
+        if let Some(progress) = self.progress.filter(|_| self.position >= self.next_report) {
+            self.next_report = self.position + progress.interval;
+            self.last_report = self.position;
+            if !(progress.callback)(self.position) {
+                return Err(Error::other("Read was cancelled"));
+            }
+        }
+        Ok(count)
+    }
+}
+
+/// Attaches a path to an error, unless it already carries a context from a nested read.
+fn with_path(error: Error, path: Vec<PathSegment>) -> Error {
+    if error
+        .get_ref()
+        .is_some_and(|inner| inner.is::<ReadErrorContext>())
+    {
+        return error;
+    }
+    let context: ReadErrorContext = ReadErrorContext {
+        message: error.to_string(),
+        offset: 0,
+        path,
+    };
+    Error::new(error.kind(), context)
+}
+
+/// Options for [`read_with`], including limits for parsing untrusted input.
+#[derive(Debug, Clone)]
+pub struct ReadOptions {
+    pub invalid_strings: InvalidStringPolicy,
+    pub root_name: RootName,
+    /// Maximum nesting of compounds and lists, counting the root. Defaults to 512, like Java.
+    /// Parsing doesn't recurse, but dropping a [`Tag`] does, so keep this bounded.
+    pub max_depth: usize,
+    /// Maximum encoded length of a string or name, in bytes.
+    pub max_string_length: usize,
+    /// Maximum element count of a list or array.
+    pub max_array_length: usize,
+    /// Whether [`read_with`] accepts bytes after the root tag.
+    pub allow_trailing_bytes: bool,
+    /// Rejects duplicate compound keys, which are tolerated by default, the last one winning.
+    pub reject_duplicate_keys: bool,
+    pub progress: Option<ReadProgress>,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        ReadOptions {
+            invalid_strings: InvalidStringPolicy::default(),
+            root_name: RootName::default(),
+            max_depth: 512,
+            max_string_length: u16::MAX as usize,
+            max_array_length: i32::MAX as usize,
+            allow_trailing_bytes: true,
+            reject_duplicate_keys: false,
+            progress: None,
+        }
+    }
+}
+
+impl ReadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn invalid_strings(mut self, policy: InvalidStringPolicy) -> Self {
+        self.invalid_strings = policy;
+        self
+    }
+
+    pub fn root_name(mut self, root_name: RootName) -> Self {
+        self.root_name = root_name;
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn max_string_length(mut self, max_string_length: usize) -> Self {
+        self.max_string_length = max_string_length;
+        self
+    }
+
+    pub fn max_array_length(mut self, max_array_length: usize) -> Self {
+        self.max_array_length = max_array_length;
+        self
+    }
+
+    pub fn allow_trailing_bytes(mut self, allow_trailing_bytes: bool) -> Self {
+        self.allow_trailing_bytes = allow_trailing_bytes;
+        self
+    }
+
+    pub fn reject_duplicate_keys(mut self, reject_duplicate_keys: bool) -> Self {
+        self.reject_duplicate_keys = reject_duplicate_keys;
+        self
+    }
+
+    /// Calls `callback` with the number of bytes consumed each time roughly another
+    /// `interval` bytes have been read, and once more with the total when the read completes.
+    /// Returning `false` cancels the read, which then fails with an `Other` error.
+    ///
+    /// Offsets count the NBT data itself, so for compressed input they are positions in the
+    /// decompressed stream.
+    pub fn progress<F>(mut self, interval: u64, callback: F) -> Self
+    where
+        F: Fn(u64) -> bool + Send + Sync + 'static,
+    {
+        self.progress = Some(ReadProgress {
+            interval: interval.max(1),
+            callback: Arc::new(callback),
+        });
+        self
+    }
+}
+
+/// Reads an NBT file from a byte vector and returns its root tag along with the root name.
+pub fn read(data: &[u8]) -> Result<NamedTag> {
+    read_from(&mut Cursor::new(data))
+}
+
+/// Reads an NBT file from a byte vector with the given options.
+pub fn read_with(data: &[u8], options: &ReadOptions) -> Result<NamedTag> {
+    let mut cursor: Cursor<&[u8]> = Cursor::new(data);
+    let named: NamedTag = read_from_with(&mut cursor, options)?;
+    if !options.allow_trailing_bytes && cursor.position() != data.len() as u64 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "Trailing bytes after root tag",
+        ));
+    }
+    Ok(named)
+}
+
+/// Reads one NBT document from the start of `data`, returning it along with the number of
+/// bytes it occupied. Anything after it is left for the caller.
+pub fn read_prefix(data: &[u8], options: &ReadOptions) -> Result<(NamedTag, usize)> {
+    let mut cursor: Cursor<&[u8]> = Cursor::new(data);
+    let named: NamedTag = read_from_with(&mut cursor, options)?;
+    Ok((named, cursor.position() as usize))
+}
+
+/// Iterates over several NBT documents stored back to back, as in some Bedrock LevelDB values.
+/// Stops at the end of the input, or after the first error.
+pub fn read_many<'a>(data: &'a [u8], options: &'a ReadOptions) -> ReadMany<'a> {
+    ReadMany {
+        data,
+        options,
+        position: 0,
+        failed: false,
+    }
+}
+
+/// Iterator returned by [`read_many`].
+#[derive(Debug, Clone)]
+pub struct ReadMany<'a> {
+    data: &'a [u8],
+    options: &'a ReadOptions,
+    position: usize,
+    failed: bool,
+}
+
+impl ReadMany<'_> {
+    /// Number of bytes consumed by the documents read so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Iterator for ReadMany<'_> {
+    type Item = Result<NamedTag>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.position >= self.data.len() {
+            return None;
+        }
+        match read_prefix(&self.data[self.position..], self.options) {
+            Ok((named, consumed)) => {
+                self.position += consumed;
+                Some(Ok(named))
+            }
+            Err(error) => {
+                self.failed = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Reads an NBT file from any reader, such as a file or decompression stream, without
+/// loading it into memory first. Wrap unbuffered readers in a `BufReader`, since every value
+/// is read with a separate call.
+pub fn read_from<R: Read>(reader: &mut R) -> Result<NamedTag> {
+    read_from_with(reader, &ReadOptions::default())
+}
+
+/// Reads a compressed NBT file from any reader, decompressing it on the fly so only a small
+/// buffer is held in memory instead of the whole decompressed document.
+pub fn read_compressed_from<R: Read>(reader: R, format: CompressionFormat) -> Result<NamedTag> {
+    let mut decoder: BufReader<Box<dyn Read + '_>> =
+        BufReader::new(decompress_reader(reader, format));
+    read_from(&mut decoder)
+}
+
+/// Reads an NBT file from any reader with the given options.
+///
+/// Errors carry a [`ReadErrorContext`] with the offset and path where reading stopped.
+pub fn read_from_with<R: Read>(reader: &mut R, options: &ReadOptions) -> Result<NamedTag> {
+    read_root(reader, options, &mut Scratch::default())
+}
+
+fn read_root<R: Read>(
+    reader: &mut R,
+    options: &ReadOptions,
+    scratch: &mut Scratch,
+) -> Result<NamedTag> {
+    #[cfg(feature = "tracing")]
+    let started: std::time::Instant = std::time::Instant::now();
+    let mut reader: CountingReader<R> = CountingReader::new(reader, options);
+    let mut read_root = || -> Result<NamedTag> {
+        let root_tag_id: TagID = read_tag_id(&mut reader)?;
+        let root_name: String = match options.root_name {
+            RootName::Named => read_name_into(&mut reader, options, &mut scratch.bytes)?,
+            RootName::Omitted => String::new(),
+        };
+        let tag: Tag = read_tag_in(&mut reader, &root_tag_id, options, 0, scratch)?;
+        Ok(NamedTag::new(root_name, tag))
+    };
+    let result: Result<NamedTag> = read_root().map_err(|error| locate(error, reader.position));
+    if result.is_ok() {
+        reader.finish();
+    }
+    // Field values are only computed when the event is enabled, so counting tags is free
+    // otherwise.
+    #[cfg(feature = "tracing")]
+    match &result {
+        Ok(named) => tracing::debug!(
+            root_name = %named.name,
+            bytes = reader.position,
+            tags = named.tag.stats().counts.values().sum::<usize>(),
+            elapsed_us = started.elapsed().as_micros() as u64,
+            "Read NBT document"
+        ),
+        Err(error) => tracing::debug!(
+            %error,
+            bytes = reader.position,
+            elapsed_us = started.elapsed().as_micros() as u64,
+            "Failed to read NBT document"
+        ),
+    }
+    result
+}
+
+/// Buffers that reading needs along the way, kept between documents by a [`Parser`].
+#[derive(Default)]
+struct Scratch {
+    /// Raw bytes of the string being decoded.
+    bytes: Vec<u8>,
+    /// Open containers. Empty between documents, but keeps its capacity.
+    stack: Vec<Frame>,
+}
+
+/// A reader that keeps its scratch buffers between documents, so reading many small ones,
+/// such as every chunk of a region, doesn't allocate them afresh each time.
+///
+/// ```
+/// use rust_nbt::{nbt, write, NamedTag, Parser, ReadOptions};
+///
+/// let mut parser: Parser = Parser::new(ReadOptions::new());
+/// for x in 0..4 {
+///     let data: Vec<u8> = write(&nbt!({ xPos: x }), "").unwrap();
+///     let named: NamedTag = parser.read(&data)?;
+///     assert_eq!(named.tag, nbt!({ xPos: x }));
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct Parser {
+    options: ReadOptions,
+    scratch: Scratch,
+}
+
+impl fmt::Debug for Parser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Parser")
+            .field("options", &self.options)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Parser {
+    pub fn new(options: ReadOptions) -> Self {
+        Parser {
+            options,
+            scratch: Scratch::default(),
+        }
+    }
+
+    pub fn options(&self) -> &ReadOptions {
+        &self.options
+    }
+
+    /// Reads a document from any reader, like [`read_from_with`].
+    pub fn read_root<R: Read>(&mut self, reader: &mut R) -> Result<NamedTag> {
+        read_root(reader, &self.options, &mut self.scratch)
+    }
+
+    /// Reads a document from a byte slice, like [`read_with`].
+    pub fn read(&mut self, data: &[u8]) -> Result<NamedTag> {
+        let mut cursor: Cursor<&[u8]> = Cursor::new(data);
+        let named: NamedTag = self.read_root(&mut cursor)?;
+        if !self.options.allow_trailing_bytes && cursor.position() != data.len() as u64 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Trailing bytes after root tag",
+            ));
+        }
+        Ok(named)
+    }
+}
+
+/// The outcome of [`read_recover`]: everything parsed before the first error.
+#[derive(Debug)]
+pub struct PartialRead {
+    /// The root tag, with every list and compound that was open when reading stopped closed
+    /// off at the last complete value.
+    pub tag: NamedTag,
+    /// Why reading stopped, or `None` if the whole document was read.
+    pub error: Option<Error>,
+}
+
+impl PartialRead {
+    /// Where reading stopped, if it did.
+    pub fn context(&self) -> Option<&ReadErrorContext> {
+        self.error.as_ref()?.get_ref()?.downcast_ref()
+    }
+}
+
+/// Reads as much of a possibly corrupted NBT file as it can, instead of failing on the first
+/// bad byte.
+///
+/// Only list and compound roots can be recovered, since they're the only ones with complete
+/// values to keep. An error is returned instead if the root tag header can't be read, if a
+/// list root's header is invalid, or if a root of any other type can't be read in full.
+pub fn read_recover(data: &[u8], options: &ReadOptions) -> Result<PartialRead> {
+    let mut cursor: Cursor<&[u8]> = Cursor::new(data);
+    let mut reader: CountingReader<Cursor<&[u8]>> = CountingReader::new(&mut cursor, options);
+    let mut read_root = || -> Result<PartialRead> {
+        let root_tag_id: TagID = read_tag_id(&mut reader)?;
+        let root_name: String = match options.root_name {
+            RootName::Named => read_name(&mut reader, options)?,
+            RootName::Omitted => String::new(),
+        };
+        let (tag, error): (Tag, Option<Error>) = match root_tag_id {
+            TagID::List | TagID::Compound => read_nested_partial(
+                &mut reader,
+                &root_tag_id,
+                options,
+                0,
+                &mut Scratch::default(),
+            )?,
+            _ => (read_tag(&mut reader, &root_tag_id, options, 0)?, None),
+        };
+        Ok(PartialRead {
+            tag: NamedTag::new(root_name, tag),
+            error,
+        })
+    };
+    let mut partial: PartialRead = read_root().map_err(|error| locate(error, reader.position))?;
+    partial.error = partial.error.map(|error| locate(error, reader.position));
+    if partial.error.is_none() {
+        reader.finish();
+    }
+    Ok(partial)
+}
+
+/// Fills in the offset of an error's context, adding an empty context if it has none.
+fn locate(error: Error, offset: u64) -> Error {
+    let mut error: Error = with_path(error, Vec::new());
+    if let Some(context) = error
+        .get_mut()
+        .and_then(|inner| inner.downcast_mut::<ReadErrorContext>())
+    {
+        context.offset = offset;
+    }
+    error
+}
+
+/// Reads a single unnamed tag payload of the given type, which must span all of `data`.
+pub(crate) fn read_payload(data: &[u8], tag_id: TagID) -> Result<Tag> {
+    read_payload_with(data, tag_id, &ReadOptions::default())
+}
+
+/// Reads a single unnamed tag payload with the given options.
+pub(crate) fn read_payload_with(data: &[u8], tag_id: TagID, options: &ReadOptions) -> Result<Tag> {
+    let mut cursor: Cursor<&[u8]> = Cursor::new(data);
+    let tag: Tag = read_tag(&mut cursor, &tag_id, options, 0)?;
+    if cursor.position() != data.len() as u64 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "Trailing bytes after tag payload",
+        ));
+    }
+    Ok(tag)
+}
+
+/// Reads a single NBT tag from the given reader. `depth` counts the enclosing compounds and
+/// lists.
+pub(crate) fn read_tag<R: Read>(
+    reader: &mut R,
+    tag_id: &TagID,
+    options: &ReadOptions,
+    depth: usize,
+) -> Result<Tag> {
+    read_tag_in(reader, tag_id, options, depth, &mut Scratch::default())
+}
+
+fn read_tag_in<R: Read>(
+    reader: &mut R,
+    tag_id: &TagID,
+    options: &ReadOptions,
+    depth: usize,
+    scratch: &mut Scratch,
+) -> Result<Tag> {
     match tag_id {
         TagID::End => Ok(Tag::End),
         TagID::Byte => Ok(Tag::Byte(read_byte(reader)?)),
@@ -27,16 +540,15 @@ fn read_tag<R: Read>(reader: &mut R, tag_id: &TagID) -> Result<Tag> {
         TagID::Long => Ok(Tag::Long(read_long(reader)?)),
         TagID::Float => Ok(Tag::Float(read_float(reader)?)),
         TagID::Double => Ok(Tag::Double(read_double(reader)?)),
-        TagID::ByteArray => Ok(Tag::ByteArray(read_byte_array(reader)?)),
-        TagID::String => Ok(Tag::String(read_string(reader)?)),
-        TagID::List => Ok(Tag::List(read_list(reader)?)),
-        TagID::Compound => Ok(Tag::Compound(read_compound(reader)?)),
-        TagID::IntArray => Ok(Tag::IntArray(read_int_array(reader)?)),
-        TagID::LongArray => Ok(Tag::LongArray(read_long_array(reader)?)),
+        TagID::ByteArray => Ok(Tag::ByteArray(read_byte_array(reader, options)?)),
+        TagID::String => read_string_tag(reader, options, &mut scratch.bytes),
+        TagID::List | TagID::Compound => read_nested(reader, tag_id, options, depth, scratch),
+        TagID::IntArray => Ok(Tag::IntArray(read_int_array(reader, options)?)),
+        TagID::LongArray => Ok(Tag::LongArray(read_long_array(reader, options)?)),
     }
 }
 
-fn read_tag_id<R: Read>(reader: &mut R) -> Result<TagID> {
+pub(crate) fn read_tag_id<R: Read>(reader: &mut R) -> Result<TagID> {
     let value: u8 = read_unsigned_byte(reader)?;
     TagID::try_from(value)
 }
@@ -48,7 +560,7 @@ fn read_unsigned_byte<R: Read>(reader: &mut R) -> Result<u8> {
     Ok(buffer[0])
 }
 
-fn read_byte<R: Read>(reader: &mut R) -> Result<ByteTag> {
+pub(crate) fn read_byte<R: Read>(reader: &mut R) -> Result<ByteTag> {
     Ok(read_unsigned_byte(reader)? as i8)
 }
 
@@ -58,89 +570,637 @@ fn read_unsigned_short<R: Read>(reader: &mut R) -> Result<u16> {
     Ok(u16::from_be_bytes(buffer))
 }
 
-fn read_short<R: Read>(reader: &mut R) -> Result<ShortTag> {
+pub(crate) fn read_short<R: Read>(reader: &mut R) -> Result<ShortTag> {
     Ok(read_unsigned_short(reader)? as i16)
 }
 
-fn read_int<R: Read>(reader: &mut R) -> Result<IntTag> {
+pub(crate) fn read_int<R: Read>(reader: &mut R) -> Result<IntTag> {
     let mut buffer: [u8; 4] = [0; 4];
     reader.read_exact(&mut buffer)?;
     Ok(i32::from_be_bytes(buffer))
 }
 
-fn read_long<R: Read>(reader: &mut R) -> Result<LongTag> {
+pub(crate) fn read_long<R: Read>(reader: &mut R) -> Result<LongTag> {
     let mut buffer: [u8; 8] = [0; 8];
     reader.read_exact(&mut buffer)?;
     Ok(i64::from_be_bytes(buffer))
 }
 
-fn read_float<R: Read>(reader: &mut R) -> Result<FloatTag> {
+pub(crate) fn read_float<R: Read>(reader: &mut R) -> Result<FloatTag> {
     let mut buffer: [u8; 4] = [0; 4];
     reader.read_exact(&mut buffer)?;
     Ok(f32::from_be_bytes(buffer))
 }
 
-fn read_double<R: Read>(reader: &mut R) -> Result<DoubleTag> {
+pub(crate) fn read_double<R: Read>(reader: &mut R) -> Result<DoubleTag> {
     let mut buffer: [u8; 8] = [0; 8];
     reader.read_exact(&mut buffer)?;
     Ok(f64::from_be_bytes(buffer))
 }
 
-fn read_byte_array<R: Read>(reader: &mut R) -> Result<ByteArrayTag> {
-    let length: usize = read_int(reader)? as usize;
-    let mut value: ByteArrayTag = Vec::with_capacity(length);
-    for _ in 0..length {
-        value.push(read_byte(reader)?);
+pub(crate) fn read_byte_array<R: Read>(
+    reader: &mut R,
+    options: &ReadOptions,
+) -> Result<ByteArrayTag> {
+    read_array(reader, options, |[byte]| byte as ByteTag)
+}
+
+/// Reads a length-prefixed array of fixed-size elements. The payload is read in blocks of
+/// up to [`MAX_PREALLOCATION`] bytes rather than element by element, which keeps a bogus
+/// length from allocating more than the input actually holds.
+fn read_array<R: Read, T, const N: usize>(
+    reader: &mut R,
+    options: &ReadOptions,
+    convert: fn([u8; N]) -> T,
+) -> Result<Vec<T>> {
+    let mut remaining: usize = read_length(reader, options)?;
+    let mut value: Vec<T> = Vec::with_capacity(capacity_hint::<T>(remaining));
+    let mut buffer: Vec<u8> = Vec::new();
+    while remaining > 0 {
+        let count: usize = remaining.min(MAX_PREALLOCATION / N);
+        buffer.resize(count * N, 0);
+        reader.read_exact(&mut buffer)?;
+        value.extend(
+            buffer
+                .chunks_exact(N)
+                .map(|bytes| convert(bytes.try_into().expect("Chunks have N bytes"))),
+        );
+        remaining -= count;
     }
     Ok(value)
 }
 
-fn read_string<R: Read>(reader: &mut R) -> Result<StringTag> {
+/// Upper bound on the memory reserved up front for a list or array, in bytes.
+const MAX_PREALLOCATION: usize = 64 * 1024;
+
+/// Caps the capacity reserved for a declared length, since the length comes from the input
+/// and may be far larger than the data that follows. Larger collections grow as elements are
+/// actually read, so a bogus length fails at the end of input instead of allocating first.
+fn capacity_hint<T>(length: usize) -> usize {
+    length.min(MAX_PREALLOCATION / std::mem::size_of::<T>().max(1))
+}
+
+/// Reads a list or array length, rejecting negative values and lengths over the limit.
+pub(crate) fn read_length<R: Read>(reader: &mut R, options: &ReadOptions) -> Result<usize> {
+    let length: IntTag = read_int(reader)?;
+    if length < 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Negative length {}", length),
+        ));
+    }
+    if length as usize > options.max_array_length {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Length {} exceeds the limit of {}",
+                length, options.max_array_length
+            ),
+        ));
+    }
+    Ok(length as usize)
+}
+
+/// Reads a list's element ID and length. A list of End tags must be empty: End elements have
+/// no payload, so the remaining input can't bound how many of them a list claims.
+pub(crate) fn read_list_header<R: Read>(
+    reader: &mut R,
+    options: &ReadOptions,
+) -> Result<(TagID, usize)> {
+    let element_id: TagID = read_tag_id(reader)?;
+    let length: usize = read_length(reader, options)?;
+    if element_id == TagID::End && length > 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "List of End tags has a non-zero length",
+        ));
+    }
+    Ok((element_id, length))
+}
+
+/// Reads a string's raw bytes into `buffer`, replacing its contents.
+fn read_string_bytes<R: Read>(
+    reader: &mut R,
+    options: &ReadOptions,
+    buffer: &mut Vec<u8>,
+) -> Result<()> {
     let length: usize = read_unsigned_short(reader)? as usize;
-    let mut buffer: Vec<u8> = vec![0; length];
-    reader.read_exact(&mut buffer)?;
-    Ok(String::from_utf8(buffer).unwrap())
+    if length > options.max_string_length {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "String length {} exceeds the limit of {}",
+                length, options.max_string_length
+            ),
+        ));
+    }
+    buffer.clear();
+    buffer.resize(length, 0);
+    reader.read_exact(buffer)
 }
 
-fn read_list<R: Read>(reader: &mut R) -> Result<ListTag<Tag>> {
-    let tag_id: TagID = read_tag_id(reader)?;
-    let length: usize = read_int(reader)? as usize;
-    let mut value: ListTag<Tag> = Vec::with_capacity(length);
-    for _ in 0..length {
-        value.push(read_tag(reader, &tag_id)?);
+/// Reads a string payload, applying the invalid string policy.
+fn read_string_tag<R: Read>(
+    reader: &mut R,
+    options: &ReadOptions,
+    buffer: &mut Vec<u8>,
+) -> Result<Tag> {
+    read_string_bytes(reader, options, buffer)?;
+    match (decode_mutf8(buffer), options.invalid_strings) {
+        (Ok(value), _) => Ok(Tag::String(value)),
+        (Err(error), InvalidStringPolicy::Error) => Err(error),
+        (Err(_), InvalidStringPolicy::Lossy) => Ok(Tag::String(decode_mutf8_lossy(buffer))),
+        (Err(_), InvalidStringPolicy::Preserve) => {
+            let mut payload: Vec<u8> = (buffer.len() as u16).to_be_bytes().to_vec();
+            payload.extend_from_slice(buffer);
+            Ok(Tag::RawEncoded(RawTag::from_parts_unchecked(
+                TagID::String,
+                payload,
+            )))
+        }
+    }
+}
+
+/// Reads a compound key or root name, which can't hold raw bytes.
+pub(crate) fn read_name<R: Read>(reader: &mut R, options: &ReadOptions) -> Result<String> {
+    read_name_into(reader, options, &mut Vec::new())
+}
+
+fn read_name_into<R: Read>(
+    reader: &mut R,
+    options: &ReadOptions,
+    buffer: &mut Vec<u8>,
+) -> Result<String> {
+    read_string_bytes(reader, options, buffer)?;
+    match (decode_mutf8(buffer), options.invalid_strings) {
+        (Ok(value), _) => Ok(value),
+        (Err(error), InvalidStringPolicy::Error) => Err(error),
+        (Err(_), _) => Ok(decode_mutf8_lossy(buffer)),
+    }
+}
+
+/// A list or compound that is still being read, along with the key it will be stored under
+/// in its parent compound.
+enum Frame {
+    List {
+        key: Option<String>,
+        tag_id: TagID,
+        remaining: usize,
+        value: ListTag<Tag>,
+    },
+    Compound {
+        key: Option<String>,
+        value: CompoundTag,
+    },
+}
+
+impl Frame {
+    /// The path segment of the next child to be added.
+    fn next_segment(&self, key: &Option<String>) -> PathSegment {
+        match self {
+            Frame::List { value, .. } => PathSegment::Index(value.len()),
+            Frame::Compound { .. } => PathSegment::Key(key.clone().unwrap_or_default()),
+        }
+    }
+
+    /// Reads the header of a list or compound nested `depth` levels deep.
+    fn open<R: Read>(
+        reader: &mut R,
+        tag_id: &TagID,
+        key: Option<String>,
+        options: &ReadOptions,
+        depth: usize,
+    ) -> Result<Self> {
+        if depth > options.max_depth {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Nesting exceeds the depth limit of {}", options.max_depth),
+            ));
+        }
+        if *tag_id == TagID::Compound {
+            return Ok(Frame::Compound {
+                key,
+                value: IndexMap::new(),
+            });
+        }
+        let (element_id, length): (TagID, usize) = read_list_header(reader, options)?;
+        Ok(Frame::List {
+            key,
+            tag_id: element_id,
+            remaining: length,
+            value: Vec::with_capacity(capacity_hint::<Tag>(length)),
+        })
+    }
+
+    /// Reads the ID and key of the next child, or `None` once the container is complete.
+    fn next_child<R: Read>(
+        &mut self,
+        reader: &mut R,
+        options: &ReadOptions,
+        buffer: &mut Vec<u8>,
+    ) -> Result<Option<(TagID, Option<String>)>> {
+        match self {
+            Frame::List {
+                tag_id, remaining, ..
+            } => {
+                if *remaining == 0 {
+                    return Ok(None);
+                }
+                *remaining -= 1;
+                Ok(Some((*tag_id, None)))
+            }
+            Frame::Compound { .. } => {
+                let tag_id: TagID = read_tag_id(reader)?;
+                if tag_id == TagID::End {
+                    return Ok(None);
+                }
+                Ok(Some((
+                    tag_id,
+                    Some(read_name_into(reader, options, buffer)?),
+                )))
+            }
+        }
+    }
+
+    fn push(&mut self, key: Option<String>, entry: Tag, options: &ReadOptions) -> Result<()> {
+        match self {
+            Frame::List { value, .. } => value.push(entry),
+            Frame::Compound { value, .. } => {
+                let key: String = key.unwrap_or_default();
+                if value.insert(key, entry).is_some() && options.reject_duplicate_keys {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "Duplicate key in compound",
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn close(self) -> (Option<String>, Tag) {
+        match self {
+            Frame::List { key, value, .. } => (key, Tag::List(value)),
+            Frame::Compound { key, value } => (key, Tag::Compound(value)),
+        }
     }
-    Ok(value)
 }
 
-fn read_compound<R: Read>(reader: &mut R) -> Result<CompoundTag> {
-    let mut value: CompoundTag = IndexMap::new();
+/// Reads a list or compound using an explicit stack rather than recursion, so deeply nested
+/// input can't overflow the call stack. Nesting is still capped by `max_depth`.
+fn read_nested<R: Read>(
+    reader: &mut R,
+    tag_id: &TagID,
+    options: &ReadOptions,
+    depth: usize,
+    scratch: &mut Scratch,
+) -> Result<Tag> {
+    match read_nested_partial(reader, tag_id, options, depth, scratch)? {
+        (tag, None) => Ok(tag),
+        (_, Some(error)) => Err(error),
+    }
+}
+
+/// Like [`read_nested`], but on error returns the containers read so far, closed off at the
+/// last complete value, along with the error. Fails outright only if the outermost
+/// container's header can't be read.
+fn read_nested_partial<R: Read>(
+    reader: &mut R,
+    tag_id: &TagID,
+    options: &ReadOptions,
+    depth: usize,
+    scratch: &mut Scratch,
+) -> Result<(Tag, Option<Error>)> {
+    let mut stack: Vec<Frame> = std::mem::take(&mut scratch.stack);
+    stack.push(Frame::open(reader, tag_id, None, options, depth + 1)?);
     loop {
-        let tag_id: TagID = read_tag_id(reader)?;
-        match tag_id {
-            TagID::End => break,
-            _ => (),
+        let top: &mut Frame = stack.last_mut().expect("Stack is never empty");
+        let child: Option<(TagID, Option<String>)> =
+            match top.next_child(reader, options, &mut scratch.bytes) {
+                Ok(child) => child,
+                Err(error) => {
+                    let error: Error = with_path(error, stack_path(&stack));
+                    return Ok((unwind(stack), Some(error)));
+                }
+            };
+        match child {
+            Some((child_id @ (TagID::List | TagID::Compound), key)) => {
+                let child_depth: usize = depth + stack.len() + 1;
+                match Frame::open(reader, &child_id, key.clone(), options, child_depth) {
+                    Ok(frame) => stack.push(frame),
+                    Err(error) => {
+                        let mut path: Vec<PathSegment> = stack_path(&stack);
+                        path.push(stack[stack.len() - 1].next_segment(&key));
+                        return Ok((unwind(stack), Some(with_path(error, path))));
+                    }
+                }
+            }
+            Some((child_id, key)) => {
+                let entry: Result<Tag> = read_tag_in(reader, &child_id, options, depth, scratch);
+                let pushed: Result<()> =
+                    entry.and_then(|entry| top.push(key.clone(), entry, options));
+                if let Err(error) = pushed {
+                    let mut path: Vec<PathSegment> = stack_path(&stack);
+                    path.push(stack[stack.len() - 1].next_segment(&key));
+                    return Ok((unwind(stack), Some(with_path(error, path))));
+                }
+            }
+            None => {
+                let frame: Frame = stack.pop().expect("Stack is never empty");
+                let segment: PathSegment = match (stack.last(), &frame) {
+                    (Some(parent), Frame::List { key, .. } | Frame::Compound { key, .. }) => {
+                        parent.next_segment(key)
+                    }
+                    (None, _) => {
+                        scratch.stack = stack;
+                        return Ok((frame.close().1, None));
+                    }
+                };
+                let (key, value): (Option<String>, Tag) = frame.close();
+                let parent: &mut Frame = stack.last_mut().expect("Parent was just checked");
+                if let Err(error) = parent.push(key, value, options) {
+                    let mut path: Vec<PathSegment> = stack_path(&stack);
+                    path.push(segment);
+                    return Ok((unwind(stack), Some(with_path(error, path))));
+                }
+            }
         }
-        let name: String = read_string(reader)?;
-        let entry: Tag = read_tag(reader, &tag_id)?;
-        value.insert(name, entry);
     }
-    Ok(value)
 }
 
-fn read_int_array<R: Read>(reader: &mut R) -> Result<IntArrayTag> {
-    let length: usize = read_int(reader)? as usize;
-    let mut value: IntArrayTag = Vec::with_capacity(length);
-    for _ in 0..length {
-        value.push(read_int(reader)?);
+/// Closes every open container, folding each into its parent, and returns the outermost one.
+fn unwind(mut stack: Vec<Frame>) -> Tag {
+    let lenient: ReadOptions = ReadOptions::default();
+    loop {
+        let (key, value): (Option<String>, Tag) =
+            stack.pop().expect("Stack is never empty").close();
+        match stack.last_mut() {
+            Some(parent) => {
+                // Never fails, since the default options allow duplicate keys.
+                let _ = parent.push(key, value, &lenient);
+            }
+            None => return value,
+        }
     }
-    Ok(value)
 }
 
-fn read_long_array<R: Read>(reader: &mut R) -> Result<LongArrayTag> {
-    let length: usize = read_int(reader)? as usize;
-    let mut value: LongArrayTag = Vec::with_capacity(length);
-    for _ in 0..length {
-        value.push(read_long(reader)?);
+/// The path to the innermost open container on the stack.
+fn stack_path(stack: &[Frame]) -> Vec<PathSegment> {
+    stack
+        .windows(2)
+        .map(|pair| match &pair[1] {
+            Frame::List { key, .. } | Frame::Compound { key, .. } => pair[0].next_segment(key),
+        })
+        .collect()
+}
+
+pub(crate) fn read_int_array<R: Read>(
+    reader: &mut R,
+    options: &ReadOptions,
+) -> Result<IntArrayTag> {
+    read_array(reader, options, IntTag::from_be_bytes)
+}
+
+pub(crate) fn read_long_array<R: Read>(
+    reader: &mut R,
+    options: &ReadOptions,
+) -> Result<LongArrayTag> {
+    read_array(reader, options, LongTag::from_be_bytes)
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::fs::File;
+    use std::io::BufReader;
+
+    #[test]
+    fn test_read_from() {
+        let file: File = File::open("./test/bigtest.nbt").unwrap();
+        let mut decoder: GzDecoder<BufReader<File>> = GzDecoder::new(BufReader::new(file));
+        let tag: Tag = read_from(&mut decoder).expect("Read failed").tag;
+
+        let data: Vec<u8> = std::fs::read("./test/bigtest_raw.nbt").unwrap();
+        assert_eq!(tag, read(&data).expect("Read failed").tag);
+
+        let compressed: Vec<u8> = crate::compress(&data, CompressionFormat::Lz4).unwrap();
+        let streamed: NamedTag =
+            read_compressed_from(compressed.as_slice(), CompressionFormat::Lz4).unwrap();
+        assert_eq!(streamed, NamedTag::new("Level", tag));
+    }
+
+    #[test]
+    fn test_invalid_string_policy() {
+        let mut data: Vec<u8> = crate::write(&Tag::from("ab"), "").unwrap();
+        let length: usize = data.len();
+        data[length - 1] = 0xff;
+        assert_eq!(read(&data).unwrap_err().kind(), ErrorKind::InvalidData);
+
+        let lossy: ReadOptions = ReadOptions::new().invalid_strings(InvalidStringPolicy::Lossy);
+        assert_eq!(
+            read_with(&data, &lossy).unwrap().tag,
+            Tag::from("a\u{fffd}")
+        );
+        let encoded_nul: Vec<u8> = vec![0x08, 0x00, 0x00, 0x00, 0x03, 0xc0, 0x80, 0xff];
+        assert_eq!(
+            read_with(&encoded_nul, &lossy).unwrap().tag,
+            Tag::from("\0\u{fffd}")
+        );
+
+        let preserve: ReadOptions =
+            ReadOptions::new().invalid_strings(InvalidStringPolicy::Preserve);
+        let tag: Tag = read_with(&data, &preserve).unwrap().tag;
+        assert_eq!(tag.id(), TagID::String);
+        assert_eq!(crate::write(&tag, "").unwrap(), data);
+        assert_eq!(tag.to_snbt(), "\"a\u{fffd}\"");
+    }
+
+    #[test]
+    fn test_nameless_root() {
+        let tag: Tag = crate::nbt!({ text: "Hello" });
+        let data: Vec<u8> = crate::write_nameless(&tag).unwrap();
+        assert_eq!(data[..3], [0x0a, 0x08, 0x00]);
+        let options: ReadOptions = ReadOptions::new().root_name(RootName::Omitted);
+        assert_eq!(read_with(&data, &options).unwrap(), NamedTag::new("", tag));
+        assert!(read(&data).is_err());
+    }
+
+    #[test]
+    fn test_read_many() {
+        let first: Vec<u8> = crate::write(&crate::nbt!({ a: 1 }), "first").unwrap();
+        let second: Vec<u8> = crate::write(&crate::nbt!([1, 2]), "second").unwrap();
+        let mut data: Vec<u8> = [first.clone(), second].concat();
+
+        let (named, consumed): (NamedTag, usize) = read_prefix(&data, &ReadOptions::new()).unwrap();
+        assert_eq!(named.name, "first");
+        assert_eq!(consumed, first.len());
+
+        let options: ReadOptions = ReadOptions::new();
+        let names: Vec<String> = read_many(&data, &options)
+            .map(|named| named.unwrap().name)
+            .collect();
+        assert_eq!(names, ["first", "second"]);
+
+        data.push(0x0a);
+        let mut documents: ReadMany = read_many(&data, &options);
+        assert_eq!(documents.by_ref().filter(Result::is_ok).count(), 2);
+        assert_eq!(documents.position(), data.len() - 1);
+        assert!(documents.next().is_none());
+    }
+
+    #[test]
+    fn test_read_recover() {
+        let tag: Tag = crate::nbt!({ Level: { Name: "Test", Sections: [{ Y: 0i8 }, { Y: 1i8 }] } });
+        let data: Vec<u8> = crate::write(&tag, "").unwrap();
+        let complete: PartialRead = read_recover(&data, &ReadOptions::new()).unwrap();
+        assert_eq!(complete.tag.tag, tag);
+        assert!(complete.error.is_none());
+
+        // Cut off inside the second section
+        let partial: PartialRead =
+            read_recover(&data[..data.len() - 4], &ReadOptions::new()).unwrap();
+        assert_eq!(
+            partial.tag.tag,
+            crate::nbt!({ Level: { Name: "Test", Sections: [{ Y: 0i8 }, {}] } })
+        );
+        let context: &ReadErrorContext = partial.context().unwrap();
+        assert_eq!(format_path(&context.path), "Level.Sections[1].Y");
+        assert_eq!(context.offset, data.len() as u64 - 4);
+
+        assert!(read_recover(&[0x0a, 0x00], &ReadOptions::new()).is_err());
+
+        // A scalar root has nothing partial to keep, so it's read in full or not at all.
+        let scalar: Vec<u8> = crate::write(&Tag::Int(7), "").unwrap();
+        let complete: PartialRead = read_recover(&scalar, &ReadOptions::new()).unwrap();
+        assert_eq!(complete.tag.tag, Tag::Int(7));
+        assert!(read_recover(&scalar[..scalar.len() - 1], &ReadOptions::new()).is_err());
+    }
+
+    #[test]
+    fn test_error_context() {
+        let tag: Tag = crate::nbt!({ Level: { Sections: [{ Y: 0i8 }, { Y: 1i8 }, { Y: 2i8 }] } });
+        let mut data: Vec<u8> = crate::write(&tag, "").unwrap();
+        // Corrupt the tag ID of the last Y entry
+        let offset: usize = data.len() - 8;
+        assert_eq!(data[offset], TagID::Byte as u8);
+        data[offset] = 0x2a;
+
+        let error: Error = read(&data).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        let context: &ReadErrorContext = error.get_ref().unwrap().downcast_ref().unwrap();
+        assert_eq!(context.offset, offset as u64 + 1);
+        assert_eq!(format_path(&context.path), "Level.Sections[2]");
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Unknown tag ID 0x2A at offset 0x{:X} in Level.Sections[2]",
+                offset + 1
+            )
+        );
+
+        let data: Vec<u8> = crate::write(&tag, "").unwrap();
+        let error: Error = read(&data[..data.len() - 4]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+        assert!(error.to_string().ends_with("in Level.Sections[2].Y"));
+    }
+
+    #[test]
+    fn test_read_limits() {
+        let nested: Tag = crate::nbt!({ a: { b: [[1, 2]] } });
+        let data: Vec<u8> = crate::write(&nested, "").unwrap();
+        assert!(read_with(&data, &ReadOptions::new().max_depth(4)).is_ok());
+        assert!(read_with(&data, &ReadOptions::new().max_depth(3)).is_err());
+        assert!(read_with(&data, &ReadOptions::new().max_array_length(1)).is_err());
+        assert!(read_with(&data, &ReadOptions::new().max_string_length(0)).is_err());
+
+        let mut trailing: Vec<u8> = data.clone();
+        trailing.push(0);
+        assert!(read_with(&trailing, &ReadOptions::new()).is_ok());
+        let exact: ReadOptions = ReadOptions::new().allow_trailing_bytes(false);
+        assert!(read_with(&trailing, &exact).is_err());
+        assert!(read_with(&data, &exact).is_ok());
+
+        // {a: 1, a: 2}
+        let duplicate: Vec<u8> = vec![0x0a, 0, 0, 0x01, 0, 1, b'a', 1, 0x01, 0, 1, b'a', 2, 0x00];
+        assert!(read_with(&duplicate, &ReadOptions::new()).is_ok());
+        assert!(read_with(&duplicate, &ReadOptions::new().reject_duplicate_keys(true)).is_err());
+
+        // A list of 2^31 - 1 End tags, which take no input but would each take memory
+        let ends: Vec<u8> = vec![0x09, 0, 0, 0x00, 0x7f, 0xff, 0xff, 0xff];
+        assert_eq!(read(&ends).unwrap_err().kind(), ErrorKind::InvalidData);
+
+        // A byte array claiming a negative length
+        let negative: Vec<u8> = vec![0x07, 0, 0, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(read(&negative).unwrap_err().kind(), ErrorKind::InvalidData);
+
+        // A million nested lists must hit the depth limit rather than the call stack
+        let mut deep: Vec<u8> = vec![0x09, 0, 0];
+        for _ in 0..1_000_000 {
+            deep.extend_from_slice(&[0x09, 0, 0, 0, 1]);
+        }
+        deep.extend_from_slice(&[0x00, 0, 0, 0, 0]);
+        assert!(read(&deep).is_err());
+        let mut deep: Vec<u8> = vec![0x09, 0, 0];
+        for _ in 0..2_000 {
+            deep.extend_from_slice(&[0x09, 0, 0, 0, 1]);
+        }
+        deep.extend_from_slice(&[0x00, 0, 0, 0, 0]);
+        let raised: ReadOptions = ReadOptions::new().max_depth(4_000);
+        assert!(read_with(&deep, &raised).is_ok());
+
+        // A long array claiming 2 billion entries with no data behind it
+        let oversized: Vec<u8> = vec![0x0c, 0, 0, 0x7f, 0xff, 0xff, 0xff];
+        assert_eq!(
+            read(&oversized).unwrap_err().kind(),
+            ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn test_read_progress() {
+        use std::sync::Mutex;
+
+        let tag: Tag = Tag::List(vec![Tag::LongArray(vec![0; 64]); 100]);
+        let data: Vec<u8> = crate::write(&tag, "").unwrap();
+        let reports: Arc<Mutex<Vec<u64>>> = Arc::new(Mutex::new(Vec::new()));
+        let recorded: Arc<Mutex<Vec<u64>>> = reports.clone();
+        let options: ReadOptions = ReadOptions::new().progress(4096, move |position| {
+            recorded.lock().unwrap().push(position);
+            true
+        });
+        assert_eq!(read_with(&data, &options).unwrap().tag, tag);
+        let reports: Vec<u64> = reports.lock().unwrap().clone();
+        assert!(reports.len() > 1);
+        assert!(reports.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(reports.last(), Some(&(data.len() as u64)));
+
+        let cancel: ReadOptions = ReadOptions::new().progress(1000, |position| position < 2000);
+        let error: Error = read_with(&data, &cancel).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Other);
+        assert!(error.to_string().starts_with("Read was cancelled"));
+    }
+
+    #[test]
+    fn test_parser() {
+        let tag: Tag = crate::nbt!({ a: [{ b: "c" }], d: { e: "f" } });
+        let data: Vec<u8> = crate::write(&tag, "root").unwrap();
+        let mut parser: Parser = Parser::new(ReadOptions::new().allow_trailing_bytes(false));
+        assert_eq!(
+            parser.read(&data).unwrap(),
+            NamedTag::new("root", tag.clone())
+        );
+        assert!(parser.scratch.stack.is_empty());
+        assert!(parser.scratch.stack.capacity() >= 3);
+
+        assert!(parser.read(&data[..data.len() - 2]).is_err());
+        let mut trailing: Vec<u8> = data.clone();
+        trailing.push(0);
+        assert!(parser.read(&trailing).is_err());
+        assert_eq!(
+            parser.read_root(&mut Cursor::new(&trailing)).unwrap().tag,
+            tag
+        );
     }
-    Ok(value)
 }
@@ -0,0 +1,884 @@
+use crate::{
+    compress, compress_best, decompress, read, write, ChunkPos, CompressedOutput,
+    CompressionCandidate, CompressionFormat, RegionPos, Tag,
+};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Cursor, Error, ErrorKind, Read, Result, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Size of a region file sector, the unit chunks are allocated in.
+pub const SECTOR_SIZE: usize = 4096;
+
+/// Number of chunks in a region file, and entries in each of its header tables.
+pub const REGION_CHUNKS: usize = 1024;
+
+/// Flag on the compression byte marking a chunk whose payload lives in a `.mcc` file.
+const EXTERNAL_FLAG: u8 = 0x80;
+
+/// How a chunk's payload is compressed, as recorded in the byte in front of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChunkCompression {
+    Gzip,
+    Zlib,
+    None,
+    /// The LZ4 block stream format used by Java Edition 1.20.5 and later.
+    Lz4,
+}
+
+impl ChunkCompression {
+    pub fn id(&self) -> u8 {
+        match self {
+            ChunkCompression::Gzip => 1,
+            ChunkCompression::Zlib => 2,
+            ChunkCompression::None => 3,
+            ChunkCompression::Lz4 => 4,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            1 => Ok(ChunkCompression::Gzip),
+            2 => Ok(ChunkCompression::Zlib),
+            3 => Ok(ChunkCompression::None),
+            4 => Ok(ChunkCompression::Lz4),
+            id => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Unknown chunk compression type {}", id),
+            )),
+        }
+    }
+
+    /// Decompresses a chunk payload.
+    pub fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            ChunkCompression::Gzip => decompress(data, CompressionFormat::Gzip),
+            ChunkCompression::Zlib => decompress(data, CompressionFormat::Deflate),
+            ChunkCompression::None => Ok(data.to_vec()),
+            ChunkCompression::Lz4 => decompress_lz4_blocks(data),
+        }
+    }
+
+    /// Compresses a chunk payload.
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            ChunkCompression::Gzip => compress(data, CompressionFormat::Gzip),
+            ChunkCompression::Zlib => compress(data, CompressionFormat::Deflate),
+            ChunkCompression::None => Ok(data.to_vec()),
+            ChunkCompression::Lz4 => Ok(compress_lz4_blocks(data)),
+        }
+    }
+}
+
+/// An Anvil (`.mca`) or McRegion (`.mcr`) file, holding up to 32×32 chunks.
+///
+/// The file starts with two 4 KiB tables: the location of each chunk, as a sector offset and
+/// count, followed by the time each chunk was last saved. Chunks are read on demand, so
+/// opening a region only reads the tables.
+///
+/// Chunks too large for 255 sectors are stored in separate `c.<x>.<z>.mcc` files next to
+/// the region. These are only read and written when the region knows where it lives, either
+/// through [`RegionFile::open_path`] or [`RegionFile::external_chunks`].
+#[derive(Debug)]
+pub struct RegionFile<S> {
+    inner: S,
+    locations: Vec<u32>,
+    timestamps: Vec<u32>,
+    external: Option<(PathBuf, RegionPos)>,
+}
+
+impl RegionFile<File> {
+    /// Opens a region file for reading and writing. When the file is named like
+    /// `r.<x>.<z>.mca`, external chunk files are looked up in the same directory.
+    pub fn open_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path: &Path = path.as_ref();
+        let region: RegionFile<File> =
+            RegionFile::open(OpenOptions::new().read(true).write(true).open(path)?)?;
+        let position: Option<RegionPos> = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(parse_region_name);
+        Ok(match position {
+            Some(position) => {
+                let directory: &Path = path.parent().unwrap_or(Path::new("."));
+                region.external_chunks(directory, position)
+            }
+            None => region,
+        })
+    }
+}
+
+/// Parses a region position from a file name such as `r.-1.2.mca`.
+fn parse_region_name(name: &str) -> Option<RegionPos> {
+    let mut parts = name.split('.');
+    let (Some("r"), Some(x), Some(z), Some("mca" | "mcr"), None) = (
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+    ) else {
+        return None;
+    };
+    Some(RegionPos::new(x.parse().ok()?, z.parse().ok()?))
+}
+
+impl<S: Read + Seek> RegionFile<S> {
+    /// Reads the header tables. An empty stream is treated as a region with no chunks.
+    pub fn open(mut inner: S) -> Result<Self> {
+        let mut header: Vec<u8> = Vec::with_capacity(SECTOR_SIZE * 2);
+        inner.seek(SeekFrom::Start(0))?;
+        (&mut inner)
+            .take(SECTOR_SIZE as u64 * 2)
+            .read_to_end(&mut header)?;
+        if !header.is_empty() && header.len() < SECTOR_SIZE * 2 {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "Region file is shorter than its header",
+            ));
+        }
+        header.resize(SECTOR_SIZE * 2, 0);
+        let table = |range: &[u8]| -> Vec<u32> {
+            range
+                .chunks_exact(4)
+                .map(|entry| u32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]]))
+                .collect()
+        };
+        Ok(RegionFile {
+            locations: table(&header[..SECTOR_SIZE]),
+            timestamps: table(&header[SECTOR_SIZE..]),
+            inner,
+            external: None,
+        })
+    }
+
+    /// Enables external chunk files, stored in `directory` for the region at `position`.
+    pub fn external_chunks<P: Into<PathBuf>>(mut self, directory: P, position: RegionPos) -> Self {
+        self.external = Some((directory.into(), position));
+        self
+    }
+
+    /// The path of a chunk's external file, if external files are enabled.
+    fn external_path(&self, x: i32, z: i32) -> Result<PathBuf> {
+        let Some((directory, region)) = &self.external else {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                format!(
+                    "Chunk ({}, {}) needs an external .mcc file, but the region's location is unknown",
+                    x, z
+                ),
+            ));
+        };
+        let (local_x, local_z): (u8, u8) = ChunkPos::new(x, z).local_in_region();
+        let chunk: ChunkPos = region.chunk_at(local_x, local_z);
+        Ok(directory.join(format!("c.{}.{}.mcc", chunk.x, chunk.z)))
+    }
+
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Whether the chunk has been generated and saved. Chunk coordinates may be absolute or
+    /// local to the region; only their position within the region is used.
+    pub fn has_chunk(&self, x: i32, z: i32) -> bool {
+        self.locations[index(x, z)] != 0
+    }
+
+    /// When the chunk was last saved, in seconds since the Unix epoch, or zero if it never was.
+    pub fn timestamp(&self, x: i32, z: i32) -> u32 {
+        self.timestamps[index(x, z)]
+    }
+
+    /// The chunks present in the region, as local coordinates in `0..32`.
+    pub fn chunk_positions(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        (0..REGION_CHUNKS)
+            .filter(|index| self.locations[*index] != 0)
+            .map(|index| ((index % 32) as i32, (index / 32) as i32))
+    }
+
+    /// Reads a chunk's compressed payload along with its compression type, or `None` if
+    /// the chunk isn't present.
+    pub fn raw_chunk(&mut self, x: i32, z: i32) -> Result<Option<(ChunkCompression, Vec<u8>)>> {
+        let location: u32 = self.locations[index(x, z)];
+        if location == 0 {
+            return Ok(None);
+        }
+        let offset: u64 = u64::from(location >> 8) * SECTOR_SIZE as u64;
+        let sectors: usize = (location & 0xff) as usize;
+        self.inner.seek(SeekFrom::Start(offset))?;
+        let mut prefix: [u8; 5] = [0; 5];
+        self.inner.read_exact(&mut prefix)?;
+        let length: usize =
+            u32::from_be_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize;
+        if length == 0 || length + 4 > sectors * SECTOR_SIZE {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Chunk ({}, {}) has an invalid length of {} bytes",
+                    x, z, length
+                ),
+            ));
+        }
+        let compression: ChunkCompression = ChunkCompression::from_id(prefix[4] & !EXTERNAL_FLAG)?;
+        if prefix[4] & EXTERNAL_FLAG != 0 {
+            return Ok(Some((compression, fs::read(self.external_path(x, z)?)?)));
+        }
+        let mut data: Vec<u8> = vec![0; length - 1];
+        self.inner.read_exact(&mut data)?;
+        Ok(Some((compression, data)))
+    }
+
+    /// Reads and decodes a chunk, or returns `None` if it isn't present.
+    pub fn chunk(&mut self, x: i32, z: i32) -> Result<Option<Tag>> {
+        let Some((compression, data)) = self.raw_chunk(x, z)? else {
+            return Ok(None);
+        };
+        Ok(Some(read(&compression.decompress(&data)?)?.tag))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<S: Read + Seek> RegionFile<S> {
+    /// Reads every chunk's payload, then decompresses and decodes them in parallel. Chunks
+    /// are yielded as local coordinates in `0..32` along with their tag, in no particular
+    /// order.
+    ///
+    /// Reading from the file happens up front on the calling thread, so an I/O error is
+    /// returned before any decoding starts. Decoding errors are reported per chunk.
+    pub fn par_chunks(&mut self) -> Result<impl ParallelIterator<Item = Result<(i32, i32, Tag)>>> {
+        let positions: Vec<(i32, i32)> = self.chunk_positions().collect();
+        let mut chunks: Vec<(i32, i32, ChunkCompression, Vec<u8>)> = Vec::new();
+        for (x, z) in positions {
+            if let Some((compression, data)) = self.raw_chunk(x, z)? {
+                chunks.push((x, z, compression, data));
+            }
+        }
+        Ok(chunks.into_par_iter().map(|(x, z, compression, data)| {
+            Ok((x, z, read(&compression.decompress(&data)?)?.tag))
+        }))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<S: Read + Write + Seek> RegionFile<S> {
+    /// Encodes and compresses many chunks in parallel, then stores them one at a time on the
+    /// calling thread with the same guarantees as [`RegionFile::set_raw_chunk`].
+    ///
+    /// Chunks are committed in header order, by `z` then `x`, so the resulting file doesn't
+    /// depend on how the work was scheduled. If any chunk fails to encode, nothing is written.
+    pub fn par_set_chunks(
+        &mut self,
+        chunks: &[(i32, i32, Tag)],
+        compression: ChunkCompression,
+    ) -> Result<()> {
+        let mut encoded: Vec<(usize, i32, i32, Vec<u8>)> = chunks
+            .par_iter()
+            .map(|(x, z, tag)| {
+                Ok((
+                    index(*x, *z),
+                    *x,
+                    *z,
+                    compression.compress(&write(tag, "")?)?,
+                ))
+            })
+            .collect::<Result<_>>()?;
+        encoded.sort_by_key(|(index, ..)| *index);
+        let timestamp: u32 = now();
+        for (_, x, z, data) in encoded {
+            self.set_raw_chunk(x, z, compression, &data, timestamp)?;
+        }
+        Ok(())
+    }
+}
+
+impl<S: Read + Write + Seek> RegionFile<S> {
+    /// Encodes and stores a chunk with zlib compression, as vanilla does by default.
+    pub fn set_chunk(&mut self, x: i32, z: i32, tag: &Tag) -> Result<()> {
+        self.set_chunk_with(x, z, tag, ChunkCompression::Zlib)
+    }
+
+    /// Encodes and stores a chunk with the given compression, stamping it with the current
+    /// time.
+    ///
+    /// The chunk is written to free sectors that don't overlap its previous copy, and the
+    /// header is only updated once the data is in place. If writing is interrupted, the
+    /// region still holds the old chunk rather than a torn one. The sectors of the old copy
+    /// become free for later writes.
+    pub fn set_chunk_with(
+        &mut self,
+        x: i32,
+        z: i32,
+        tag: &Tag,
+        compression: ChunkCompression,
+    ) -> Result<()> {
+        let data: Vec<u8> = compression.compress(&write(tag, "")?)?;
+        self.set_raw_chunk(x, z, compression, &data, now())
+    }
+
+    /// Encodes a chunk with every candidate and stores the smallest result, returning the
+    /// candidate that was picked.
+    ///
+    /// Only formats with a region compression byte can be used: [`CompressionFormat::Deflate`]
+    /// is stored as zlib and [`CompressionFormat::Gzip`] as gzip. Any other format is an error.
+    pub fn set_chunk_best(
+        &mut self,
+        x: i32,
+        z: i32,
+        tag: &Tag,
+        candidates: &[CompressionCandidate],
+    ) -> Result<CompressionCandidate> {
+        for candidate in candidates {
+            chunk_compression(candidate.format)?;
+        }
+        let best: CompressedOutput = compress_best(&write(tag, "")?, candidates)?;
+        self.set_raw_chunk(
+            x,
+            z,
+            chunk_compression(best.candidate.format)?,
+            &best.data,
+            now(),
+        )?;
+        Ok(best.candidate)
+    }
+
+    /// Stores an already-compressed chunk payload with the given timestamp.
+    ///
+    /// Payloads over 255 sectors go to an external `.mcc` file, leaving a one-sector stub in
+    /// the region. A stale external file is deleted once the chunk fits in the region again.
+    pub fn set_raw_chunk(
+        &mut self,
+        x: i32,
+        z: i32,
+        compression: ChunkCompression,
+        data: &[u8],
+        timestamp: u32,
+    ) -> Result<()> {
+        let external: bool = (data.len() + 5).div_ceil(SECTOR_SIZE) > 0xff;
+        let mut sector_data: Vec<u8> = Vec::new();
+        if external {
+            let path: PathBuf = self.external_path(x, z)?;
+            let temporary: PathBuf = path.with_extension("mcc.tmp");
+            fs::write(&temporary, data)?;
+            fs::rename(&temporary, &path)?;
+            sector_data.extend_from_slice(&1u32.to_be_bytes());
+            sector_data.push(compression.id() | EXTERNAL_FLAG);
+        } else {
+            sector_data.extend_from_slice(&(data.len() as u32 + 1).to_be_bytes());
+            sector_data.push(compression.id());
+            sector_data.extend_from_slice(data);
+        }
+        let sectors: usize = sector_data.len().div_ceil(SECTOR_SIZE);
+        sector_data.resize(sectors * SECTOR_SIZE, 0);
+        let offset: usize = self.allocate(sectors);
+        self.inner
+            .seek(SeekFrom::Start((offset * SECTOR_SIZE) as u64))?;
+        self.inner.write_all(&sector_data)?;
+        self.inner.flush()?;
+        self.write_entry(
+            index(x, z),
+            (offset as u32) << 8 | sectors as u32,
+            timestamp,
+        )?;
+        if !external {
+            if let Ok(path) = self.external_path(x, z) {
+                match fs::remove_file(path) {
+                    Err(error) if error.kind() != ErrorKind::NotFound => return Err(error),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes a chunk from the header. Its sectors become free for later writes.
+    pub fn remove_chunk(&mut self, x: i32, z: i32) -> Result<()> {
+        self.write_entry(index(x, z), 0, 0)
+    }
+
+    /// Finds the first run of free sectors long enough, or the end of the file. Sectors still
+    /// in use, including those of the chunk being replaced, are never reused.
+    fn allocate(&self, sectors: usize) -> usize {
+        let mut used: Vec<bool> = vec![true, true];
+        for location in self.locations.iter().filter(|location| **location != 0) {
+            let start: usize = (location >> 8) as usize;
+            let end: usize = start + (location & 0xff) as usize;
+            if used.len() < end {
+                used.resize(end, false);
+            }
+            used[start..end].fill(true);
+        }
+        let mut run: usize = 0;
+        for (sector, in_use) in used.iter().enumerate() {
+            run = if *in_use { 0 } else { run + 1 };
+            if run == sectors {
+                return sector + 1 - sectors;
+            }
+        }
+        used.len() - run
+    }
+
+    fn write_entry(&mut self, index: usize, location: u32, timestamp: u32) -> Result<()> {
+        self.inner.seek(SeekFrom::Start((index * 4) as u64))?;
+        self.inner.write_all(&location.to_be_bytes())?;
+        self.inner
+            .seek(SeekFrom::Start((SECTOR_SIZE + index * 4) as u64))?;
+        self.inner.write_all(&timestamp.to_be_bytes())?;
+        self.inner.flush()?;
+        self.locations[index] = location;
+        self.timestamps[index] = timestamp;
+        Ok(())
+    }
+}
+
+/// The current time in seconds since the Unix epoch, as stored in the timestamp table.
+fn now() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs() as u32)
+}
+
+/// The region compression byte matching a general compression format.
+fn chunk_compression(format: CompressionFormat) -> Result<ChunkCompression> {
+    match format {
+        CompressionFormat::Deflate => Ok(ChunkCompression::Zlib),
+        CompressionFormat::Gzip => Ok(ChunkCompression::Gzip),
+        format => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{:?} can't be stored in a region file", format),
+        )),
+    }
+}
+
+/// A stream that can be cut short, which [`RegionFile::compact`] needs to drop free sectors
+/// from the end of the file.
+pub trait Truncate {
+    fn truncate(&mut self, length: u64) -> Result<()>;
+}
+
+impl Truncate for File {
+    fn truncate(&mut self, length: u64) -> Result<()> {
+        self.set_len(length)
+    }
+}
+
+impl Truncate for Cursor<Vec<u8>> {
+    fn truncate(&mut self, length: u64) -> Result<()> {
+        self.get_mut().truncate(length as usize);
+        Ok(())
+    }
+}
+
+impl<S: Read + Write + Seek + Truncate> RegionFile<S> {
+    /// Moves every chunk towards the start of the file to close the gaps left behind by
+    /// rewritten and removed chunks, then truncates the file. Returns the number of bytes
+    /// reclaimed.
+    ///
+    /// Chunks are moved one at a time with the same guarantees as [`RegionFile::set_raw_chunk`].
+    /// A chunk whose new place overlaps its old one is first copied to free space, so an
+    /// interrupted compaction never leaves a torn chunk behind.
+    pub fn compact(&mut self) -> Result<u64> {
+        let length: u64 = self.inner.seek(SeekFrom::End(0))?;
+        if length == 0 {
+            return Ok(0);
+        }
+        let mut entries: Vec<(usize, usize, usize)> = self
+            .locations
+            .iter()
+            .enumerate()
+            .filter(|(_, location)| **location != 0)
+            .map(|(index, location)| ((location >> 8) as usize, (location & 0xff) as usize, index))
+            .collect();
+        entries.sort_unstable();
+        let mut next: usize = 2;
+        for (offset, sectors, index) in entries {
+            if offset > next {
+                let mut data: Vec<u8> = vec![0; sectors * SECTOR_SIZE];
+                self.inner
+                    .seek(SeekFrom::Start((offset * SECTOR_SIZE) as u64))?;
+                self.inner.read_exact(&mut data)?;
+                if next + sectors > offset {
+                    let temporary: usize = self.allocate(sectors);
+                    self.move_chunk(index, &data, temporary)?;
+                }
+                self.move_chunk(index, &data, next)?;
+            }
+            next += sectors;
+        }
+        let compacted: u64 = (next * SECTOR_SIZE) as u64;
+        self.inner.truncate(compacted)?;
+        Ok(length.saturating_sub(compacted))
+    }
+
+    /// Writes a chunk's sectors at a new offset and points its header entry there.
+    fn move_chunk(&mut self, index: usize, data: &[u8], offset: usize) -> Result<()> {
+        self.inner
+            .seek(SeekFrom::Start((offset * SECTOR_SIZE) as u64))?;
+        self.inner.write_all(data)?;
+        self.inner.flush()?;
+        let sectors: usize = data.len() / SECTOR_SIZE;
+        self.write_entry(
+            index,
+            (offset as u32) << 8 | sectors as u32,
+            self.timestamps[index],
+        )
+    }
+}
+
+/// The header table index of a chunk.
+fn index(x: i32, z: i32) -> usize {
+    ChunkPos::new(x, z).region_index()
+}
+
+/// Magic bytes opening each block of an LZ4 block stream.
+const LZ4_BLOCK_MAGIC: &[u8; 8] = b"LZ4Block";
+
+/// Decodes the block stream written by lz4-java's `LZ4BlockOutputStream`: a series of blocks,
+/// each a magic, a method token, little-endian compressed and original lengths and a
+/// checksum, ending with an empty block.
+fn decompress_lz4_blocks(mut data: &[u8]) -> Result<Vec<u8>> {
+    let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_string());
+    let mut output: Vec<u8> = Vec::new();
+    loop {
+        let Some((header, rest)) = data.split_first_chunk::<21>() else {
+            return Err(invalid("Truncated LZ4 block header"));
+        };
+        if &header[..8] != LZ4_BLOCK_MAGIC {
+            return Err(invalid("Missing LZ4 block magic"));
+        }
+        let length = |at: usize| -> usize {
+            u32::from_le_bytes([header[at], header[at + 1], header[at + 2], header[at + 3]])
+                as usize
+        };
+        let (compressed, original): (usize, usize) = (length(9), length(13));
+        if original == 0 {
+            return Ok(output);
+        }
+        let block: &[u8] = rest
+            .get(..compressed)
+            .ok_or_else(|| invalid("Truncated LZ4 block"))?;
+        match header[8] & 0xf0 {
+            0x10 => output.extend_from_slice(block),
+            0x20 => output.extend_from_slice(
+                &lz4_flex::block::decompress(block, original)
+                    .map_err(|error| Error::new(ErrorKind::InvalidData, error))?,
+            ),
+            _ => return Err(invalid("Unknown LZ4 block method")),
+        }
+        data = &rest[compressed..];
+    }
+}
+
+/// Block size used by lz4-java's `LZ4BlockOutputStream` by default.
+const LZ4_BLOCK_SIZE: usize = 1 << 16;
+
+/// Encodes data in the LZ4 block stream format, storing blocks raw when LZ4 doesn't shrink
+/// them.
+fn compress_lz4_blocks(data: &[u8]) -> Vec<u8> {
+    // The low bits of the token record the block size as a power of two above 1 KiB.
+    let level: u8 = (LZ4_BLOCK_SIZE.trailing_zeros() - 10) as u8;
+    let mut output: Vec<u8> = Vec::with_capacity(data.len() / 2 + 64);
+    let mut push_block = |token: u8, block: &[u8], original: &[u8]| {
+        output.extend_from_slice(LZ4_BLOCK_MAGIC);
+        output.push(token | level);
+        output.extend_from_slice(&(block.len() as u32).to_le_bytes());
+        output.extend_from_slice(&(original.len() as u32).to_le_bytes());
+        let checksum: u32 = match original {
+            [] => 0,
+            original => xxhash32(original, 0x9747_b28c) & 0x0fff_ffff,
+        };
+        output.extend_from_slice(&checksum.to_le_bytes());
+        output.extend_from_slice(block);
+    };
+    for original in data.chunks(LZ4_BLOCK_SIZE) {
+        let compressed: Vec<u8> = lz4_flex::block::compress(original);
+        if compressed.len() < original.len() {
+            push_block(0x20, &compressed, original);
+        } else {
+            push_block(0x10, original, original);
+        }
+    }
+    push_block(0x10, &[], &[]);
+    output
+}
+
+/// The 32-bit xxHash of `data`, which lz4-java uses for block checksums.
+fn xxhash32(data: &[u8], seed: u32) -> u32 {
+    const PRIME_1: u32 = 2_654_435_761;
+    const PRIME_2: u32 = 2_246_822_519;
+    const PRIME_3: u32 = 3_266_489_917;
+    const PRIME_4: u32 = 668_265_263;
+    const PRIME_5: u32 = 374_761_393;
+    let lane = |bytes: &[u8]| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let round = |acc: u32, bytes: &[u8]| {
+        acc.wrapping_add(lane(bytes).wrapping_mul(PRIME_2))
+            .rotate_left(13)
+            .wrapping_mul(PRIME_1)
+    };
+    let stripes: usize = data.len() / 16 * 16;
+    let mut hash: u32 = if data.len() >= 16 {
+        let mut acc: [u32; 4] = [
+            seed.wrapping_add(PRIME_1).wrapping_add(PRIME_2),
+            seed.wrapping_add(PRIME_2),
+            seed,
+            seed.wrapping_sub(PRIME_1),
+        ];
+        for stripe in data[..stripes].chunks_exact(16) {
+            for (index, acc) in acc.iter_mut().enumerate() {
+                *acc = round(*acc, &stripe[index * 4..]);
+            }
+        }
+        acc[0]
+            .rotate_left(1)
+            .wrapping_add(acc[1].rotate_left(7))
+            .wrapping_add(acc[2].rotate_left(12))
+            .wrapping_add(acc[3].rotate_left(18))
+    } else {
+        seed.wrapping_add(PRIME_5)
+    };
+    hash = hash.wrapping_add(data.len() as u32);
+    let mut rest: &[u8] = &data[stripes..];
+    while rest.len() >= 4 {
+        hash = hash
+            .wrapping_add(lane(rest).wrapping_mul(PRIME_3))
+            .rotate_left(17)
+            .wrapping_mul(PRIME_4);
+        rest = &rest[4..];
+    }
+    for byte in rest {
+        hash = hash
+            .wrapping_add(u32::from(*byte).wrapping_mul(PRIME_5))
+            .rotate_left(11)
+            .wrapping_mul(PRIME_1);
+    }
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(PRIME_2);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(PRIME_3);
+    hash ^ hash >> 16
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nbt, write, CompoundTag};
+
+    #[test]
+    fn test_region_read() {
+        let chunk: Tag = nbt!({ xPos: 1, zPos: 2, Status: "minecraft:full" });
+        let payload: Vec<u8> = write(&chunk, "").unwrap();
+        let mut data: Vec<u8> = vec![0; SECTOR_SIZE * 3];
+        let index: usize = 1 + 2 * 32;
+        data[index * 4..index * 4 + 4].copy_from_slice(&(2 << 8 | 1u32).to_be_bytes());
+        data[SECTOR_SIZE + index * 4..SECTOR_SIZE + index * 4 + 4]
+            .copy_from_slice(&1_700_000_000u32.to_be_bytes());
+        let start: usize = SECTOR_SIZE * 2;
+        data[start..start + 4].copy_from_slice(&(payload.len() as u32 + 1).to_be_bytes());
+        data[start + 4] = ChunkCompression::None.id();
+        data[start + 5..start + 5 + payload.len()].copy_from_slice(&payload);
+
+        let mut region: RegionFile<Cursor<Vec<u8>>> = RegionFile::open(Cursor::new(data)).unwrap();
+        assert!(region.has_chunk(1, 2));
+        assert!(region.has_chunk(33, -30));
+        assert_eq!(region.timestamp(1, 2), 1_700_000_000);
+        assert_eq!(region.chunk_positions().collect::<Vec<_>>(), [(1, 2)]);
+        assert_eq!(region.chunk(1, 2).unwrap(), Some(chunk));
+        assert_eq!(region.chunk(0, 0).unwrap(), None);
+
+        let empty: RegionFile<Cursor<Vec<u8>>> = RegionFile::open(Cursor::new(Vec::new())).unwrap();
+        assert_eq!(empty.chunk_positions().count(), 0);
+        assert!(RegionFile::open(Cursor::new(vec![0; 100])).is_err());
+
+        #[cfg(feature = "compression")]
+        for compression in [ChunkCompression::Gzip, ChunkCompression::Zlib] {
+            let compressed: Vec<u8> = compression.compress(&payload).unwrap();
+            assert_eq!(compression.decompress(&compressed).unwrap(), payload);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_chunks() {
+        let mut region: RegionFile<Cursor<Vec<u8>>> =
+            RegionFile::open(Cursor::new(Vec::new())).unwrap();
+        for index in 0..100 {
+            let (x, z): (i32, i32) = (index % 32, index / 32);
+            region
+                .set_chunk_with(x, z, &nbt!({ xPos: x, zPos: z }), ChunkCompression::Lz4)
+                .unwrap();
+        }
+        let mut chunks: Vec<(i32, i32, Tag)> =
+            region.par_chunks().unwrap().collect::<Result<_>>().unwrap();
+        chunks.sort_by_key(|(x, z, _)| (*z, *x));
+        assert_eq!(chunks.len(), 100);
+        for (x, z, tag) in &chunks {
+            assert_eq!(*tag, nbt!({ xPos: *x, zPos: *z }));
+        }
+
+        // Committing in header order lays the chunks out the same as writing them one by one.
+        let expected: Vec<u32> = region.locations.clone();
+        chunks.reverse();
+        let mut parallel: RegionFile<Cursor<Vec<u8>>> =
+            RegionFile::open(Cursor::new(Vec::new())).unwrap();
+        parallel
+            .par_set_chunks(&chunks, ChunkCompression::Lz4)
+            .unwrap();
+        assert_eq!(parallel.locations, expected);
+        assert_eq!(
+            parallel.chunk(3, 2).unwrap(),
+            Some(nbt!({ xPos: 3, zPos: 2 }))
+        );
+    }
+
+    #[test]
+    fn test_lz4_blocks() {
+        let input: Vec<u8> = b"chunk data ".repeat(100);
+        let compressed: Vec<u8> = lz4_flex::block::compress(&input);
+        let mut stream: Vec<u8> = Vec::new();
+        for (token, block, original) in [
+            (0x20u8 | 6, compressed.as_slice(), input.len()),
+            (0x10, b"raw".as_slice(), 3),
+            (0x10, b"".as_slice(), 0),
+        ] {
+            stream.extend_from_slice(LZ4_BLOCK_MAGIC);
+            stream.push(token);
+            stream.extend_from_slice(&(block.len() as u32).to_le_bytes());
+            stream.extend_from_slice(&(original as u32).to_le_bytes());
+            stream.extend_from_slice(&0u32.to_le_bytes());
+            stream.extend_from_slice(block);
+        }
+        let mut expected: Vec<u8> = input.clone();
+        expected.extend_from_slice(b"raw");
+        assert_eq!(ChunkCompression::Lz4.decompress(&stream).unwrap(), expected);
+        assert!(decompress_lz4_blocks(&stream[..30]).is_err());
+
+        let large: Vec<u8> = (0..200_000u32)
+            .flat_map(|n| (n % 251).to_le_bytes())
+            .collect();
+        let compressed: Vec<u8> = ChunkCompression::Lz4.compress(&large).unwrap();
+        assert_eq!(
+            ChunkCompression::Lz4.decompress(&compressed).unwrap(),
+            large
+        );
+        assert_eq!(xxhash32(b"", 0), 0x02cc_5d05);
+        assert_eq!(xxhash32(b"a", 0), 0x550d_7456);
+        assert_eq!(
+            xxhash32(b"Nobody inspects the spammish repetition", 0),
+            0xe229_3b2f
+        );
+    }
+
+    #[test]
+    fn test_region_write() {
+        let mut region: RegionFile<Cursor<Vec<u8>>> =
+            RegionFile::open(Cursor::new(Vec::new())).unwrap();
+        let small: Tag = nbt!({ xPos: 0, zPos: 0 });
+        let large: Tag = Tag::Compound(CompoundTag::from([(
+            "data".to_string(),
+            Tag::ByteArray(vec![1; 10_000]),
+        )]));
+        region
+            .set_chunk_with(0, 0, &small, ChunkCompression::None)
+            .unwrap();
+        region
+            .set_chunk_with(1, 0, &small, ChunkCompression::Lz4)
+            .unwrap();
+        assert_eq!(region.locations[0], 2 << 8 | 1);
+        assert_eq!(region.locations[1], 3 << 8 | 1);
+        assert!(region.timestamp(0, 0) > 0);
+
+        // Growing a chunk moves it past the end; its old sector is reused afterwards.
+        region
+            .set_chunk_with(0, 0, &large, ChunkCompression::None)
+            .unwrap();
+        assert_eq!(region.locations[0], 4 << 8 | 3);
+        region
+            .set_chunk_with(2, 0, &small, ChunkCompression::Lz4)
+            .unwrap();
+        assert_eq!(region.locations[2], 2 << 8 | 1);
+        region.remove_chunk(1, 0).unwrap();
+
+        let oversized: Vec<u8> = vec![0; 256 * SECTOR_SIZE];
+        let error: Error = region
+            .set_raw_chunk(3, 0, ChunkCompression::None, &oversized, 0)
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Unsupported);
+
+        let data: Vec<u8> = region.into_inner().into_inner();
+        assert_eq!(data.len(), 7 * SECTOR_SIZE);
+        let mut reopened: RegionFile<Cursor<Vec<u8>>> =
+            RegionFile::open(Cursor::new(data)).unwrap();
+        assert_eq!(reopened.chunk(0, 0).unwrap(), Some(large.clone()));
+        assert_eq!(reopened.chunk(1, 0).unwrap(), None);
+        assert_eq!(reopened.chunk(2, 0).unwrap(), Some(small.clone()));
+
+        // The large chunk overlaps the free sector in front of it, so it moves twice.
+        assert_eq!(reopened.compact().unwrap(), SECTOR_SIZE as u64);
+        assert_eq!(reopened.locations[0], 3 << 8 | 3);
+        assert_eq!(reopened.compact().unwrap(), 0);
+        let data: Vec<u8> = reopened.into_inner().into_inner();
+        assert_eq!(data.len(), 6 * SECTOR_SIZE);
+        let mut reopened: RegionFile<Cursor<Vec<u8>>> =
+            RegionFile::open(Cursor::new(data)).unwrap();
+        assert_eq!(reopened.chunk(0, 0).unwrap(), Some(large));
+        assert_eq!(reopened.chunk(2, 0).unwrap(), Some(small));
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_set_chunk_best() {
+        let mut region: RegionFile<Cursor<Vec<u8>>> =
+            RegionFile::open(Cursor::new(Vec::new())).unwrap();
+        let tag: Tag = nbt!({ data: [B; 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0] });
+        let candidates: [CompressionCandidate; 2] = [
+            CompressionCandidate::new(CompressionFormat::Gzip, 9),
+            CompressionCandidate::new(CompressionFormat::Deflate, 9),
+        ];
+        let chosen: CompressionCandidate = region.set_chunk_best(0, 0, &tag, &candidates).unwrap();
+        assert_eq!(chosen, candidates[1]);
+        assert_eq!(
+            region.raw_chunk(0, 0).unwrap().unwrap().0,
+            ChunkCompression::Zlib
+        );
+        assert_eq!(region.chunk(0, 0).unwrap(), Some(tag.clone()));
+
+        let lz4: [CompressionCandidate; 1] = [CompressionCandidate::new(CompressionFormat::Lz4, 0)];
+        assert!(region.set_chunk_best(1, 0, &tag, &lz4).is_err());
+        assert!(!region.has_chunk(1, 0));
+    }
+
+    #[test]
+    fn test_external_chunks() {
+        let directory: PathBuf = std::env::temp_dir().join("rust_nbt_test_external_chunks");
+        fs::create_dir_all(&directory).unwrap();
+        let path: PathBuf = directory.join("r.-1.2.mca");
+        File::create(&path).unwrap();
+
+        let mut region: RegionFile<File> = RegionFile::open_path(&path).unwrap();
+        let huge: Tag = Tag::Compound(CompoundTag::from([(
+            "data".to_string(),
+            Tag::LongArray((0..200_000).collect()),
+        )]));
+        region
+            .set_chunk_with(-31, 65, &huge, ChunkCompression::None)
+            .unwrap();
+        let external: PathBuf = directory.join("c.-31.65.mcc");
+        assert!(external.exists());
+        assert_eq!(region.locations[index(-31, 65)] & 0xff, 1);
+
+        let mut reopened: RegionFile<File> = RegionFile::open_path(&path).unwrap();
+        assert_eq!(reopened.chunk(1, 1).unwrap(), Some(huge));
+        reopened
+            .set_chunk_with(1, 1, &nbt!({}), ChunkCompression::None)
+            .unwrap();
+        assert!(!external.exists());
+        assert_eq!(reopened.chunk(1, 1).unwrap(), Some(nbt!({})));
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(parse_region_name("r.-1.2.mca"), Some(RegionPos::new(-1, 2)));
+        assert_eq!(parse_region_name("r.1.mca"), None);
+    }
+}
@@ -0,0 +1,406 @@
+use crate::{RawTag, Tag};
+use std::fmt::{Display, Error, Formatter, Result, Write};
+
+impl Tag {
+    /// Converts this tag to compact SNBT text, as accepted by Minecraft commands.
+    pub fn to_snbt(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Formats the tag as compact SNBT.
+impl Display for Tag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        SnbtFormatter::compact().write(f, self)
+    }
+}
+
+/// Configurable SNBT output, from compact single-line text to indented multi-line dumps.
+#[derive(Debug, Clone)]
+pub struct SnbtFormatter {
+    indent: usize,
+    inline_threshold: usize,
+    quote_all_keys: bool,
+}
+
+impl Default for SnbtFormatter {
+    /// Pretty output with two-space indentation and every non-empty container expanded.
+    fn default() -> Self {
+        SnbtFormatter {
+            indent: 2,
+            inline_threshold: 0,
+            quote_all_keys: false,
+        }
+    }
+}
+
+impl SnbtFormatter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Single-line output without any whitespace, as used by `Display`.
+    pub fn compact() -> Self {
+        SnbtFormatter {
+            indent: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the number of spaces per nesting level. Zero produces compact output.
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Keeps lists, arrays and compounds on one line when their inline form is at most this
+    /// many characters long.
+    pub fn inline_threshold(mut self, inline_threshold: usize) -> Self {
+        self.inline_threshold = inline_threshold;
+        self
+    }
+
+    /// Quotes every compound key, even those that would be valid bare.
+    pub fn quote_all_keys(mut self, quote_all_keys: bool) -> Self {
+        self.quote_all_keys = quote_all_keys;
+        self
+    }
+
+    /// Formats a tag to a new string.
+    pub fn format(&self, tag: &Tag) -> String {
+        let mut output: String = String::new();
+        self.write(&mut output, tag)
+            .expect("Formatting only fails when the sink does");
+        output
+    }
+
+    /// Formats a tag into any `fmt::Write` sink, failing only if the sink does.
+    ///
+    /// Infinite floats are written as out-of-range literals such as `1e39f`, which parse back
+    /// to infinity. NaN has no SNBT literal and is written as `NaNf` or `NaNd`, like Java's
+    /// `Float.toString`.
+    pub fn write<W: Write>(&self, writer: &mut W, tag: &Tag) -> Result {
+        self.write_tag(writer, tag, 0)
+    }
+
+    fn is_pretty(&self) -> bool {
+        self.indent > 0
+    }
+
+    fn write_tag<W: Write>(&self, writer: &mut W, tag: &Tag, depth: usize) -> Result {
+        if let Tag::RawEncoded(raw) = tag {
+            return self.write_tag(writer, &decode_for_display(raw), depth);
+        }
+        if !self.is_pretty() || container_len(tag) == 0 || self.fits_inline(tag) {
+            return self.write_inline(writer, tag);
+        }
+        let entries: Vec<Entry> = match tag {
+            Tag::ByteArray(value) => value
+                .iter()
+                .map(|v| Entry::Text(format!("{}b", v)))
+                .collect(),
+            Tag::IntArray(value) => value.iter().map(|v| Entry::Text(v.to_string())).collect(),
+            Tag::LongArray(value) => value
+                .iter()
+                .map(|v| Entry::Text(format!("{}L", v)))
+                .collect(),
+            Tag::List(value) => value.iter().map(|v| Entry::Tag(None, v)).collect(),
+            Tag::Compound(value) => value.iter().map(|(k, v)| Entry::Tag(Some(k), v)).collect(),
+            _ => return self.write_inline(writer, tag),
+        };
+        let (open, close): (&str, char) = brackets(tag);
+        writer.write_str(open)?;
+        writer.write_char('\n')?;
+        let last: usize = entries.len() - 1;
+        for (index, entry) in entries.into_iter().enumerate() {
+            write!(writer, "{:width$}", "", width = self.indent * (depth + 1))?;
+            match entry {
+                Entry::Text(text) => writer.write_str(&text)?,
+                Entry::Tag(name, value) => {
+                    if let Some(name) = name {
+                        self.write_key(writer, name)?;
+                        writer.write_str(": ")?;
+                    }
+                    self.write_tag(writer, value, depth + 1)?;
+                }
+            }
+            if index < last {
+                writer.write_char(',')?;
+            }
+            writer.write_char('\n')?;
+        }
+        write!(writer, "{:width$}", "", width = self.indent * depth)?;
+        writer.write_char(close)
+    }
+
+    fn fits_inline(&self, tag: &Tag) -> bool {
+        let mut limited: LimitedWriter = LimitedWriter {
+            remaining: self.inline_threshold,
+        };
+        self.inline_threshold > 0 && self.write_inline(&mut limited, tag).is_ok()
+    }
+
+    fn write_inline<W: Write>(&self, writer: &mut W, tag: &Tag) -> Result {
+        let separator: &str = if self.is_pretty() { ", " } else { "," };
+        match tag {
+            Tag::End => Ok(()),
+            Tag::Byte(value) => write!(writer, "{}b", value),
+            Tag::Short(value) => write!(writer, "{}s", value),
+            Tag::Int(value) => write!(writer, "{}", value),
+            Tag::Long(value) => write!(writer, "{}L", value),
+            Tag::Float(value) => write_float(writer, *value, "1e39", "f"),
+            Tag::Double(value) => write_float(writer, *value, "1e309", "d"),
+            Tag::ByteArray(value) => write_array(writer, "B", separator, value, "b"),
+            Tag::String(value) => write_quoted(writer, value),
+            Tag::List(value) => {
+                writer.write_char('[')?;
+                for (index, entry) in value.iter().enumerate() {
+                    if index > 0 {
+                        writer.write_str(separator)?;
+                    }
+                    self.write_inline(writer, entry)?;
+                }
+                writer.write_char(']')
+            }
+            Tag::Compound(value) => {
+                writer.write_char('{')?;
+                for (index, (name, entry)) in value.iter().enumerate() {
+                    if index > 0 {
+                        writer.write_str(separator)?;
+                    }
+                    self.write_key(writer, name)?;
+                    writer.write_str(if self.is_pretty() { ": " } else { ":" })?;
+                    self.write_inline(writer, entry)?;
+                }
+                writer.write_char('}')
+            }
+            Tag::IntArray(value) => write_array(writer, "I", separator, value, ""),
+            Tag::LongArray(value) => write_array(writer, "L", separator, value, "L"),
+            Tag::RawEncoded(raw) => self.write_inline(writer, &decode_for_display(raw)),
+        }
+    }
+
+    fn write_key<W: Write>(&self, writer: &mut W, key: &str) -> Result {
+        if self.quote_all_keys {
+            write_quoted(writer, key)
+        } else {
+            write_key(writer, key)
+        }
+    }
+}
+
+/// A line inside an expanded container: either a pre-formatted array element or a child tag.
+enum Entry<'a> {
+    Text(String),
+    Tag(Option<&'a String>, &'a Tag),
+}
+
+/// A sink that fails once more than `remaining` bytes are written, used to measure whether a
+/// container fits inline without formatting all of it.
+struct LimitedWriter {
+    remaining: usize,
+}
+
+impl Write for LimitedWriter {
+    fn write_str(&mut self, s: &str) -> Result {
+        self.remaining = self.remaining.checked_sub(s.len()).ok_or(Error)?;
+        Ok(())
+    }
+}
+
+fn container_len(tag: &Tag) -> usize {
+    match tag {
+        Tag::ByteArray(value) => value.len(),
+        Tag::List(value) => value.len(),
+        Tag::Compound(value) => value.len(),
+        Tag::IntArray(value) => value.len(),
+        Tag::LongArray(value) => value.len(),
+        _ => 0,
+    }
+}
+
+fn brackets(tag: &Tag) -> (&'static str, char) {
+    match tag {
+        Tag::ByteArray(_) => ("[B;", ']'),
+        Tag::IntArray(_) => ("[I;", ']'),
+        Tag::LongArray(_) => ("[L;", ']'),
+        Tag::Compound(_) => ("{", '}'),
+        _ => ("[", ']'),
+    }
+}
+
+/// Decodes a raw payload for display, replacing invalid strings. A payload that still can't
+/// be decoded is shown as its bytes, so formatting never fails on it.
+fn decode_for_display(raw: &RawTag) -> Tag {
+    raw.decode_lossy()
+        .unwrap_or_else(|_| Tag::ByteArray(raw.payload().iter().map(|byte| *byte as i8).collect()))
+}
+
+/// Writes a float with its suffix, spelling infinity as a literal too large for the type.
+fn write_float<W: Write, T: Display + Copy + Into<f64>>(
+    writer: &mut W,
+    value: T,
+    overflow: &str,
+    suffix: &str,
+) -> Result {
+    let wide: f64 = value.into();
+    if wide.is_nan() {
+        return write!(writer, "NaN{}", suffix);
+    }
+    if wide.is_infinite() {
+        let sign: &str = if wide < 0.0 { "-" } else { "" };
+        return write!(writer, "{}{}{}", sign, overflow, suffix);
+    }
+    write!(writer, "{}{}", value, suffix)
+}
+
+fn write_array<W: Write, T: Display>(
+    writer: &mut W,
+    prefix: &str,
+    separator: &str,
+    entries: &[T],
+    suffix: &str,
+) -> Result {
+    write!(writer, "[{};", prefix)?;
+    for (index, entry) in entries.iter().enumerate() {
+        if index > 0 {
+            writer.write_str(separator)?;
+        }
+        write!(writer, "{}{}", entry, suffix)?;
+    }
+    writer.write_char(']')
+}
+
+/// Writes a compound key, quoting it only when it contains characters SNBT doesn't allow bare.
+pub(crate) fn write_key<W: Write>(writer: &mut W, key: &str) -> Result {
+    if is_bare_key(key) {
+        writer.write_str(key)
+    } else {
+        write_quoted(writer, key)
+    }
+}
+
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+'))
+}
+
+/// Writes a quoted string, preferring double quotes unless the value contains them and
+/// single quotes would need less escaping.
+pub(crate) fn write_quoted<W: Write>(writer: &mut W, value: &str) -> Result {
+    let quote: char = if value.contains('"') && !value.contains('\'') {
+        '\''
+    } else {
+        '"'
+    };
+    writer.write_char(quote)?;
+    for c in value.chars() {
+        match c {
+            '\\' => writer.write_str("\\\\")?,
+            '\u{8}' => writer.write_str("\\b")?,
+            '\u{c}' => writer.write_str("\\f")?,
+            '\n' => writer.write_str("\\n")?,
+            '\r' => writer.write_str("\\r")?,
+            '\t' => writer.write_str("\\t")?,
+            c if c == quote => {
+                writer.write_char('\\')?;
+                writer.write_char(c)?;
+            }
+            c => writer.write_char(c)?,
+        }
+    }
+    writer.write_char(quote)
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CompoundTag;
+    use indexmap::IndexMap;
+
+    #[test]
+    fn test_to_snbt() {
+        let mut compound: CompoundTag = IndexMap::new();
+        compound.insert("byte".to_string(), Tag::Byte(-1));
+        compound.insert("short".to_string(), Tag::Short(2));
+        compound.insert("int".to_string(), Tag::Int(3));
+        compound.insert("long".to_string(), Tag::Long(4));
+        compound.insert("float".to_string(), Tag::Float(0.5));
+        compound.insert("double".to_string(), Tag::Double(1.0));
+        compound.insert("bytes".to_string(), Tag::ByteArray(vec![1, 2]));
+        compound.insert("ints".to_string(), Tag::IntArray(vec![]));
+        compound.insert("longs".to_string(), Tag::LongArray(vec![5]));
+        compound.insert(
+            "list".to_string(),
+            Tag::List(vec![
+                Tag::String("a".to_string()),
+                Tag::String("b".to_string()),
+            ]),
+        );
+        compound.insert("nested key".to_string(), Tag::Compound(IndexMap::new()));
+        let tag: Tag = Tag::Compound(compound);
+        assert_eq!(
+            tag.to_snbt(),
+            r#"{byte:-1b,short:2s,int:3,long:4L,float:0.5f,double:1d,bytes:[B;1b,2b],ints:[I;],longs:[L;5L],list:["a","b"],"nested key":{}}"#
+        );
+
+        let strings: Vec<&str> = vec!["\"This shouldn't error", "\"", "'", "\nline", "\\"];
+        let escaped: Vec<String> = strings
+            .into_iter()
+            .map(|value| Tag::String(value.to_string()).to_snbt())
+            .collect();
+        assert_eq!(
+            escaped,
+            [
+                r#""\"This shouldn't error""#,
+                r#"'"'"#,
+                r#""'""#,
+                r#""\nline""#,
+                r#""\\""#
+            ]
+        );
+
+        assert_eq!(Tag::Float(f32::INFINITY).to_snbt(), "1e39f");
+        assert_eq!(Tag::Double(f64::NEG_INFINITY).to_snbt(), "-1e309d");
+        assert_eq!("1e39".parse::<f32>().unwrap(), f32::INFINITY);
+        let nan: Tag = Tag::List(vec![Tag::Double(f64::NAN), Tag::Double(-f64::NAN)]);
+        assert_eq!(nan.to_snbt(), "[NaNd,NaNd]");
+        assert_eq!(Tag::Float(f32::NAN).to_snbt(), "NaNf");
+        let broken: Tag =
+            Tag::RawEncoded(RawTag::from_parts_unchecked(crate::TagID::Int, vec![1, 2]));
+        assert_eq!(broken.to_snbt(), "[B;1b,2b]");
+    }
+
+    #[test]
+    fn test_snbt_formatter() {
+        let mut compound: CompoundTag = IndexMap::new();
+        compound.insert(
+            "Pos".to_string(),
+            Tag::List(vec![Tag::Double(1.0), Tag::Double(2.0)]),
+        );
+        compound.insert("Empty".to_string(), Tag::Compound(IndexMap::new()));
+        compound.insert("Ids".to_string(), Tag::IntArray(vec![1, 2]));
+        let tag: Tag = Tag::Compound(compound);
+
+        assert_eq!(
+            SnbtFormatter::new().format(&tag),
+            "{\n  Pos: [\n    1d,\n    2d\n  ],\n  Empty: {},\n  Ids: [I;\n    1,\n    2\n  ]\n}"
+        );
+        assert_eq!(
+            SnbtFormatter::new()
+                .indent(4)
+                .inline_threshold(12)
+                .quote_all_keys(true)
+                .format(&tag),
+            "{\n    \"Pos\": [1d, 2d],\n    \"Empty\": {},\n    \"Ids\": [I;1, 2]\n}"
+        );
+        assert_eq!(
+            SnbtFormatter::new().inline_threshold(100).format(&tag),
+            "{Pos: [1d, 2d], Empty: {}, Ids: [I;1, 2]}"
+        );
+        assert_eq!(SnbtFormatter::compact().format(&tag), tag.to_snbt());
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{
+    read_list_header, read_name, read_tag, read_tag_id, PathSegment, ReadOptions, RootName, Tag,
+    TagID,
+};
+use indexmap::IndexMap;
+use std::io::{Cursor, Error, ErrorKind, Result};
+use std::ops::Range;
+
+/// A tag along with the byte range its payload occupied in the input.
+///
+/// Spans cover the payload only, not the tag ID or name in front of it, so a value can be
+/// overwritten in place when its replacement has the same encoded length.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedTag {
+    pub span: Range<usize>,
+    pub value: SpannedValue,
+}
+
+/// The payload of a [`SpannedTag`]. Only lists and compounds record spans for their children.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpannedValue {
+    Scalar(Tag),
+    List(TagID, Vec<SpannedTag>),
+    Compound(IndexMap<String, SpannedTag>),
+}
+
+impl SpannedTag {
+    /// Converts back into a plain tag, dropping the spans.
+    pub fn to_tag(&self) -> Tag {
+        match &self.value {
+            SpannedValue::Scalar(tag) => tag.clone(),
+            SpannedValue::List(_, entries) => {
+                Tag::List(entries.iter().map(SpannedTag::to_tag).collect())
+            }
+            SpannedValue::Compound(entries) => Tag::Compound(
+                entries
+                    .iter()
+                    .map(|(key, entry)| (key.clone(), entry.to_tag()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Looks up a nested tag by path. See [`crate::parse_path`] for the syntax.
+    pub fn get_path(&self, path: &str) -> Option<&SpannedTag> {
+        let mut current: &SpannedTag = self;
+        for segment in crate::parse_path(path).ok()? {
+            current = match (&current.value, segment) {
+                (SpannedValue::Compound(entries), PathSegment::Key(key)) => entries.get(&key)?,
+                (SpannedValue::List(_, entries), PathSegment::Index(index)) => {
+                    entries.get(index)?
+                }
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+}
+
+/// Reads an NBT file from a byte slice, recording where each tag's payload was found.
+/// Returns the root name along with the root tag.
+pub fn read_spanned(data: &[u8], options: &ReadOptions) -> Result<(String, SpannedTag)> {
+    let mut cursor: Cursor<&[u8]> = Cursor::new(data);
+    let root_tag_id: TagID = read_tag_id(&mut cursor)?;
+    let root_name: String = match options.root_name {
+        RootName::Named => read_name(&mut cursor, options)?,
+        RootName::Omitted => String::new(),
+    };
+    let tag: SpannedTag = read_spanned_tag(&mut cursor, root_tag_id, options, 1)?;
+    Ok((root_name, tag))
+}
+
+fn read_spanned_tag(
+    cursor: &mut Cursor<&[u8]>,
+    tag_id: TagID,
+    options: &ReadOptions,
+    depth: usize,
+) -> Result<SpannedTag> {
+    let start: usize = cursor.position() as usize;
+    let value: SpannedValue = match tag_id {
+        TagID::List | TagID::Compound if depth > options.max_depth => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Nesting exceeds the depth limit of {}", options.max_depth),
+            ));
+        }
+        TagID::List => {
+            let (element_id, length): (TagID, usize) = read_list_header(cursor, options)?;
+            let mut entries: Vec<SpannedTag> = Vec::new();
+            for _ in 0..length {
+                entries.push(read_spanned_tag(cursor, element_id, options, depth + 1)?);
+            }
+            SpannedValue::List(element_id, entries)
+        }
+        TagID::Compound => {
+            let mut entries: IndexMap<String, SpannedTag> = IndexMap::new();
+            loop {
+                let entry_id: TagID = read_tag_id(cursor)?;
+                if entry_id == TagID::End {
+                    break;
+                }
+                let name: String = read_name(cursor, options)?;
+                let entry: SpannedTag = read_spanned_tag(cursor, entry_id, options, depth + 1)?;
+                entries.insert(name, entry);
+            }
+            SpannedValue::Compound(entries)
+        }
+        _ => SpannedValue::Scalar(read_tag(cursor, &tag_id, options, depth)?),
+    };
+    Ok(SpannedTag {
+        span: start..cursor.position() as usize,
+        value,
+    })
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nbt, write};
+
+    #[test]
+    fn test_read_spanned() {
+        let tag: Tag = nbt!({ Data: { Time: 5i64, Name: "World" }, Pos: [1.0, 2.0] });
+        let mut data: Vec<u8> = write(&tag, "").unwrap();
+        let (root_name, spanned): (String, SpannedTag) =
+            read_spanned(&data, &ReadOptions::new()).unwrap();
+        assert_eq!(root_name, "");
+        assert_eq!(spanned.to_tag(), tag);
+        assert_eq!(spanned.span, 3..data.len());
+
+        let time: Range<usize> = spanned.get_path("Data.Time").unwrap().span.clone();
+        assert_eq!(time.len(), 8);
+        data[time].copy_from_slice(&99i64.to_be_bytes());
+        assert_eq!(
+            crate::read(&data).unwrap().tag.get_path("Data.Time"),
+            Some(&Tag::Long(99))
+        );
+
+        let pos: &SpannedTag = spanned.get_path("Pos[1]").unwrap();
+        assert_eq!(pos.span.len(), 8);
+        assert_eq!(pos.span.end, data.len() - 1);
+
+        let limited: ReadOptions = ReadOptions::new().max_array_length(1);
+        assert!(read_spanned(&data, &limited).is_err());
+        let ends: Vec<u8> = vec![0x09, 0, 0, 0x00, 0x7f, 0xff, 0xff, 0xff];
+        let error: Error = read_spanned(&ends, &ReadOptions::new()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+}
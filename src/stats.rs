@@ -0,0 +1,123 @@
+use crate::{encode_mutf8, PathSegment, Tag, TagID};
+use indexmap::IndexMap;
+
+/// Number of entries kept in [`TagStats::largest_arrays`].
+const LARGEST_ARRAYS: usize = 10;
+
+/// A size profile of a tag tree, as returned by [`Tag::stats`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagStats {
+    /// Number of tags of each type, including the root, in order of first appearance.
+    pub counts: IndexMap<TagID, usize>,
+    /// Encoded bytes of all string values and compound keys, excluding length prefixes.
+    pub string_bytes: usize,
+    /// The largest byte, int and long arrays and lists by encoded size, biggest first, as
+    /// `(path, bytes)`.
+    pub largest_arrays: Vec<(Vec<PathSegment>, usize)>,
+    /// Deepest nesting of compounds and lists. A scalar root has a depth of zero.
+    pub max_depth: usize,
+}
+
+impl Tag {
+    /// Counts tags by type and finds the largest arrays and the deepest nesting, to show
+    /// what makes a document big.
+    pub fn stats(&self) -> TagStats {
+        let mut stats: TagStats = TagStats::default();
+        collect_stats(self, &mut Vec::new(), 0, &mut stats);
+        stats
+    }
+
+    /// The exact number of bytes this tag's payload takes when written, excluding the tag
+    /// ID and name in front of it. A root document adds 3 bytes plus the encoded root name.
+    pub fn encoded_size(&self) -> usize {
+        match self {
+            Tag::End => 0,
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) | Tag::Float(_) => 4,
+            Tag::Long(_) | Tag::Double(_) => 8,
+            Tag::ByteArray(values) => 4 + values.len(),
+            Tag::String(value) => 2 + encode_mutf8(value).len(),
+            Tag::List(entries) => 5 + entries.iter().map(Tag::encoded_size).sum::<usize>(),
+            Tag::Compound(entries) => {
+                1 + entries
+                    .iter()
+                    .map(|(key, entry)| 3 + encode_mutf8(key).len() + entry.encoded_size())
+                    .sum::<usize>()
+            }
+            Tag::IntArray(values) => 4 + values.len() * 4,
+            Tag::LongArray(values) => 4 + values.len() * 8,
+            Tag::RawEncoded(raw) => raw.payload().len(),
+        }
+    }
+}
+
+fn collect_stats(tag: &Tag, path: &mut Vec<PathSegment>, depth: usize, stats: &mut TagStats) {
+    *stats.counts.entry(tag.id()).or_default() += 1;
+    match tag {
+        Tag::String(value) => stats.string_bytes += encode_mutf8(value).len(),
+        Tag::List(entries) => {
+            stats.max_depth = stats.max_depth.max(depth + 1);
+            record_array(tag, path, stats);
+            for (index, entry) in entries.iter().enumerate() {
+                path.push(PathSegment::Index(index));
+                collect_stats(entry, path, depth + 1, stats);
+                path.pop();
+            }
+        }
+        Tag::Compound(entries) => {
+            stats.max_depth = stats.max_depth.max(depth + 1);
+            for (key, entry) in entries {
+                stats.string_bytes += encode_mutf8(key).len();
+                path.push(PathSegment::Key(key.clone()));
+                collect_stats(entry, path, depth + 1, stats);
+                path.pop();
+            }
+        }
+        Tag::ByteArray(_) | Tag::IntArray(_) | Tag::LongArray(_) => record_array(tag, path, stats),
+        _ => {}
+    }
+}
+
+fn record_array(tag: &Tag, path: &[PathSegment], stats: &mut TagStats) {
+    let size: usize = tag.encoded_size();
+    let largest: &mut Vec<(Vec<PathSegment>, usize)> = &mut stats.largest_arrays;
+    if largest.len() == LARGEST_ARRAYS && largest.last().is_some_and(|(_, last)| *last >= size) {
+        return;
+    }
+    let index: usize = largest.partition_point(|(_, other)| *other >= size);
+    largest.insert(index, (path.to_vec(), size));
+    largest.truncate(LARGEST_ARRAYS);
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{format_path, read, write};
+
+    #[test]
+    fn test_stats() {
+        let data: Vec<u8> = std::fs::read("./test/bigtest_raw.nbt").unwrap();
+        let tag: Tag = read(&data).unwrap().tag;
+        assert_eq!(tag.encoded_size(), data.len() - 3 - "Level".len());
+        assert_eq!(
+            Tag::from("\0").encoded_size(),
+            write(&Tag::from("\0"), "").unwrap().len() - 3
+        );
+
+        let stats: TagStats = tag.stats();
+        assert_eq!(stats.counts[&TagID::Compound], 6);
+        assert_eq!(stats.counts[&TagID::Long], 8);
+        assert_eq!(stats.max_depth, 3);
+        let (path, size): &(Vec<PathSegment>, usize) = &stats.largest_arrays[0];
+        assert_eq!(format_path(path), "listTest (compound)");
+        assert_eq!(
+            *size,
+            tag.get_path(r#""listTest (compound)""#)
+                .unwrap()
+                .encoded_size()
+        );
+        assert_eq!(stats.largest_arrays.len(), 5);
+    }
+}
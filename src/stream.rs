@@ -0,0 +1,240 @@
+use crate::{
+    read_byte, read_byte_array, read_double, read_float, read_int, read_int_array,
+    read_list_header, read_long, read_long_array, read_name, read_short, read_tag_id, ByteArrayTag,
+    ByteTag, DoubleTag, FloatTag, IntArrayTag, IntTag, LongArrayTag, LongTag, ReadOptions,
+    RootName, ShortTag, StringTag, TagID,
+};
+use std::io::{Error, ErrorKind, Read, Result};
+
+/// A single step of a streamed NBT document.
+///
+/// Values inside compounds carry their key as `Some(name)`, as does the root tag. List
+/// elements have no name and carry `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtEvent {
+    CompoundStart(Option<String>),
+    CompoundEnd,
+    ListStart(Option<String>, TagID, usize),
+    ListEnd,
+    Byte(Option<String>, ByteTag),
+    Short(Option<String>, ShortTag),
+    Int(Option<String>, IntTag),
+    Long(Option<String>, LongTag),
+    Float(Option<String>, FloatTag),
+    Double(Option<String>, DoubleTag),
+    ByteArray(Option<String>, ByteArrayTag),
+    String(Option<String>, StringTag),
+    IntArray(Option<String>, IntArrayTag),
+    LongArray(Option<String>, LongArrayTag),
+}
+
+/// The container currently being read.
+enum Frame {
+    Compound,
+    List { tag_id: TagID, remaining: usize },
+}
+
+/// A pull parser that reads an NBT document from any `Read` as a sequence of events,
+/// without building a `Tag` tree. Only the current nesting path is kept in memory.
+///
+/// The depth, length and string limits of the [`ReadOptions`] apply as in [`crate::read_with`].
+/// Events carry plain strings, so [`crate::InvalidStringPolicy::Preserve`] decodes string
+/// values lossily, as it does for compound keys.
+pub struct NbtReader<R: Read> {
+    reader: R,
+    options: ReadOptions,
+    stack: Vec<Frame>,
+    started: bool,
+    finished: bool,
+}
+
+impl<R: Read> NbtReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::new_with(reader, &ReadOptions::default())
+    }
+
+    /// Creates a reader that applies the given options.
+    pub fn new_with(reader: R, options: &ReadOptions) -> Self {
+        NbtReader {
+            reader,
+            options: options.clone(),
+            stack: Vec::new(),
+            started: false,
+            finished: false,
+        }
+    }
+
+    /// Returns the current nesting depth, counting open compounds and lists.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads the next event, or `None` once the root tag has been fully read.
+    pub fn next_event(&mut self) -> Result<Option<NbtEvent>> {
+        if self.finished {
+            return Ok(None);
+        }
+        if !self.started {
+            self.started = true;
+            let tag_id: TagID = read_tag_id(&mut self.reader)?;
+            if tag_id == TagID::End {
+                self.finished = true;
+                return Ok(None);
+            }
+            let name: String = match self.options.root_name {
+                RootName::Named => read_name(&mut self.reader, &self.options)?,
+                RootName::Omitted => String::new(),
+            };
+            return self.read_value(tag_id, Some(name)).map(Some);
+        }
+        match self.stack.last_mut() {
+            None => {
+                self.finished = true;
+                Ok(None)
+            }
+            Some(Frame::Compound) => {
+                let tag_id: TagID = read_tag_id(&mut self.reader)?;
+                if tag_id == TagID::End {
+                    self.stack.pop();
+                    return Ok(Some(NbtEvent::CompoundEnd));
+                }
+                let name: String = read_name(&mut self.reader, &self.options)?;
+                self.read_value(tag_id, Some(name)).map(Some)
+            }
+            Some(Frame::List { remaining: 0, .. }) => {
+                self.stack.pop();
+                Ok(Some(NbtEvent::ListEnd))
+            }
+            Some(Frame::List { tag_id, remaining }) => {
+                *remaining -= 1;
+                let tag_id: TagID = *tag_id;
+                self.read_value(tag_id, None).map(Some)
+            }
+        }
+    }
+
+    fn read_value(&mut self, tag_id: TagID, name: Option<String>) -> Result<NbtEvent> {
+        let reader: &mut R = &mut self.reader;
+        let options: &ReadOptions = &self.options;
+        if matches!(tag_id, TagID::List | TagID::Compound) && self.stack.len() >= options.max_depth
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Nesting exceeds the depth limit of {}", options.max_depth),
+            ));
+        }
+        Ok(match tag_id {
+            TagID::End => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "List of End tags must be empty",
+                ))
+            }
+            TagID::Byte => NbtEvent::Byte(name, read_byte(reader)?),
+            TagID::Short => NbtEvent::Short(name, read_short(reader)?),
+            TagID::Int => NbtEvent::Int(name, read_int(reader)?),
+            TagID::Long => NbtEvent::Long(name, read_long(reader)?),
+            TagID::Float => NbtEvent::Float(name, read_float(reader)?),
+            TagID::Double => NbtEvent::Double(name, read_double(reader)?),
+            TagID::ByteArray => NbtEvent::ByteArray(name, read_byte_array(reader, options)?),
+            TagID::String => NbtEvent::String(name, read_name(reader, options)?),
+            TagID::List => {
+                let (element_id, length): (TagID, usize) = read_list_header(reader, options)?;
+                self.stack.push(Frame::List {
+                    tag_id: element_id,
+                    remaining: length,
+                });
+                NbtEvent::ListStart(name, element_id, length)
+            }
+            TagID::Compound => {
+                self.stack.push(Frame::Compound);
+                NbtEvent::CompoundStart(name)
+            }
+            TagID::IntArray => NbtEvent::IntArray(name, read_int_array(reader, options)?),
+            TagID::LongArray => NbtEvent::LongArray(name, read_long_array(reader, options)?),
+        })
+    }
+}
+
+impl<R: Read> Iterator for NbtReader<R> {
+    type Item = Result<NbtEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_event() {
+            Ok(event) => event.map(Ok),
+            Err(error) => {
+                self.finished = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nbt, write, Tag};
+    use std::fs;
+
+    #[test]
+    fn test_nbt_reader() {
+        let tag: Tag = nbt!({ a: 1i8, list: [{ s: "x" }, {}], empty: [], ints: [I; 7] });
+        let data: Vec<u8> = write(&tag, "root").unwrap();
+        let events: Vec<NbtEvent> = NbtReader::new(data.as_slice())
+            .collect::<Result<_>>()
+            .unwrap();
+        let name = |name: &str| Some(name.to_string());
+        assert_eq!(
+            events,
+            [
+                NbtEvent::CompoundStart(name("root")),
+                NbtEvent::Byte(name("a"), 1),
+                NbtEvent::ListStart(name("list"), TagID::Compound, 2),
+                NbtEvent::CompoundStart(None),
+                NbtEvent::String(name("s"), "x".to_string()),
+                NbtEvent::CompoundEnd,
+                NbtEvent::CompoundStart(None),
+                NbtEvent::CompoundEnd,
+                NbtEvent::ListEnd,
+                NbtEvent::ListStart(name("empty"), TagID::End, 0),
+                NbtEvent::ListEnd,
+                NbtEvent::IntArray(name("ints"), vec![7]),
+                NbtEvent::CompoundEnd,
+            ]
+        );
+
+        let data: Vec<u8> = fs::read("./test/bigtest_raw.nbt").unwrap();
+        let mut reader: NbtReader<&[u8]> = NbtReader::new(data.as_slice());
+        let mut count: usize = 0;
+        while reader.next_event().unwrap().is_some() {
+            count += 1;
+        }
+        assert!(count > 0);
+        assert!(reader.into_inner().is_empty());
+
+        let truncated: Vec<Result<NbtEvent>> = NbtReader::new(&data[..data.len() / 2]).collect();
+        assert!(truncated.last().unwrap().is_err());
+
+        let next = |data: &[u8], options: &ReadOptions| -> Vec<Result<NbtEvent>> {
+            NbtReader::new_with(data, options).collect()
+        };
+        let negative: [u8; 8] = [0x09, 0, 0, 0x01, 0xff, 0xff, 0xff, 0xff];
+        assert!(next(&negative, &ReadOptions::new())[0].is_err());
+        let end_list: [u8; 8] = [0x09, 0, 0, 0x00, 0x7f, 0xff, 0xff, 0xff];
+        assert!(next(&end_list, &ReadOptions::new())[0].is_err());
+        let nested: Vec<u8> = write(&nbt!({ a: { b: {} } }), "").unwrap();
+        assert!(next(&nested, &ReadOptions::new().max_depth(2))
+            .last()
+            .unwrap()
+            .is_err());
+        assert!(next(&nested, &ReadOptions::new().max_depth(3))
+            .iter()
+            .all(Result::is_ok));
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{
+    read_nbt_file, write_nbt_file, BlockState, CompoundTag, FileOptions, NbtDeserialize,
+    NbtSerialize,
+};
+use std::io::Result;
+use std::path::Path;
+
+/// A vanilla structure file, as saved by structure blocks into `generated/*/structures`.
+///
+/// Keys not modelled here are kept in the `extra` compounds, so saving a structure again
+/// doesn't lose them.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct Structure {
+    #[nbt(rename = "DataVersion")]
+    pub data_version: i32,
+    /// Width, height and length in blocks.
+    pub size: Vec<i32>,
+    /// The block states that `blocks` refer to. Absent when `palettes` is used instead.
+    pub palette: Option<Vec<BlockState>>,
+    /// Alternative palettes, one of which is picked at random when placing, as shipwrecks
+    /// do.
+    pub palettes: Option<Vec<Vec<BlockState>>>,
+    pub blocks: Vec<StructureBlock>,
+    pub entities: Vec<StructureEntity>,
+    #[nbt(extra)]
+    pub extra: CompoundTag,
+}
+
+/// A block in a structure, referring to its state by palette index.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct StructureBlock {
+    pub state: i32,
+    /// Position relative to the structure's origin.
+    pub pos: Vec<i32>,
+    /// Block entity data, without its position.
+    pub nbt: Option<CompoundTag>,
+    #[nbt(extra)]
+    pub extra: CompoundTag,
+}
+
+/// An entity in a structure.
+#[derive(NbtSerialize, NbtDeserialize, Debug, Clone, PartialEq)]
+pub struct StructureEntity {
+    /// Exact position relative to the structure's origin.
+    pub pos: Vec<f64>,
+    /// The block the entity is in, relative to the structure's origin.
+    #[nbt(rename = "blockPos")]
+    pub block_pos: Vec<i32>,
+    pub nbt: CompoundTag,
+    #[nbt(extra)]
+    pub extra: CompoundTag,
+}
+
+impl Structure {
+    /// Reads a structure file, detecting its compression.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Structure::from_nbt(&read_nbt_file(path, &FileOptions::new())?.tag)
+    }
+
+    /// Writes a gzip compressed structure file, as the game does.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_nbt_file(path, &self.to_nbt()?, "", &FileOptions::new())
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nbt, Tag};
+
+    #[test]
+    fn test_structure() {
+        let tag: Tag = nbt!({
+            DataVersion: 3465,
+            size: [2, 1, 1],
+            palette: [{ Name: "minecraft:chest", Properties: { facing: "north" } }],
+            blocks: [{
+                state: 0,
+                pos: [1, 0, 0],
+                nbt: { id: "minecraft:chest", Items: [] },
+                custom: 1i8,
+            }],
+            entities: [{
+                pos: [0.5, 0.0, 0.5],
+                blockPos: [0, 0, 0],
+                nbt: { id: "minecraft:armor_stand" },
+                custom: 2i8,
+            }],
+            author: "builder",
+        });
+        let structure: Structure = Structure::from_nbt(&tag).unwrap();
+        assert_eq!(structure.size, [2, 1, 1]);
+        assert_eq!(
+            structure.palette.as_ref().unwrap()[0].name,
+            "minecraft:chest"
+        );
+        assert_eq!(structure.entities[0].block_pos, [0, 0, 0]);
+        assert_eq!(structure.extra["author"], nbt!("builder"));
+        assert_eq!(structure.blocks[0].extra["custom"], nbt!(1i8));
+        assert_eq!(structure.entities[0].extra["custom"], nbt!(2i8));
+        assert_eq!(structure.to_nbt().unwrap(), tag);
+
+        // Structure files are gzip compressed
+        #[cfg(feature = "compression")]
+        {
+            use crate::{detect_compression, CompressionFormat};
+            use std::path::PathBuf;
+
+            let path: PathBuf = std::env::temp_dir().join("rust_nbt_test_structure.nbt");
+            structure.save(&path).unwrap();
+            assert_eq!(
+                detect_compression(&std::fs::read(&path).unwrap()),
+                Some(CompressionFormat::Gzip)
+            );
+            assert_eq!(Structure::load(&path).unwrap(), structure);
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+}
@@ -1,9 +1,17 @@
 use indexmap::IndexMap;
+use std::collections::HashSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::io::{Error, ErrorKind, Result};
 
 /// Represents an NBT tag type.
+///
+/// Equality follows the payload types: floats compare by their bits, so `NaN == NaN` and
+/// `0.0 != -0.0`, as they would once written out. Compounds compare their entries regardless
+/// of order, and a [`Tag::RawEncoded`] payload is only equal to another raw payload with
+/// identical bytes. This makes `Tag` `Eq`, and `Hash` is consistent with it, so tags can be
+/// used as map keys.
 #[repr(u8)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Tag {
     End,
     Byte(ByteTag),
@@ -18,6 +26,7 @@ pub enum Tag {
     Compound(CompoundTag),
     IntArray(IntArrayTag),
     LongArray(LongArrayTag),
+    RawEncoded(RawTag),
 }
 
 impl Tag {
@@ -36,6 +45,24 @@ impl Tag {
             Tag::Compound(_) => TagID::Compound,
             Tag::IntArray(_) => TagID::IntArray,
             Tag::LongArray(_) => TagID::LongArray,
+            Tag::RawEncoded(raw) => raw.id(),
+        }
+    }
+
+    /// Fills in keys missing from this compound with copies from `template`, recursing into
+    /// compounds present in both. Existing values are never overwritten, and nothing happens
+    /// unless both tags are compounds.
+    pub fn apply_defaults(&mut self, template: &Tag) {
+        let (Tag::Compound(compound), Tag::Compound(defaults)) = (self, template) else {
+            return;
+        };
+        for (name, default) in defaults {
+            match compound.get_mut(name) {
+                Some(entry) => entry.apply_defaults(default),
+                None => {
+                    compound.insert(name.clone(), default.clone());
+                }
+            }
         }
     }
 }
@@ -53,6 +80,373 @@ pub type CompoundTag = IndexMap<String, Tag>;
 pub type IntArrayTag = Vec<i32>;
 pub type LongArrayTag = Vec<i64>;
 
+impl PartialEq for Tag {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Tag::End, Tag::End) => true,
+            (Tag::Byte(a), Tag::Byte(b)) => a == b,
+            (Tag::Short(a), Tag::Short(b)) => a == b,
+            (Tag::Int(a), Tag::Int(b)) => a == b,
+            (Tag::Long(a), Tag::Long(b)) => a == b,
+            (Tag::Float(a), Tag::Float(b)) => a.to_bits() == b.to_bits(),
+            (Tag::Double(a), Tag::Double(b)) => a.to_bits() == b.to_bits(),
+            (Tag::ByteArray(a), Tag::ByteArray(b)) => a == b,
+            (Tag::String(a), Tag::String(b)) => a == b,
+            (Tag::List(a), Tag::List(b)) => a == b,
+            (Tag::Compound(a), Tag::Compound(b)) => a == b,
+            (Tag::IntArray(a), Tag::IntArray(b)) => a == b,
+            (Tag::LongArray(a), Tag::LongArray(b)) => a == b,
+            (Tag::RawEncoded(a), Tag::RawEncoded(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Tag {}
+
+impl Hash for Tag {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id().hash(state);
+        match self {
+            Tag::End => (),
+            Tag::Byte(value) => value.hash(state),
+            Tag::Short(value) => value.hash(state),
+            Tag::Int(value) => value.hash(state),
+            Tag::Long(value) => value.hash(state),
+            Tag::Float(value) => value.to_bits().hash(state),
+            Tag::Double(value) => value.to_bits().hash(state),
+            Tag::ByteArray(value) => value.hash(state),
+            Tag::String(value) => value.hash(state),
+            Tag::List(value) => value.hash(state),
+            Tag::Compound(value) => {
+                // IndexMap equality ignores order, so combine per-entry hashes commutatively.
+                let combined: u64 = value
+                    .iter()
+                    .map(|entry| {
+                        let mut hasher: DefaultHasher = DefaultHasher::new();
+                        entry.hash(&mut hasher);
+                        hasher.finish()
+                    })
+                    .fold(0, u64::wrapping_add);
+                value.len().hash(state);
+                combined.hash(state);
+            }
+            Tag::IntArray(value) => value.hash(state),
+            Tag::LongArray(value) => value.hash(state),
+            Tag::RawEncoded(raw) => raw.hash(state),
+        }
+    }
+}
+
+impl From<bool> for Tag {
+    fn from(value: bool) -> Self {
+        Tag::Byte(value as ByteTag)
+    }
+}
+
+impl From<ByteTag> for Tag {
+    fn from(value: ByteTag) -> Self {
+        Tag::Byte(value)
+    }
+}
+
+impl From<ShortTag> for Tag {
+    fn from(value: ShortTag) -> Self {
+        Tag::Short(value)
+    }
+}
+
+impl From<IntTag> for Tag {
+    fn from(value: IntTag) -> Self {
+        Tag::Int(value)
+    }
+}
+
+impl From<LongTag> for Tag {
+    fn from(value: LongTag) -> Self {
+        Tag::Long(value)
+    }
+}
+
+impl From<FloatTag> for Tag {
+    fn from(value: FloatTag) -> Self {
+        Tag::Float(value)
+    }
+}
+
+impl From<DoubleTag> for Tag {
+    fn from(value: DoubleTag) -> Self {
+        Tag::Double(value)
+    }
+}
+
+impl From<ByteArrayTag> for Tag {
+    fn from(value: ByteArrayTag) -> Self {
+        Tag::ByteArray(value)
+    }
+}
+
+impl From<StringTag> for Tag {
+    fn from(value: StringTag) -> Self {
+        Tag::String(value)
+    }
+}
+
+impl From<&str> for Tag {
+    fn from(value: &str) -> Self {
+        Tag::String(value.to_string())
+    }
+}
+
+impl From<ListTag<Tag>> for Tag {
+    fn from(value: ListTag<Tag>) -> Self {
+        Tag::List(value)
+    }
+}
+
+impl From<CompoundTag> for Tag {
+    fn from(value: CompoundTag) -> Self {
+        Tag::Compound(value)
+    }
+}
+
+impl From<IntArrayTag> for Tag {
+    fn from(value: IntArrayTag) -> Self {
+        Tag::IntArray(value)
+    }
+}
+
+impl From<LongArrayTag> for Tag {
+    fn from(value: LongArrayTag) -> Self {
+        Tag::LongArray(value)
+    }
+}
+
+impl From<RawTag> for Tag {
+    fn from(value: RawTag) -> Self {
+        Tag::RawEncoded(value)
+    }
+}
+
+/// Implements `TryFrom<Tag>` for a payload type, failing if the tag is another variant.
+macro_rules! impl_try_from_tag {
+    ($($variant:ident => $payload:ty),* $(,)?) => {
+        $(
+            impl TryFrom<Tag> for $payload {
+                type Error = Error;
+
+                fn try_from(tag: Tag) -> Result<Self> {
+                    match tag {
+                        Tag::$variant(value) => Ok(value),
+                        Tag::RawEncoded(raw) => <$payload>::try_from(raw.decode()?),
+                        tag => Err(unexpected_tag(TagID::$variant, &tag)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_tag! {
+    Byte => ByteTag,
+    Short => ShortTag,
+    Int => IntTag,
+    Long => LongTag,
+    Float => FloatTag,
+    Double => DoubleTag,
+    ByteArray => ByteArrayTag,
+    String => StringTag,
+    List => ListTag<Tag>,
+    Compound => CompoundTag,
+    IntArray => IntArrayTag,
+    LongArray => LongArrayTag,
+}
+
+impl TryFrom<Tag> for bool {
+    type Error = Error;
+
+    fn try_from(tag: Tag) -> Result<Self> {
+        Ok(ByteTag::try_from(tag)? != 0)
+    }
+}
+
+/// Builds the error for a tag that isn't of the expected type.
+pub fn unexpected_tag(expected: TagID, tag: &Tag) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("Expected {:?}, found {:?}", expected, tag.id()),
+    )
+}
+
+/// An already-encoded tag payload, spliced verbatim into the output when written.
+///
+/// This lets proxies and caches forward unchanged subtrees without a decode and re-encode.
+/// The payload excludes the tag ID and name, which come from the surrounding compound or list.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RawTag {
+    id: TagID,
+    payload: Vec<u8>,
+}
+
+impl RawTag {
+    /// Wraps a big-endian payload, checking that it decodes as exactly one tag of type `id`.
+    pub fn new(id: TagID, payload: Vec<u8>) -> Result<Self> {
+        if id == TagID::End {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Cannot splice an End tag",
+            ));
+        }
+        crate::read_payload(&payload, id)?;
+        Ok(RawTag { id, payload })
+    }
+
+    /// Wraps a payload that is known to be well-formed but may not decode, such as a string
+    /// kept as raw bytes by [`crate::InvalidStringPolicy::Preserve`].
+    pub(crate) fn from_parts_unchecked(id: TagID, payload: Vec<u8>) -> Self {
+        RawTag { id, payload }
+    }
+
+    /// Encodes a tag's payload up front so it can be reused across writes.
+    pub fn encode(tag: &Tag) -> Result<Self> {
+        if let Tag::RawEncoded(raw) = tag {
+            return Ok(raw.clone());
+        }
+        let payload: Vec<u8> = crate::write_payload(tag)?;
+        RawTag::new(tag.id(), payload)
+    }
+
+    pub fn id(&self) -> TagID {
+        self.id
+    }
+
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Decodes the payload back into a regular tag.
+    pub fn decode(&self) -> Result<Tag> {
+        crate::read_payload(&self.payload, self.id)
+    }
+
+    /// Decodes the payload, replacing invalid strings so the result can be displayed.
+    pub(crate) fn decode_lossy(&self) -> Result<Tag> {
+        let options: crate::ReadOptions =
+            crate::ReadOptions::new().invalid_strings(crate::InvalidStringPolicy::Lossy);
+        crate::read_payload_with(&self.payload, self.id, &options)
+    }
+}
+
+/// A root tag along with the name it was stored under, such as `"Level"` in `level.dat`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedTag {
+    pub name: String,
+    pub tag: Tag,
+}
+
+impl NamedTag {
+    pub fn new<S: Into<String>>(name: S, tag: Tag) -> Self {
+        NamedTag {
+            name: name.into(),
+            tag,
+        }
+    }
+}
+
+/// Extra operations on [`CompoundTag`], which is a plain `IndexMap` alias.
+pub trait CompoundTagExt {
+    /// Renames `old` to `new` in place, keeping the entry's position. An existing `new` entry
+    /// is replaced. Returns `false` if `old` isn't present.
+    fn rename_key(&mut self, old: &str, new: &str) -> bool;
+
+    /// Applies `renames` (old key to new key) to this compound and every compound nested in
+    /// it, including those inside lists. Returns the number of keys renamed.
+    ///
+    /// Every rename is looked up against the original keys, so swaps like `a` to `b` and `b`
+    /// to `a` exchange the values. An existing key that a rename targets is replaced.
+    fn rename_keys_recursive(&mut self, renames: &IndexMap<String, String>) -> usize;
+}
+
+impl CompoundTagExt for CompoundTag {
+    fn rename_key(&mut self, old: &str, new: &str) -> bool {
+        if !self.contains_key(old) {
+            return false;
+        }
+        if old != new {
+            self.shift_remove(new);
+        }
+        if let Some((index, _, value)) = self.shift_remove_full(old) {
+            self.shift_insert(index, new.to_string(), value);
+        }
+        true
+    }
+
+    fn rename_keys_recursive(&mut self, renames: &IndexMap<String, String>) -> usize {
+        let targets: HashSet<&String> = self.keys().filter_map(|key| renames.get(key)).collect();
+        let mut count: usize = 0;
+        let mut renamed: CompoundTag = CompoundTag::with_capacity(self.len());
+        for (key, value) in std::mem::take(self) {
+            match renames.get(&key) {
+                Some(new) => {
+                    renamed.insert(new.clone(), value);
+                    count += 1;
+                }
+                None if targets.contains(&key) => {}
+                None => {
+                    renamed.insert(key, value);
+                }
+            }
+        }
+        *self = renamed;
+        for entry in self.values_mut() {
+            count += rename_keys_in(entry, renames);
+        }
+        count
+    }
+}
+
+/// Extra operations on [`ListTag`], which is a plain `Vec` alias.
+pub trait ListTagExt {
+    /// Appends values that all share the list's element type, or the type of the first value
+    /// if the list is empty. On a mismatch nothing is appended and an error is returned.
+    fn extend_from_values<I, T>(&mut self, values: I) -> Result<()>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<Tag>;
+}
+
+impl ListTagExt for ListTag<Tag> {
+    fn extend_from_values<I, T>(&mut self, values: I) -> Result<()>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<Tag>,
+    {
+        let values: Vec<Tag> = values.into_iter().map(Into::into).collect();
+        if let Some(expected) = self.first().or(values.first()).map(Tag::id) {
+            if values.iter().any(|value| value.id() != expected) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("List elements must all be {:?}", expected),
+                ));
+            }
+        }
+        self.extend(values);
+        Ok(())
+    }
+}
+
+fn rename_keys_in(tag: &mut Tag, renames: &IndexMap<String, String>) -> usize {
+    match tag {
+        Tag::Compound(compound) => compound.rename_keys_recursive(renames),
+        Tag::List(list) => list
+            .iter_mut()
+            .map(|entry| rename_keys_in(entry, renames))
+            .sum(),
+        _ => 0,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TagID {
     End = 0,
     Byte,
@@ -87,7 +481,198 @@ impl TryFrom<u8> for TagID {
             10 => Ok(TagID::Compound),
             11 => Ok(TagID::IntArray),
             12 => Ok(TagID::LongArray),
-            _ => Err(Error::new(ErrorKind::InvalidData, "Unknown tag ID")),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Unknown tag ID 0x{:02X}", value),
+            )),
+        }
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_defaults() {
+        let mut game_rules: CompoundTag = IndexMap::new();
+        game_rules.insert(
+            "doDaylightCycle".to_string(),
+            Tag::String("false".to_string()),
+        );
+        let mut data: CompoundTag = IndexMap::new();
+        data.insert("GameRules".to_string(), Tag::Compound(game_rules));
+        let mut tag: Tag =
+            Tag::Compound(IndexMap::from([("Data".to_string(), Tag::Compound(data))]));
+
+        let mut default_rules: CompoundTag = IndexMap::new();
+        default_rules.insert(
+            "doDaylightCycle".to_string(),
+            Tag::String("true".to_string()),
+        );
+        default_rules.insert(
+            "keepInventory".to_string(),
+            Tag::String("false".to_string()),
+        );
+        let mut default_data: CompoundTag = IndexMap::new();
+        default_data.insert("GameRules".to_string(), Tag::Compound(default_rules));
+        default_data.insert("Difficulty".to_string(), Tag::Byte(2));
+        let template: Tag = Tag::Compound(IndexMap::from([(
+            "Data".to_string(),
+            Tag::Compound(default_data),
+        )]));
+
+        tag.apply_defaults(&template);
+
+        let Tag::Compound(root) = &tag else {
+            panic!("Root is not a compound");
+        };
+        let Some(Tag::Compound(data)) = root.get("Data") else {
+            panic!("Data is not a compound");
+        };
+        assert!(matches!(data.get("Difficulty"), Some(Tag::Byte(2))));
+        let Some(Tag::Compound(game_rules)) = data.get("GameRules") else {
+            panic!("GameRules is not a compound");
+        };
+        assert!(
+            matches!(game_rules.get("doDaylightCycle"), Some(Tag::String(value)) if value == "false")
+        );
+        assert!(
+            matches!(game_rules.get("keepInventory"), Some(Tag::String(value)) if value == "false")
+        );
+    }
+
+    #[test]
+    fn test_rename_key() {
+        let mut item: CompoundTag = IndexMap::new();
+        item.insert("id".to_string(), Tag::String("minecraft:stone".to_string()));
+        item.insert("Count".to_string(), Tag::Byte(1));
+        let mut compound: CompoundTag = IndexMap::new();
+        compound.insert("first".to_string(), Tag::Int(1));
+        compound.insert("Count".to_string(), Tag::Int(2));
+        compound.insert("Items".to_string(), Tag::List(vec![Tag::Compound(item)]));
+
+        assert!(compound.rename_key("first", "second"));
+        assert!(!compound.rename_key("missing", "other"));
+        let keys: Vec<&str> = compound.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["second", "Count", "Items"]);
+
+        let renames: IndexMap<String, String> =
+            IndexMap::from([("Count".to_string(), "count".to_string())]);
+        assert_eq!(compound.rename_keys_recursive(&renames), 2);
+        let keys: Vec<&str> = compound.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["second", "count", "Items"]);
+        let Some(Tag::List(items)) = compound.get("Items") else {
+            panic!("Items is not a list");
+        };
+        let Tag::Compound(item) = &items[0] else {
+            panic!("Item is not a compound");
+        };
+        let keys: Vec<&str> = item.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["id", "count"]);
+
+        let swaps: IndexMap<String, String> = IndexMap::from([
+            ("a".to_string(), "b".to_string()),
+            ("b".to_string(), "a".to_string()),
+        ]);
+        let mut swapped: CompoundTag = IndexMap::from([
+            ("a".to_string(), Tag::Int(1)),
+            ("b".to_string(), Tag::Int(2)),
+        ]);
+        assert_eq!(swapped.rename_keys_recursive(&swaps), 2);
+        assert_eq!(
+            swapped,
+            IndexMap::from([
+                ("b".to_string(), Tag::Int(1)),
+                ("a".to_string(), Tag::Int(2))
+            ])
+        );
+        assert_eq!(swapped.keys().collect::<Vec<_>>(), ["b", "a"]);
+    }
+
+    #[test]
+    fn test_raw_encoded() {
+        let mut section: CompoundTag = IndexMap::new();
+        section.insert("Y".to_string(), Tag::Byte(3));
+        section.insert("BlockLight".to_string(), Tag::ByteArray(vec![0; 8]));
+        let section: Tag = Tag::Compound(section);
+        let raw: RawTag = RawTag::encode(&section).expect("Encode failed");
+        assert_eq!(raw.id(), TagID::Compound);
+
+        let decoded: CompoundTag = IndexMap::from([("Section".to_string(), section.clone())]);
+        let spliced: CompoundTag =
+            IndexMap::from([("Section".to_string(), Tag::RawEncoded(raw.clone()))]);
+        assert_eq!(
+            crate::write(&Tag::Compound(spliced), "").expect("Write failed"),
+            crate::write(&Tag::Compound(decoded), "").expect("Write failed")
+        );
+        assert!(matches!(raw.decode(), Ok(Tag::Compound(_))));
+
+        assert!(RawTag::new(TagID::Int, vec![0, 0, 0]).is_err());
+        assert!(RawTag::new(TagID::Int, vec![0, 0, 0, 1, 0]).is_err());
+        assert!(RawTag::new(TagID::End, Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_bulk_insert() {
+        let mut list: ListTag<Tag> = Vec::with_capacity(3);
+        list.extend_from_values([Tag::Int(1), Tag::Int(2), Tag::Int(3)])
+            .expect("Extend failed");
+        assert!(list
+            .extend_from_values([Tag::Int(4), Tag::Long(5)])
+            .is_err());
+        assert_eq!(list.len(), 3);
+
+        let mut compound: CompoundTag = IndexMap::with_capacity(2);
+        compound.extend([
+            ("a".to_string(), Tag::Byte(1)),
+            ("b".to_string(), Tag::List(list)),
+        ]);
+        assert_eq!(compound.len(), 2);
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert!(matches!(Tag::from(5), Tag::Int(5)));
+        assert!(matches!(Tag::from(5i64), Tag::Long(5)));
+        assert!(matches!(Tag::from("five"), Tag::String(value) if value == "five"));
+        assert!(matches!(Tag::from(vec![5i64]), Tag::LongArray(value) if value == [5]));
+
+        assert_eq!(i32::try_from(Tag::Int(5)).unwrap(), 5);
+        assert_eq!(String::try_from(Tag::from("five")).unwrap(), "five");
+        assert_eq!(
+            LongArrayTag::try_from(Tag::LongArray(vec![5])).unwrap(),
+            [5]
+        );
+        assert!(bool::try_from(Tag::Byte(1)).unwrap());
+        let raw: Tag = Tag::RawEncoded(RawTag::encode(&Tag::Short(7)).unwrap());
+        assert_eq!(i16::try_from(raw).unwrap(), 7);
+
+        let error: Error = i32::try_from(Tag::Long(5)).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "Expected Int, found Long");
+    }
+
+    #[test]
+    fn test_equality_and_hash() {
+        fn hash_of(tag: &Tag) -> u64 {
+            let mut hasher: DefaultHasher = DefaultHasher::new();
+            tag.hash(&mut hasher);
+            hasher.finish()
         }
+
+        let a: Tag = crate::nbt!({ x: 1, y: [f64::NAN, 2.5], z: "text" });
+        let b: Tag = crate::nbt!({ z: "text", y: [f64::NAN, 2.5], x: 1 });
+        assert_eq!(a, b);
+        assert_eq!(hash_of(&a), hash_of(&b));
+        assert_eq!(a.clone(), a);
+        let keys: std::collections::HashSet<Tag> = [a.clone(), b].into_iter().collect();
+        assert_eq!(keys.len(), 1);
+
+        assert_ne!(a, crate::nbt!({ x: 1i64, y: [f64::NAN, 2.5], z: "text" }));
+        assert_ne!(Tag::Double(0.0), Tag::Double(-0.0));
+        assert_ne!(Tag::Int(1), Tag::Long(1));
+        assert_ne!(hash_of(&Tag::Int(1)), hash_of(&Tag::Long(1)));
     }
 }
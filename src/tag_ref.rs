@@ -0,0 +1,198 @@
+use crate::{
+    decode_mutf8, read_id, read_list_header_at, read_u16, take, take_array, CompoundTag,
+    ReadOptions, Tag, TagID,
+};
+use indexmap::IndexMap;
+use std::borrow::Cow;
+use std::io::{Error, ErrorKind, Result};
+
+/// A tag borrowed from an encoded buffer, avoiding allocations where the encoding allows.
+///
+/// Strings borrow from the input unless they need modified UTF-8 decoding. Arrays keep their
+/// raw big-endian bytes, since they can't be reinterpreted in place on little-endian hosts;
+/// [`TagRef::to_tag`] converts them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagRef<'a> {
+    End,
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(&'a [u8]),
+    String(Cow<'a, str>),
+    List(Vec<TagRef<'a>>),
+    Compound(IndexMap<Cow<'a, str>, TagRef<'a>>),
+    /// Big-endian `i32` values, four bytes each.
+    IntArray(&'a [u8]),
+    /// Big-endian `i64` values, eight bytes each.
+    LongArray(&'a [u8]),
+}
+
+impl TagRef<'_> {
+    pub fn id(&self) -> TagID {
+        match self {
+            TagRef::End => TagID::End,
+            TagRef::Byte(_) => TagID::Byte,
+            TagRef::Short(_) => TagID::Short,
+            TagRef::Int(_) => TagID::Int,
+            TagRef::Long(_) => TagID::Long,
+            TagRef::Float(_) => TagID::Float,
+            TagRef::Double(_) => TagID::Double,
+            TagRef::ByteArray(_) => TagID::ByteArray,
+            TagRef::String(_) => TagID::String,
+            TagRef::List(_) => TagID::List,
+            TagRef::Compound(_) => TagID::Compound,
+            TagRef::IntArray(_) => TagID::IntArray,
+            TagRef::LongArray(_) => TagID::LongArray,
+        }
+    }
+
+    /// Copies the borrowed data into an owned tag.
+    pub fn to_tag(&self) -> Tag {
+        match self {
+            TagRef::End => Tag::End,
+            TagRef::Byte(value) => Tag::Byte(*value),
+            TagRef::Short(value) => Tag::Short(*value),
+            TagRef::Int(value) => Tag::Int(*value),
+            TagRef::Long(value) => Tag::Long(*value),
+            TagRef::Float(value) => Tag::Float(*value),
+            TagRef::Double(value) => Tag::Double(*value),
+            TagRef::ByteArray(bytes) => Tag::ByteArray(bytes.iter().map(|b| *b as i8).collect()),
+            TagRef::String(value) => Tag::String(value.to_string()),
+            TagRef::List(entries) => Tag::List(entries.iter().map(TagRef::to_tag).collect()),
+            TagRef::Compound(entries) => {
+                let compound: CompoundTag = entries
+                    .iter()
+                    .map(|(key, entry)| (key.to_string(), entry.to_tag()))
+                    .collect();
+                Tag::Compound(compound)
+            }
+            TagRef::IntArray(bytes) => Tag::IntArray(
+                bytes
+                    .chunks_exact(4)
+                    .map(|chunk| i32::from_be_bytes(chunk.try_into().unwrap()))
+                    .collect(),
+            ),
+            TagRef::LongArray(bytes) => Tag::LongArray(
+                bytes
+                    .chunks_exact(8)
+                    .map(|chunk| i64::from_be_bytes(chunk.try_into().unwrap()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Reads an uncompressed NBT document without copying strings or arrays out of `data`.
+/// Returns the root name along with the root tag.
+pub fn read_ref(data: &[u8]) -> Result<(Cow<'_, str>, TagRef<'_>)> {
+    read_ref_with(data, &ReadOptions::default())
+}
+
+/// Like [`read_ref`], applying the depth and length limits of the given options.
+pub fn read_ref_with<'a>(
+    data: &'a [u8],
+    options: &ReadOptions,
+) -> Result<(Cow<'a, str>, TagRef<'a>)> {
+    let mut position: usize = 0;
+    let tag_id: TagID = read_id(data, &mut position)?;
+    let name: Cow<str> = read_str(data, &mut position)?;
+    let tag: TagRef = read_tag_ref(data, &mut position, tag_id, options, 1)?;
+    Ok((name, tag))
+}
+
+fn read_str<'a>(data: &'a [u8], position: &mut usize) -> Result<Cow<'a, str>> {
+    let length: usize = read_u16(data, position)? as usize;
+    let bytes: &[u8] = take(data, position, length)?;
+    match std::str::from_utf8(bytes) {
+        Ok(value) => Ok(Cow::Borrowed(value)),
+        Err(_) => Ok(Cow::Owned(decode_mutf8(bytes)?)),
+    }
+}
+
+fn read_tag_ref<'a>(
+    data: &'a [u8],
+    position: &mut usize,
+    tag_id: TagID,
+    options: &ReadOptions,
+    depth: usize,
+) -> Result<TagRef<'a>> {
+    let mut fixed = |size: usize| take(data, position, size);
+    Ok(match tag_id {
+        TagID::End => TagRef::End,
+        TagID::Byte => TagRef::Byte(fixed(1)?[0] as i8),
+        TagID::Short => TagRef::Short(i16::from_be_bytes(fixed(2)?.try_into().unwrap())),
+        TagID::Int => TagRef::Int(i32::from_be_bytes(fixed(4)?.try_into().unwrap())),
+        TagID::Long => TagRef::Long(i64::from_be_bytes(fixed(8)?.try_into().unwrap())),
+        TagID::Float => TagRef::Float(f32::from_be_bytes(fixed(4)?.try_into().unwrap())),
+        TagID::Double => TagRef::Double(f64::from_be_bytes(fixed(8)?.try_into().unwrap())),
+        TagID::ByteArray => TagRef::ByteArray(take_array(data, position, 1, options)?),
+        TagID::String => TagRef::String(read_str(data, position)?),
+        TagID::IntArray => TagRef::IntArray(take_array(data, position, 4, options)?),
+        TagID::LongArray => TagRef::LongArray(take_array(data, position, 8, options)?),
+        TagID::List | TagID::Compound if depth > options.max_depth => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Nesting exceeds the depth limit of {}", options.max_depth),
+            ));
+        }
+        TagID::List => {
+            let (element_id, length): (TagID, usize) =
+                read_list_header_at(data, position, options)?;
+            let mut entries: Vec<TagRef> = Vec::new();
+            for _ in 0..length {
+                entries.push(read_tag_ref(
+                    data,
+                    position,
+                    element_id,
+                    options,
+                    depth + 1,
+                )?);
+            }
+            TagRef::List(entries)
+        }
+        TagID::Compound => {
+            let mut entries: IndexMap<Cow<str>, TagRef> = IndexMap::new();
+            loop {
+                let entry_id: TagID = read_id(data, position)?;
+                if entry_id == TagID::End {
+                    break;
+                }
+                let name: Cow<str> = read_str(data, position)?;
+                let entry: TagRef = read_tag_ref(data, position, entry_id, options, depth + 1)?;
+                entries.insert(name, entry);
+            }
+            TagRef::Compound(entries)
+        }
+    })
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_ref() {
+        let data: Vec<u8> = std::fs::read("./test/bigtest_raw.nbt").unwrap();
+        let (name, tag): (Cow<str>, TagRef) = read_ref(&data).unwrap();
+        assert!(matches!(name, Cow::Borrowed("Level")));
+        assert_eq!(tag.to_tag(), crate::read(&data).unwrap().tag);
+
+        let TagRef::Compound(entries) = &tag else {
+            panic!("Root is not a compound");
+        };
+        assert!(matches!(
+            entries.get("stringTest"),
+            Some(TagRef::String(Cow::Borrowed(_)))
+        ));
+        assert!(read_ref(&data[..data.len() - 1]).is_err());
+
+        let limited: ReadOptions = ReadOptions::new().max_depth(1);
+        assert!(read_ref_with(&data, &limited).is_err());
+        let ends: Vec<u8> = vec![0x09, 0, 0, 0x00, 0x7f, 0xff, 0xff, 0xff];
+        assert_eq!(read_ref(&ends).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{unexpected_tag, Tag, TagID};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::io::{Error, ErrorKind, Result};
+
+/// Parses a JSON text component stored in a string tag, such as sign text, item names and
+/// book pages.
+///
+/// The tag holds the JSON source itself, so quotes inside the text are escaped twice when
+/// shown as SNBT: once for JSON and again for the SNBT string. The binary tag only carries
+/// the JSON layer, which is what gets undone here. Only JSON objects, arrays and strings are
+/// components; anything else, such as `42` or `true`, or text that isn't JSON at all, was
+/// written by very old versions and is returned as plain text.
+pub fn read_text_component(tag: &Tag) -> Result<Value> {
+    match tag {
+        Tag::String(source) => Ok(match serde_json::from_str(source) {
+            Ok(value @ (Value::Object(_) | Value::Array(_) | Value::String(_))) => value,
+            _ => Value::String(source.clone()),
+        }),
+        Tag::RawEncoded(raw) => read_text_component(&raw.decode()?),
+        tag => Err(unexpected_tag(TagID::String, tag)),
+    }
+}
+
+/// Parses a JSON text component into a typed structure.
+pub fn read_text_component_as<T: DeserializeOwned>(tag: &Tag) -> Result<T> {
+    serde_json::from_value(read_text_component(tag)?)
+        .map_err(|error| Error::new(ErrorKind::InvalidData, error))
+}
+
+/// Encodes a text component as the compact JSON string tag Minecraft expects.
+pub fn text_component<T: Serialize + ?Sized>(component: &T) -> Result<Tag> {
+    serde_json::to_string(component)
+        .map(Tag::String)
+        .map_err(|error| Error::new(ErrorKind::InvalidInput, error))
+}
+
+/// Flattens a text component to its plain text, following `extra` children and ignoring
+/// styling. Translated and other non-literal components contribute nothing.
+pub fn text_component_plain(component: &Value) -> String {
+    let mut text: String = String::new();
+    push_plain(component, &mut text);
+    text
+}
+
+fn push_plain(component: &Value, text: &mut String) {
+    match component {
+        Value::String(value) => text.push_str(value),
+        Value::Array(components) => components
+            .iter()
+            .for_each(|component| push_plain(component, text)),
+        Value::Object(fields) => {
+            if let Some(Value::String(value)) = fields.get("text") {
+                text.push_str(value);
+            }
+            if let Some(extra) = fields.get("extra") {
+                push_plain(extra, text);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt;
+    use serde_json::json;
+
+    #[test]
+    fn test_text_component() {
+        let sign: Tag =
+            nbt!({ Text1: r#"{"text":"Say \"hi\"","extra":[{"text":"!","bold":true}]}"# });
+        let component: Value = read_text_component(sign.get_path("Text1").unwrap()).unwrap();
+        assert_eq!(component["extra"][0]["bold"], json!(true));
+        assert_eq!(text_component_plain(&component), "Say \"hi\"!");
+
+        let tag: Tag = text_component(&component).unwrap();
+        assert_eq!(
+            tag,
+            Tag::from(r#"{"text":"Say \"hi\"","extra":[{"text":"!","bold":true}]}"#)
+        );
+        assert_eq!(read_text_component(&tag).unwrap(), component);
+
+        assert_eq!(
+            read_text_component(&Tag::from("legacy text")).unwrap(),
+            json!("legacy text")
+        );
+        for legacy in ["42", "true", "null"] {
+            assert_eq!(
+                read_text_component(&Tag::from(legacy)).unwrap(),
+                json!(legacy)
+            );
+        }
+        assert!(read_text_component_as::<Vec<Value>>(&tag).is_err());
+        assert!(read_text_component(&Tag::Int(0)).is_err());
+    }
+}
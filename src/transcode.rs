@@ -0,0 +1,216 @@
+use crate::{
+    json_to_tag, write_string, write_tag, write_tag_id, NbtEvent, NbtReader, NumberPolicy, Tag,
+    TagID,
+};
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::Value;
+use std::fmt;
+use std::io::{Read, Result, Write};
+
+/// Converts an uncompressed NBT document to JSON as it's read, without building a tree.
+///
+/// The output matches [`crate::tag_to_json`], but only the current nesting path and one
+/// value are held in memory at a time. The root name is dropped. Output is written in small
+/// pieces, so wrap unbuffered writers such as files in a `BufWriter`.
+pub fn transcode_nbt_to_json<R: Read, W: Write>(reader: R, mut writer: W) -> Result<()> {
+    // Whether each open container has had an entry written yet.
+    let mut started: Vec<bool> = Vec::new();
+    for event in NbtReader::new(reader) {
+        let event: NbtEvent = event?;
+        if let NbtEvent::CompoundEnd | NbtEvent::ListEnd = event {
+            started.pop();
+            writer.write_all(match event {
+                NbtEvent::CompoundEnd => b"}",
+                _ => b"]",
+            })?;
+            continue;
+        }
+        if let Some(started) = started.last_mut() {
+            if *started {
+                writer.write_all(b",")?;
+            }
+            *started = true;
+            if let Some(name) = event_name(&event) {
+                serde_json::to_writer(&mut writer, name)?;
+                writer.write_all(b":")?;
+            }
+        }
+        match event {
+            NbtEvent::CompoundStart(_) => {
+                started.push(false);
+                writer.write_all(b"{")?;
+            }
+            NbtEvent::ListStart(..) => {
+                started.push(false);
+                writer.write_all(b"[")?;
+            }
+            NbtEvent::Byte(_, value) => serde_json::to_writer(&mut writer, &value)?,
+            NbtEvent::Short(_, value) => serde_json::to_writer(&mut writer, &value)?,
+            NbtEvent::Int(_, value) => serde_json::to_writer(&mut writer, &value)?,
+            NbtEvent::Long(_, value) => serde_json::to_writer(&mut writer, &value)?,
+            NbtEvent::Float(_, value) => serde_json::to_writer(&mut writer, &f64::from(value))?,
+            NbtEvent::Double(_, value) => serde_json::to_writer(&mut writer, &value)?,
+            NbtEvent::ByteArray(_, value) => serde_json::to_writer(&mut writer, &value)?,
+            NbtEvent::String(_, value) => serde_json::to_writer(&mut writer, &value)?,
+            NbtEvent::IntArray(_, value) => serde_json::to_writer(&mut writer, &value)?,
+            NbtEvent::LongArray(_, value) => serde_json::to_writer(&mut writer, &value)?,
+            NbtEvent::CompoundEnd | NbtEvent::ListEnd => unreachable!(),
+        }
+    }
+    writer.flush()
+}
+
+fn event_name(event: &NbtEvent) -> Option<&str> {
+    match event {
+        NbtEvent::CompoundStart(name)
+        | NbtEvent::ListStart(name, ..)
+        | NbtEvent::Byte(name, _)
+        | NbtEvent::Short(name, _)
+        | NbtEvent::Int(name, _)
+        | NbtEvent::Long(name, _)
+        | NbtEvent::Float(name, _)
+        | NbtEvent::Double(name, _)
+        | NbtEvent::ByteArray(name, _)
+        | NbtEvent::String(name, _)
+        | NbtEvent::IntArray(name, _)
+        | NbtEvent::LongArray(name, _) => name.as_deref(),
+        NbtEvent::CompoundEnd | NbtEvent::ListEnd => None,
+    }
+}
+
+/// Converts a JSON document to uncompressed NBT with an empty root name, writing compounds
+/// as they're parsed.
+///
+/// The output matches [`json_to_tag`] with the same policy. Objects are streamed entry by
+/// entry, but each array is collected before it's written, since NBT lists need their
+/// element type and length up front.
+pub fn transcode_json_to_nbt<R: Read, W: Write>(
+    reader: R,
+    mut writer: W,
+    policy: NumberPolicy,
+) -> Result<()> {
+    let mut deserializer: serde_json::Deserializer<serde_json::de::IoRead<R>> =
+        serde_json::Deserializer::from_reader(reader);
+    EntrySeed {
+        writer: &mut writer,
+        name: "",
+        policy,
+    }
+    .deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    writer.flush()
+}
+
+/// Writes one named JSON value as an NBT entry.
+struct EntrySeed<'a, W: Write> {
+    writer: &'a mut W,
+    name: &'a str,
+    policy: NumberPolicy,
+}
+
+impl<W: Write> EntrySeed<'_, W> {
+    fn write_value<E: de::Error>(mut self, value: Value) -> std::result::Result<(), E> {
+        let tag: Tag = json_to_tag(&value, self.policy).map_err(E::custom)?;
+        self.write_header(tag.id())?;
+        write_tag(self.writer, &tag).map_err(E::custom)
+    }
+
+    fn write_header<E: de::Error>(&mut self, tag_id: TagID) -> std::result::Result<(), E> {
+        write_tag_id(self.writer, tag_id).map_err(E::custom)?;
+        write_string(self.writer, self.name).map_err(E::custom)
+    }
+}
+
+impl<'de, W: Write> DeserializeSeed<'de> for EntrySeed<'_, W> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, W: Write> Visitor<'de> for EntrySeed<'_, W> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> std::result::Result<(), E> {
+        self.write_value(Value::from(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> std::result::Result<(), E> {
+        self.write_value(Value::from(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> std::result::Result<(), E> {
+        self.write_value(Value::from(value))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> std::result::Result<(), E> {
+        self.write_value(Value::from(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> std::result::Result<(), E> {
+        self.write_value(Value::from(value))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> std::result::Result<(), E> {
+        self.write_value(Value::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        let mut values: Vec<Value> = Vec::new();
+        while let Some(value) = seq.next_element::<Value>()? {
+            values.push(value);
+        }
+        self.write_value(Value::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> std::result::Result<(), A::Error> {
+        self.write_header(TagID::Compound)?;
+        while let Some(key) = map.next_key::<String>()? {
+            map.next_value_seed(EntrySeed {
+                writer: &mut *self.writer,
+                name: &key,
+                policy: self.policy,
+            })?;
+        }
+        write_tag_id(self.writer, TagID::End).map_err(de::Error::custom)
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{read, tag_to_json};
+
+    #[test]
+    fn test_transcode_round_trip() {
+        let data: Vec<u8> = std::fs::read("./test/bigtest_raw.nbt").unwrap();
+        let tag: Tag = read(&data).unwrap().tag;
+
+        let mut json: Vec<u8> = Vec::new();
+        transcode_nbt_to_json(data.as_slice(), &mut json).unwrap();
+        let value: Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value, tag_to_json(&tag).unwrap());
+
+        let mut nbt: Vec<u8> = Vec::new();
+        transcode_json_to_nbt(json.as_slice(), &mut nbt, NumberPolicy::Narrowest).unwrap();
+        assert_eq!(
+            read(&nbt).unwrap().tag,
+            json_to_tag(&value, NumberPolicy::Narrowest).unwrap()
+        );
+
+        assert!(
+            transcode_json_to_nbt(&b"{\"a\": null}"[..], Vec::new(), NumberPolicy::default())
+                .is_err()
+        );
+        assert!(transcode_json_to_nbt(&b"{} {}"[..], Vec::new(), NumberPolicy::default()).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::{unexpected_tag, CompoundTag, Tag, TagID};
+use std::io::{Error, ErrorKind, Result};
+
+/// The ways Java Edition has stored UUIDs over the years.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UuidEncoding {
+    /// An `IntArray` of four ints, most significant first. Used since 1.16.
+    IntArray,
+    /// Two `Long` entries named `<key>Most` and `<key>Least`. Used before 1.16.
+    MostLeast,
+    /// A hyphenated hex `String`, as found in older owner and skull data.
+    String,
+}
+
+/// Encodes a UUID as an `IntArray` of four ints.
+pub fn uuid_to_int_array(uuid: u128) -> Tag {
+    Tag::IntArray(
+        (0..4)
+            .map(|index| (uuid >> (96 - index * 32)) as u32 as i32)
+            .collect(),
+    )
+}
+
+/// Encodes a UUID as a hyphenated hex string.
+pub fn uuid_to_string(uuid: u128) -> Tag {
+    Tag::String(format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        uuid >> 96,
+        uuid >> 80 & 0xffff,
+        uuid >> 64 & 0xffff,
+        uuid >> 48 & 0xffff,
+        uuid & 0xffff_ffff_ffff
+    ))
+}
+
+/// Splits a UUID into its most and least significant halves, as stored in `<key>Most` and
+/// `<key>Least`.
+pub fn uuid_to_most_least(uuid: u128) -> (i64, i64) {
+    ((uuid >> 64) as i64, uuid as i64)
+}
+
+/// Joins a UUID from its most and least significant halves.
+pub fn uuid_from_most_least(most: i64, least: i64) -> u128 {
+    (most as u64 as u128) << 64 | least as u64 as u128
+}
+
+impl Tag {
+    /// Reads a UUID stored as a four-int `IntArray` or a hyphenated hex `String`.
+    pub fn as_uuid(&self) -> Result<u128> {
+        let invalid = || Error::new(ErrorKind::InvalidData, "Invalid UUID");
+        match self {
+            Tag::IntArray(ints) => {
+                let [a, b, c, d] = ints[..] else {
+                    return Err(invalid());
+                };
+                Ok([a, b, c, d]
+                    .iter()
+                    .fold(0, |uuid, int| uuid << 32 | *int as u32 as u128))
+            }
+            Tag::String(value) => {
+                let groups: Vec<&str> = value.split('-').collect();
+                let lengths: Vec<usize> = groups.iter().map(|group| group.len()).collect();
+                if lengths != [8, 4, 4, 4, 12] {
+                    return Err(invalid());
+                }
+                u128::from_str_radix(&groups.concat(), 16).map_err(|_| invalid())
+            }
+            Tag::RawEncoded(raw) => raw.decode()?.as_uuid(),
+            tag => Err(unexpected_tag(TagID::IntArray, tag)),
+        }
+    }
+}
+
+/// Reads the UUID stored under `key` in any of the historical encodings, returning it along
+/// with the encoding that was found. `None` means no encoding of the key is present.
+pub fn read_uuid(compound: &CompoundTag, key: &str) -> Option<Result<(u128, UuidEncoding)>> {
+    if let Some(tag) = compound.get(key) {
+        let encoding: UuidEncoding = match tag.id() {
+            TagID::String => UuidEncoding::String,
+            _ => UuidEncoding::IntArray,
+        };
+        return Some(tag.as_uuid().map(|uuid| (uuid, encoding)));
+    }
+    let most: &Tag = compound.get(&format!("{}Most", key))?;
+    let least: &Tag = compound.get(&format!("{}Least", key))?;
+    Some(match (most, least) {
+        (Tag::Long(most), Tag::Long(least)) => {
+            Ok((uuid_from_most_least(*most, *least), UuidEncoding::MostLeast))
+        }
+        (Tag::Long(_), tag) | (tag, _) => Err(unexpected_tag(TagID::Long, tag)),
+    })
+}
+
+/// Stores a UUID under `key` in the given encoding, removing any other encodings of the same
+/// key so the compound doesn't end up with conflicting values.
+pub fn write_uuid(compound: &mut CompoundTag, key: &str, uuid: u128, encoding: UuidEncoding) {
+    let most_key: String = format!("{}Most", key);
+    let least_key: String = format!("{}Least", key);
+    match encoding {
+        UuidEncoding::IntArray | UuidEncoding::String => {
+            compound.shift_remove(&most_key);
+            compound.shift_remove(&least_key);
+            let tag: Tag = match encoding {
+                UuidEncoding::IntArray => uuid_to_int_array(uuid),
+                _ => uuid_to_string(uuid),
+            };
+            compound.insert(key.to_string(), tag);
+        }
+        UuidEncoding::MostLeast => {
+            compound.shift_remove(key);
+            let (most, least): (i64, i64) = uuid_to_most_least(uuid);
+            compound.insert(most_key, Tag::Long(most));
+            compound.insert(least_key, Tag::Long(least));
+        }
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<::uuid::Uuid> for Tag {
+    fn from(value: ::uuid::Uuid) -> Self {
+        uuid_to_int_array(value.as_u128())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl TryFrom<Tag> for ::uuid::Uuid {
+    type Error = Error;
+
+    fn try_from(tag: Tag) -> Result<Self> {
+        tag.as_uuid().map(::uuid::Uuid::from_u128)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl crate::NbtSerialize for ::uuid::Uuid {
+    fn to_nbt(&self) -> Result<Tag> {
+        Ok(Tag::from(*self))
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl crate::NbtDeserialize for ::uuid::Uuid {
+    fn from_nbt(tag: &Tag) -> Result<Self> {
+        tag.as_uuid().map(::uuid::Uuid::from_u128)
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt;
+
+    #[test]
+    fn test_uuid_encodings() {
+        let uuid: u128 = 0x069a79f4_44e9_4726_a5be_fca90e38aaf5;
+        assert_eq!(
+            uuid_to_int_array(uuid),
+            nbt!([I; 110787060, 1156138790, -1514210135, 238594805])
+        );
+        assert_eq!(
+            uuid_to_string(uuid),
+            Tag::from("069a79f4-44e9-4726-a5be-fca90e38aaf5")
+        );
+        assert_eq!(uuid_to_int_array(uuid).as_uuid().unwrap(), uuid);
+        assert_eq!(uuid_to_string(uuid).as_uuid().unwrap(), uuid);
+        assert!(nbt!([I; 1, 2, 3]).as_uuid().is_err());
+        assert!(nbt!("069a79f444e94726a5befca90e38aaf5").as_uuid().is_err());
+
+        let mut compound: CompoundTag = CompoundTag::new();
+        assert!(read_uuid(&compound, "Owner").is_none());
+        for encoding in [
+            UuidEncoding::MostLeast,
+            UuidEncoding::String,
+            UuidEncoding::IntArray,
+        ] {
+            write_uuid(&mut compound, "Owner", uuid, encoding);
+            assert_eq!(
+                read_uuid(&compound, "Owner").unwrap().unwrap(),
+                (uuid, encoding)
+            );
+        }
+        assert_eq!(compound.len(), 1);
+
+        #[cfg(feature = "uuid")]
+        {
+            let value: ::uuid::Uuid = ::uuid::Uuid::from_u128(uuid);
+            assert_eq!(::uuid::Uuid::try_from(Tag::from(value)).unwrap(), value);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{
+    compress, decompress, detect_compression, read, tag_to_typed_json, typed_json_to_tag,
+    write_with, CompressionFormat, WriteOptions,
+};
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::io::{Error, ErrorKind};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = JSON, js_name = parse)]
+    fn json_parse(text: &str) -> JsValue;
+    #[wasm_bindgen(js_namespace = JSON, js_name = stringify)]
+    fn json_stringify(value: &JsValue) -> JsValue;
+}
+
+/// Reads an NBT file into `{ name, compression, tag }`, for JavaScript callers. Build a
+/// `cdylib` that depends on this crate with the `wasm` feature to export it.
+///
+/// Tags cross the boundary as plain objects in the typed JSON layout of
+/// [`tag_to_typed_json`], so no type information is lost in either direction.
+///
+/// `options.compression` is `"gzip"`, `"zlib"`, `"lz4"` or `"none"`, and is detected from the
+/// data when left out. The `compression` returned is the one that was used, for passing
+/// back to [`write_nbt`].
+#[wasm_bindgen(js_name = readNbt)]
+pub fn read_nbt(bytes: &[u8], options: JsValue) -> Result<JsValue, JsValue> {
+    let options: Value = from_js(&options)?;
+    let compression: Option<CompressionFormat> = match options["compression"].as_str() {
+        None | Some("auto") => detect_compression(bytes),
+        Some(name) => parse_compression(name).map_err(to_js)?,
+    };
+    let data: Cow<[u8]> = match compression {
+        Some(format) => Cow::Owned(decompress(bytes, format).map_err(to_js)?),
+        None => Cow::Borrowed(bytes),
+    };
+    let named = read(&data).map_err(to_js)?;
+    to_js_value(&json!({
+        "name": named.name,
+        "compression": compression_name(compression),
+        "tag": tag_to_typed_json(&named.tag).map_err(to_js)?,
+    }))
+}
+
+/// Writes a tag in the typed JSON layout to an NBT file.
+///
+/// `options.name` sets the root name, empty by default, and `options.compression` takes the
+/// same values as in [`read_nbt`], defaulting to `"none"`.
+#[wasm_bindgen(js_name = writeNbt)]
+pub fn write_nbt(tag: JsValue, options: JsValue) -> Result<Vec<u8>, JsValue> {
+    let tag = typed_json_to_tag(&from_js(&tag)?).map_err(to_js)?;
+    let options: Value = from_js(&options)?;
+    let name: &str = options["name"].as_str().unwrap_or_default();
+    let data: Vec<u8> = write_with(&tag, &WriteOptions::new().name(name)).map_err(to_js)?;
+    match options["compression"].as_str() {
+        None => Ok(data),
+        Some(name) => match parse_compression(name).map_err(to_js)? {
+            Some(format) => compress(&data, format).map_err(to_js),
+            None => Ok(data),
+        },
+    }
+}
+
+fn parse_compression(name: &str) -> std::io::Result<Option<CompressionFormat>> {
+    Ok(match name {
+        "gzip" => Some(CompressionFormat::Gzip),
+        "zlib" => Some(CompressionFormat::Deflate),
+        "lz4" => Some(CompressionFormat::Lz4),
+        "none" => None,
+        other => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Unknown compression {:?}", other),
+            ))
+        }
+    })
+}
+
+fn compression_name(compression: Option<CompressionFormat>) -> &'static str {
+    match compression {
+        Some(CompressionFormat::Gzip) => "gzip",
+        Some(CompressionFormat::Deflate) => "zlib",
+        Some(CompressionFormat::DeflateRaw) => "deflate",
+        Some(CompressionFormat::Lz4) => "lz4",
+        None => "none",
+    }
+}
+
+/// Converts a JavaScript value to JSON, treating `undefined` as an empty object.
+fn from_js(value: &JsValue) -> Result<Value, JsValue> {
+    if value.is_undefined() || value.is_null() {
+        return Ok(Value::Object(Default::default()));
+    }
+    let text: String = json_stringify(value)
+        .as_string()
+        .ok_or_else(|| JsValue::from_str("Value can't be converted to JSON"))?;
+    serde_json::from_str(&text).map_err(|error| JsValue::from_str(&error.to_string()))
+}
+
+fn to_js_value(value: &Value) -> Result<JsValue, JsValue> {
+    Ok(json_parse(&value.to_string()))
+}
+
+fn to_js(error: Error) -> JsValue {
+    JsValue::from_str(&error.to_string())
+}
@@ -0,0 +1,158 @@
+use crate::{ChunkPos, Dimension, LevelDat, PlayerData, RegionFile, RegionPos, Tag};
+use std::fs::File;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+
+/// A Java Edition world folder, tying its `level.dat`, player files and region files together.
+///
+/// Only Java worlds are supported. Bedrock worlds keep their chunks in a LevelDB database,
+/// which this crate can't read, so opening one fails with [`ErrorKind::Unsupported`].
+///
+/// ```no_run
+/// use rust_nbt::{ChunkPos, Dimension, World};
+///
+/// let world: World = World::open("saves/New World")?;
+/// println!("{}", world.level_dat().data.level_name);
+/// let chunk = world.chunk(Dimension::Overworld, ChunkPos::new(0, 0))?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct World {
+    path: PathBuf,
+    level_dat: LevelDat,
+}
+
+impl World {
+    /// Opens a world folder by reading its `level.dat`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path: PathBuf = path.as_ref().to_path_buf();
+        let level_path: PathBuf = path.join("level.dat");
+        if path.join("db").is_dir() && path.join("levelname.txt").is_file() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "Bedrock worlds aren't supported",
+            ));
+        }
+        let level_dat: LevelDat = LevelDat::load(level_path)?;
+        Ok(World { path, level_dat })
+    }
+
+    /// The world folder.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The world's `level.dat`, as it was when the world was opened.
+    pub fn level_dat(&self) -> &LevelDat {
+        &self.level_dat
+    }
+
+    /// The folder holding a dimension's `region` directory.
+    pub fn dimension_path(&self, dimension: Dimension) -> PathBuf {
+        match dimension {
+            Dimension::Overworld => self.path.clone(),
+            Dimension::Nether => self.path.join("DIM-1"),
+            Dimension::End => self.path.join("DIM1"),
+        }
+    }
+
+    /// The dimensions that have a `region` directory.
+    pub fn dimensions(&self) -> Vec<Dimension> {
+        [Dimension::Overworld, Dimension::Nether, Dimension::End]
+            .into_iter()
+            .filter(|dimension| self.dimension_path(*dimension).join("region").is_dir())
+            .collect()
+    }
+
+    /// Reads a player's file from `playerdata`, by their hyphenated UUID, or returns `None` if
+    /// they have never joined.
+    pub fn player(&self, uuid: &str) -> Result<Option<PlayerData>> {
+        let path: PathBuf = self.path.join("playerdata").join(format!("{}.dat", uuid));
+        if !path.is_file() {
+            return Ok(None);
+        }
+        PlayerData::load(path).map(Some)
+    }
+
+    /// Opens a region file, or returns `None` if that region hasn't been generated.
+    pub fn region(&self, dimension: Dimension, pos: RegionPos) -> Result<Option<RegionFile<File>>> {
+        let path: PathBuf = self
+            .dimension_path(dimension)
+            .join("region")
+            .join(pos.file_name());
+        if !path.is_file() {
+            return Ok(None);
+        }
+        RegionFile::open_path(path).map(Some)
+    }
+
+    /// Reads a chunk, or returns `None` if it hasn't been generated.
+    pub fn chunk(&self, dimension: Dimension, pos: ChunkPos) -> Result<Option<Tag>> {
+        match self.region(dimension, pos.region())? {
+            Some(mut region) => region.chunk(pos.x, pos.z),
+            None => Ok(None),
+        }
+    }
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nbt, write_nbt_file, ChunkCompression, FileCompression, FileOptions};
+    use std::fs;
+
+    #[test]
+    fn test_world() {
+        let path: PathBuf = std::env::temp_dir().join("rust_nbt_test_world");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("playerdata")).unwrap();
+        fs::create_dir_all(path.join("DIM-1/region")).unwrap();
+        let options: FileOptions = FileOptions::new().compression(FileCompression::None);
+        let level: Tag = nbt!({
+            Data: {
+                LevelName: "Test",
+                SpawnX: 0,
+                SpawnY: 64,
+                SpawnZ: 0,
+                Time: 0i64,
+                LastPlayed: 0i64,
+            },
+        });
+        write_nbt_file(path.join("level.dat"), &level, "", &options).unwrap();
+        let uuid: &str = "00000000-0000-0000-0000-000000000001";
+        let player: Tag =
+            nbt!({ Pos: [0.5, 64.0, 0.5], Rotation: [0.0f32, 0.0f32], Inventory: [] });
+        write_nbt_file(
+            path.join("playerdata").join(format!("{}.dat", uuid)),
+            &player,
+            "",
+            &options,
+        )
+        .unwrap();
+        let pos: ChunkPos = ChunkPos::new(-1, 40);
+        let region_path: PathBuf = path.join("DIM-1/region").join(pos.region().file_name());
+        File::create(&region_path).unwrap();
+        RegionFile::open_path(&region_path)
+            .unwrap()
+            .set_chunk_with(pos.x, pos.z, &nbt!({ xPos: -1 }), ChunkCompression::Lz4)
+            .unwrap();
+
+        let world: World = World::open(&path).unwrap();
+        assert_eq!(world.level_dat().data.level_name, "Test");
+        assert_eq!(world.dimensions(), [Dimension::Nether]);
+        assert_eq!(world.player(uuid).unwrap().unwrap().pos, [0.5, 64.0, 0.5]);
+        assert!(world.player("missing").unwrap().is_none());
+        assert_eq!(
+            world.chunk(Dimension::Nether, pos).unwrap(),
+            Some(nbt!({ xPos: -1 }))
+        );
+        assert_eq!(world.chunk(Dimension::Overworld, pos).unwrap(), None);
+
+        fs::create_dir(path.join("db")).unwrap();
+        fs::write(path.join("levelname.txt"), "Test").unwrap();
+        let error: Error = World::open(&path).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Unsupported);
+        fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -1,21 +1,116 @@
 use crate::{
-    ByteArrayTag, ByteTag, CompoundTag, DoubleTag, FloatTag, IntArrayTag, IntTag, ListTag,
-    LongArrayTag, LongTag, ShortTag, StringTag, Tag, TagID,
+    compress_with_level, encode_mutf8, ByteArrayTag, ByteTag, CompoundTag, CompressionFormat,
+    DoubleTag, FloatTag, IntArrayTag, IntTag, ListTag, LongArrayTag, LongTag, RootName, ShortTag,
+    Tag, TagID, DEFAULT_COMPRESSION_LEVEL,
 };
-use std::io::{Cursor, Result, Write};
+use std::borrow::Cow;
+use std::io::{Cursor, Error, ErrorKind, Result, Write};
 
-/// Writes an NBT file to a byte vector, starting with the root compound tag.
+/// Options for [`write_with`].
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    /// The root tag's name, ignored when `root_name` is [`RootName::Omitted`].
+    pub name: String,
+    pub root_name: RootName,
+    /// Compression applied to the encoded document, if any.
+    pub compression: Option<CompressionFormat>,
+    pub compression_level: Option<u32>,
+}
+
+impl WriteOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn root_name(mut self, root_name: RootName) -> Self {
+        self.root_name = root_name;
+        self
+    }
+
+    pub fn compression(mut self, format: CompressionFormat) -> Self {
+        self.compression = Some(format);
+        self
+    }
+
+    pub fn compression_level(mut self, level: u32) -> Self {
+        self.compression_level = Some(level);
+        self
+    }
+}
+
+/// Writes an NBT file to a byte vector as configured, compressing it if requested.
+pub fn write_with(tag: &Tag, options: &WriteOptions) -> Result<Vec<u8>> {
+    let data: Vec<u8> = match options.root_name {
+        RootName::Named => write(tag, &options.name)?,
+        RootName::Omitted => write_nameless(tag)?,
+    };
+    match options.compression {
+        Some(format) => compress_with_level(
+            &data,
+            format,
+            options
+                .compression_level
+                .unwrap_or(DEFAULT_COMPRESSION_LEVEL),
+        ),
+        None => Ok(data),
+    }
+}
+
+/// Writes an NBT file to a byte vector, starting with the root compound tag. The vector is
+/// sized up front from [`Tag::encoded_size`], so it's never reallocated while writing.
 pub fn write(tag: &Tag, root_name: &str) -> Result<Vec<u8>> {
-    let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
-    write_tag_id(&mut cursor, tag.id())?;
-    write_unsigned_short(&mut cursor, root_name.len() as u16)?;
-    cursor.write_all(root_name.as_bytes())?;
+    let size: usize = 3 + encode_mutf8(root_name).len() + tag.encoded_size();
+    let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::with_capacity(size));
+    write_to(&mut cursor, tag, root_name)?;
+    Ok(cursor.into_inner())
+}
+
+/// Writes an NBT file directly to any writer, such as a file or socket, without buffering
+/// the whole document in memory. Wrap unbuffered writers in a `BufWriter`, since every value
+/// is written with a separate call.
+pub fn write_to<W: Write>(writer: &mut W, tag: &Tag, root_name: &str) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let started: std::time::Instant = std::time::Instant::now();
+    write_tag_id(writer, tag.id())?;
+    write_string(writer, root_name)?;
+    write_tag(writer, tag)?;
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        root_name,
+        bytes = 3 + encode_mutf8(root_name).len() + tag.encoded_size(),
+        elapsed_us = started.elapsed().as_micros() as u64,
+        "Wrote NBT document"
+    );
+    Ok(())
+}
+
+/// Writes a root tag without a name, as used by Java network NBT (1.20.2+).
+pub fn write_nameless(tag: &Tag) -> Result<Vec<u8>> {
+    let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::with_capacity(1 + tag.encoded_size()));
+    write_nameless_to(&mut cursor, tag)?;
+    Ok(cursor.into_inner())
+}
+
+/// Writes a root tag without a name directly to any writer.
+pub fn write_nameless_to<W: Write>(writer: &mut W, tag: &Tag) -> Result<()> {
+    write_tag_id(writer, tag.id())?;
+    write_tag(writer, tag)
+}
+
+/// Writes a single unnamed tag payload to a byte vector.
+pub(crate) fn write_payload(tag: &Tag) -> Result<Vec<u8>> {
+    let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::with_capacity(tag.encoded_size()));
     write_tag(&mut cursor, tag)?;
     Ok(cursor.into_inner())
 }
 
 /// Writes a single NBT tag to the given writer.
-fn write_tag<W: Write>(writer: &mut W, tag: &Tag) -> Result<()> {
+pub(crate) fn write_tag<W: Write>(writer: &mut W, tag: &Tag) -> Result<()> {
     match tag {
         Tag::End => Ok(()), // End tag has no payload.
         Tag::Byte(value) => write_byte(writer, *value),
@@ -30,10 +125,11 @@ fn write_tag<W: Write>(writer: &mut W, tag: &Tag) -> Result<()> {
         Tag::Compound(compound) => write_compound(writer, compound),
         Tag::IntArray(data) => write_int_array(writer, data),
         Tag::LongArray(data) => write_long_array(writer, data),
+        Tag::RawEncoded(raw) => writer.write_all(raw.payload()),
     }
 }
 
-fn write_tag_id<W: Write>(writer: &mut W, tag_id: TagID) -> Result<()> {
+pub(crate) fn write_tag_id<W: Write>(writer: &mut W, tag_id: TagID) -> Result<()> {
     let value: u8 = tag_id as u8;
     write_unsigned_byte(writer, value)
 }
@@ -72,19 +168,40 @@ fn write_double<W: Write>(writer: &mut W, value: DoubleTag) -> Result<()> {
 }
 
 fn write_byte_array<W: Write>(writer: &mut W, value: &ByteArrayTag) -> Result<()> {
+    write_array(writer, value, |entry| [entry as u8])
+}
+
+/// Number of array elements encoded into a buffer before it's handed to the writer.
+const ARRAY_BLOCK_LENGTH: usize = 4096;
+
+/// Writes a length-prefixed array of fixed-size elements, encoding them in blocks so the
+/// writer sees a few large writes rather than one per element.
+fn write_array<W: Write, T: Copy, const N: usize>(
+    writer: &mut W,
+    value: &[T],
+    convert: fn(T) -> [u8; N],
+) -> Result<()> {
     let length: IntTag = value.len() as i32;
     write_int(writer, length)?;
-    for entry in value {
-        write_byte(writer, *entry)?;
+    let mut buffer: Vec<u8> = Vec::with_capacity(value.len().min(ARRAY_BLOCK_LENGTH) * N);
+    for block in value.chunks(ARRAY_BLOCK_LENGTH) {
+        buffer.clear();
+        buffer.extend(block.iter().flat_map(|entry| convert(*entry)));
+        writer.write_all(&buffer)?;
     }
     Ok(())
 }
 
-fn write_string<W: Write>(writer: &mut W, value: &StringTag) -> Result<()> {
-    let entry: &[u8] = value.as_bytes();
-    let length: u16 = value.len() as u16;
+pub(crate) fn write_string<W: Write>(writer: &mut W, value: &str) -> Result<()> {
+    let entry: Cow<[u8]> = encode_mutf8(value);
+    let length: u16 = u16::try_from(entry.len()).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            "String is longer than 65535 bytes when encoded",
+        )
+    })?;
     write_unsigned_short(writer, length)?;
-    writer.write_all(entry)
+    writer.write_all(&entry)
 }
 
 fn write_list<W: Write>(writer: &mut W, value: &ListTag<Tag>) -> Result<()> {
@@ -114,19 +231,69 @@ fn write_compound<W: Write>(writer: &mut W, value: &CompoundTag) -> Result<()> {
 }
 
 fn write_int_array<W: Write>(writer: &mut W, value: &IntArrayTag) -> Result<()> {
-    let length: IntTag = value.len() as i32;
-    write_int(writer, length)?;
-    for entry in value {
-        write_int(writer, *entry)?;
-    }
-    Ok(())
+    write_array(writer, value, IntTag::to_be_bytes)
 }
 
 fn write_long_array<W: Write>(writer: &mut W, value: &LongArrayTag) -> Result<()> {
-    let length: IntTag = value.len() as i32;
-    write_int(writer, length)?;
-    for entry in value {
-        write_long(writer, *entry)?;
+    write_array(writer, value, LongTag::to_be_bytes)
+}
+
+// Test module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt;
+    use std::io::BufWriter;
+
+    #[test]
+    fn test_write_to() {
+        let tag: Tag = nbt!({ name: "Bananrama" });
+        let mut output: BufWriter<Vec<u8>> = BufWriter::new(Vec::new());
+        write_to(&mut output, &tag, "hello world").expect("Write failed");
+        let output: Vec<u8> = output.into_inner().expect("Flush failed");
+        assert_eq!(output, write(&tag, "hello world").expect("Write failed"));
+        assert_eq!(output, std::fs::read("./test/hello_world.nbt").unwrap());
+    }
+
+    #[test]
+    fn test_write_with() {
+        let tag: Tag = nbt!({ name: "Bananrama" });
+        let options: WriteOptions = WriteOptions::new().name("hello world");
+        assert_eq!(
+            write_with(&tag, &options).unwrap(),
+            std::fs::read("./test/hello_world.nbt").unwrap()
+        );
+
+        let options: WriteOptions = options.compression(CompressionFormat::Lz4);
+        let compressed: Vec<u8> = write_with(&tag, &options).unwrap();
+        let named: crate::NamedTag =
+            crate::read(&crate::decompress(&compressed, CompressionFormat::Lz4).unwrap()).unwrap();
+        assert_eq!(named, crate::NamedTag::new("hello world", tag.clone()));
+
+        let options: WriteOptions = WriteOptions::new().root_name(RootName::Omitted);
+        assert_eq!(
+            write_with(&tag, &options).unwrap(),
+            write_nameless(&tag).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_arrays() {
+        // Long enough to span several write blocks and read buffers.
+        let longs: LongArrayTag = (0..10_000).map(|index| index * -7_000_000_000).collect();
+        let tag: Tag = nbt!({
+            bytes: Tag::ByteArray(vec![-1, 0, 1]),
+            ints: Tag::IntArray((0..20_000).collect()),
+            longs: Tag::LongArray(longs.clone()),
+            empty: Tag::LongArray(Vec::new()),
+        });
+        let data: Vec<u8> = write(&tag, "").unwrap();
+        assert_eq!(&data[15..18], [0xff, 0x00, 0x01]);
+        assert_eq!(data.capacity(), data.len());
+        assert_eq!(crate::read(&data).unwrap().tag, tag);
+        let long_bytes: Vec<u8> = longs.iter().flat_map(|long| long.to_be_bytes()).collect();
+        assert!(data
+            .windows(long_bytes.len())
+            .any(|window| window == long_bytes));
     }
-    Ok(())
 }